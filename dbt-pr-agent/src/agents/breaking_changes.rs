@@ -0,0 +1,184 @@
+//! Detects which specific downstream models actually break when a column is
+//! removed or renamed, instead of flagging the whole downstream set as
+//! vaguely "affected".
+
+use crate::lineage::LineageGraph;
+use crate::severity::Severity;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Breakage {
+    /// The downstream model's SQL explicitly references the removed column.
+    WillBreak,
+    /// The downstream model does `SELECT *`, so we can't rule out a
+    /// reference to the removed column.
+    UnknownLikelyAffected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakingChange {
+    pub model: String,
+    pub column: String,
+    pub breakage: Breakage,
+    pub severity: Severity,
+}
+
+fn references_column(sql: &str, column: &str) -> bool {
+    sql.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|token| token.eq_ignore_ascii_case(column))
+}
+
+/// Scans each `(model, sql)` pair for a reference to `removed_column`,
+/// classifying `SELECT *` consumers as unknown-but-likely-affected rather
+/// than a confirmed breakage.
+pub fn detect_breaking_changes(
+    removed_column: &str,
+    downstream: &[(String, String)],
+) -> Vec<BreakingChange> {
+    downstream
+        .iter()
+        .filter_map(|(model, sql)| {
+            let lower = sql.to_ascii_lowercase();
+            if lower.contains("select *") {
+                Some(BreakingChange {
+                    model: model.clone(),
+                    column: removed_column.to_string(),
+                    breakage: Breakage::UnknownLikelyAffected,
+                    severity: Severity::Medium,
+                })
+            } else if references_column(sql, removed_column) {
+                Some(BreakingChange {
+                    model: model.clone(),
+                    column: removed_column.to_string(),
+                    breakage: Breakage::WillBreak,
+                    severity: Severity::Critical,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// A test or model in the base manifest that directly referenced a node
+/// removed by this PR, and will therefore fail outright once it's gone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedReference {
+    pub referencing_node: String,
+    pub removed_node: String,
+    pub severity: Severity,
+}
+
+/// For each `unique_id` in `removed_nodes` (see [`crate::lineage::GraphDiff::removed_nodes`]),
+/// finds every node in `base` — tests included, since a generic test
+/// (`unique_id` prefix `test.`) is just another node with a `depends_on`
+/// edge — that directly depended on it. Unlike column-level breakage
+/// ([`detect_breaking_changes`]), this needs no SQL-content inspection: a
+/// dependency on a node that no longer exists is always a break, so every
+/// result is flagged at [`Severity::Critical`].
+pub fn detect_orphaned_by_deletion(
+    base: &LineageGraph,
+    removed_nodes: &[String],
+) -> Vec<OrphanedReference> {
+    let mut orphans: Vec<OrphanedReference> = removed_nodes
+        .iter()
+        .flat_map(|removed| {
+            base.all_downstream_with_depth(removed)
+                .into_iter()
+                .filter(|(_, depth)| *depth == 1)
+                .map(move |(dependent, _)| OrphanedReference {
+                    referencing_node: dependent.to_string(),
+                    removed_node: removed.clone(),
+                    severity: Severity::Critical,
+                })
+        })
+        .collect();
+    orphans.sort_by(|a, b| {
+        (&a.referencing_node, &a.removed_node).cmp(&(&b.referencing_node, &b.removed_node))
+    });
+    orphans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::{Access, Materialization, ModelInfo};
+    use std::collections::HashMap;
+
+    fn model(id: &str, depends_on: &[&str]) -> ModelInfo {
+        ModelInfo {
+            unique_id: id.to_string(),
+            name: id.to_string(),
+            package_name: "trill_shop".to_string(),
+            materialized: Materialization::Table,
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            original_file_path: format!("models/{id}.sql"),
+            patch_path: None,
+            owner: None,
+            group: None,
+            access: Access::default(),
+            tags: Vec::new(),
+            meta: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn deleting_a_model_flags_the_two_tests_that_depended_on_it_as_critical() {
+        let base = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", &[]),
+            model(
+                "test.trill_shop.not_null_stg_orders_order_id",
+                &["model.trill_shop.stg_orders"],
+            ),
+            model(
+                "test.trill_shop.unique_stg_orders_order_id",
+                &["model.trill_shop.stg_orders"],
+            ),
+        ]);
+
+        let orphans =
+            detect_orphaned_by_deletion(&base, &["model.trill_shop.stg_orders".to_string()]);
+
+        assert_eq!(orphans.len(), 2);
+        assert!(orphans.iter().all(|o| o.severity == Severity::Critical));
+        assert!(orphans
+            .iter()
+            .any(|o| o.referencing_node == "test.trill_shop.not_null_stg_orders_order_id"));
+        assert!(orphans
+            .iter()
+            .any(|o| o.referencing_node == "test.trill_shop.unique_stg_orders_order_id"));
+    }
+
+    #[test]
+    fn deleting_a_model_nothing_depended_on_flags_nothing() {
+        let base = LineageGraph::from_models(vec![model("model.trill_shop.orphan", &[])]);
+
+        assert!(
+            detect_orphaned_by_deletion(&base, &["model.trill_shop.orphan".to_string()]).is_empty()
+        );
+    }
+
+    #[test]
+    fn flags_a_downstream_model_that_selects_the_removed_column() {
+        let downstream = vec![(
+            "orders_summary".to_string(),
+            "select order_id, customer_email from stg_orders".to_string(),
+        )];
+
+        let changes = detect_breaking_changes("customer_email", &downstream);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].breakage, Breakage::WillBreak);
+        assert_eq!(changes[0].severity, Severity::Critical);
+    }
+
+    #[test]
+    fn select_star_downstream_is_unknown_not_confirmed() {
+        let downstream = vec![(
+            "orders_wide".to_string(),
+            "select * from stg_orders".to_string(),
+        )];
+        let changes = detect_breaking_changes("customer_email", &downstream);
+        assert_eq!(changes[0].breakage, Breakage::UnknownLikelyAffected);
+    }
+}
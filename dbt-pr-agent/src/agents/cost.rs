@@ -0,0 +1,170 @@
+//! Cost-impact estimation for a materialization change.
+
+use crate::manifest::Materialization;
+use crate::warehouse::Warehouse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Snowflake credit-seconds per million rows for a full table build, on a
+/// generic small-warehouse assumption. This is a heuristic, not a billing
+/// API value: Snowflake's artifacts don't expose bytes-scanned, so anything
+/// derived from row counts alone is flagged `low_confidence`.
+const SNOWFLAKE_CREDIT_SECONDS_PER_MILLION_ROWS: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub model: String,
+    pub warehouse: Warehouse,
+    pub estimated_dollars: f64,
+    /// Set when the estimate is derived from a heuristic rather than an
+    /// artifact-reported byte/scan figure.
+    pub low_confidence: bool,
+    pub note: String,
+}
+
+/// Estimates the added warehouse cost of a model's materialization change.
+///
+/// Returns `None` when the change doesn't plausibly add cost (e.g. table to
+/// view, or unchanged materialization).
+pub fn analyze_cost_impact(
+    warehouse: &Warehouse,
+    model: &str,
+    before: Option<Materialization>,
+    after: Materialization,
+    row_count: Option<u64>,
+    credit_price_usd: f64,
+) -> Option<CostEstimate> {
+    let became_more_expensive =
+        !matches!(after, Materialization::View | Materialization::Ephemeral)
+            && before != Some(after);
+
+    if !became_more_expensive {
+        return None;
+    }
+
+    match warehouse {
+        Warehouse::Snowflake => {
+            let rows = row_count.unwrap_or(1_000_000);
+            let credit_seconds =
+                (rows as f64 / 1_000_000.0) * SNOWFLAKE_CREDIT_SECONDS_PER_MILLION_ROWS;
+            let estimated_dollars = (credit_seconds / 3600.0) * credit_price_usd;
+            Some(CostEstimate {
+                model: model.to_string(),
+                warehouse: warehouse.clone(),
+                estimated_dollars,
+                low_confidence: true,
+                note: "Snowflake artifacts don't expose bytes-scanned; estimate is a row-count heuristic.".to_string(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Aggregates a PR's per-model [`CostEstimate`]s into a single picture:
+/// the total estimated delta plus each contributing model's share, so a
+/// reviewer can see which model actually drives the cost instead of just
+/// an aggregate percentage.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostAnalysis {
+    pub total_estimated_dollars: f64,
+    pub per_model: HashMap<String, f64>,
+}
+
+impl CostAnalysis {
+    pub fn from_estimates(estimates: &[CostEstimate]) -> Self {
+        let per_model: HashMap<String, f64> = estimates
+            .iter()
+            .map(|e| (e.model.clone(), e.estimated_dollars))
+            .collect();
+        let total_estimated_dollars = per_model.values().sum();
+        Self {
+            total_estimated_dollars,
+            per_model,
+        }
+    }
+
+    /// `per_model`, highest estimated dollars first, capped at `n`.
+    pub fn top_contributors(&self, n: usize) -> Vec<(String, f64)> {
+        let mut contributors: Vec<(String, f64)> = self
+            .per_model
+            .iter()
+            .map(|(m, d)| (m.clone(), *d))
+            .collect();
+        contributors.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        contributors.truncate(n);
+        contributors
+    }
+
+    /// A one-line note naming the top cost contributor, for the report.
+    /// `None` when there's nothing to report.
+    pub fn top_contributor_note(&self) -> Option<String> {
+        let (model, dollars) = self.top_contributors(1).into_iter().next()?;
+        Some(format!(
+            "{model} accounts for the largest share of the estimated cost increase (${dollars:.2})"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_to_table_produces_a_nonzero_low_confidence_snowflake_estimate() {
+        let estimate = analyze_cost_impact(
+            &Warehouse::Snowflake,
+            "orders_summary",
+            Some(Materialization::View),
+            Materialization::Table,
+            Some(5_000_000),
+            2.0,
+        )
+        .expect("materializing a view as a table should produce an estimate");
+
+        assert!(estimate.estimated_dollars > 0.0);
+        assert!(estimate.low_confidence);
+    }
+
+    #[test]
+    fn the_dominant_models_materialization_change_tops_the_contributor_list() {
+        let estimates = vec![
+            analyze_cost_impact(
+                &Warehouse::Snowflake,
+                "orders_summary",
+                Some(Materialization::View),
+                Materialization::Table,
+                Some(50_000_000),
+                2.0,
+            )
+            .unwrap(),
+            analyze_cost_impact(
+                &Warehouse::Snowflake,
+                "stg_customers",
+                Some(Materialization::View),
+                Materialization::Table,
+                Some(100_000),
+                2.0,
+            )
+            .unwrap(),
+        ];
+
+        let analysis = CostAnalysis::from_estimates(&estimates);
+
+        assert_eq!(analysis.per_model.len(), 2);
+        assert_eq!(
+            analysis.total_estimated_dollars,
+            analysis.per_model.values().sum::<f64>()
+        );
+
+        let top = analysis.top_contributors(1);
+        assert_eq!(top[0].0, "orders_summary");
+        assert!(analysis
+            .top_contributor_note()
+            .unwrap()
+            .contains("orders_summary"));
+    }
+}
@@ -0,0 +1,331 @@
+//! The impact agent: asks an LLM to reason about the blast radius of a
+//! change, giving it on-demand access to the lineage graph via tool calls
+//! instead of dumping the whole DOT graph into the prompt up front.
+
+use crate::artifacts::SourceFreshness;
+use crate::cancellation::CancellationToken;
+use crate::lineage::LineageGraph;
+use crate::llm::{
+    AgentLlmSettings, LlmError, LlmProvider, LlmRequest, Message, ToolCall, ToolSpec,
+};
+use crate::severity::Severity;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Guards against a misbehaving model looping forever on tool calls.
+const MAX_ITERATIONS: usize = 8;
+
+fn tool_specs() -> Vec<ToolSpec> {
+    vec![
+        ToolSpec {
+            name: "get_downstream".to_string(),
+            description: "List the unique_ids directly and transitively downstream of a model"
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": { "model": { "type": "string" } },
+                "required": ["model"],
+            }),
+        },
+        ToolSpec {
+            name: "get_upstream".to_string(),
+            description: "List the unique_ids a model directly depends on".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": { "model": { "type": "string" } },
+                "required": ["model"],
+            }),
+        },
+        ToolSpec {
+            name: "get_model_sql".to_string(),
+            description: "Return the compiled SQL for a model".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": { "model": { "type": "string" } },
+                "required": ["model"],
+            }),
+        },
+    ]
+}
+
+/// Executes one tool call against `graph`/`get_sql`, returning the JSON
+/// string to feed back to the model as the tool's result.
+fn dispatch_tool_call(
+    call: &ToolCall,
+    graph: &LineageGraph,
+    get_sql: &dyn Fn(&str) -> Option<String>,
+) -> String {
+    let model = call
+        .arguments
+        .get("model")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+    match call.name.as_str() {
+        "get_downstream" => json!(graph.all_downstream(model)).to_string(),
+        "get_upstream" => {
+            let upstream: Vec<&str> = graph
+                .node(model)
+                .map(|n| n.depends_on.iter().map(|s| s.as_str()).collect())
+                .unwrap_or_default();
+            json!(upstream).to_string()
+        }
+        "get_model_sql" => json!(get_sql(model)).to_string(),
+        other => json!({ "error": format!("unknown tool: {other}") }).to_string(),
+    }
+}
+
+/// Runs the impact analysis tool-use loop and returns the model's final
+/// textual analysis.
+pub fn run_impact_agent(
+    provider: &dyn LlmProvider,
+    graph: &LineageGraph,
+    get_sql: &dyn Fn(&str) -> Option<String>,
+    changed_models: &[String],
+    settings: AgentLlmSettings,
+    cancellation: &CancellationToken,
+) -> Result<String, LlmError> {
+    let tools = tool_specs();
+    let mut messages = vec![
+        Message::system(
+            "You are a dbt impact analysis assistant. Use the provided tools to inspect \
+             lineage on demand, then summarize the blast radius of the changed models.",
+        ),
+        Message::user(format!("Changed models: {}", changed_models.join(", "))),
+    ];
+
+    for _ in 0..MAX_ITERATIONS {
+        if cancellation.is_cancelled() {
+            return Err(LlmError::Cancelled);
+        }
+
+        let request = LlmRequest {
+            messages: messages.clone(),
+            tools: tools.clone(),
+            temperature: settings.temperature,
+            max_tokens: settings.max_tokens,
+        };
+        let response = provider.complete(&request)?;
+
+        if response.tool_calls.is_empty() {
+            return Ok(response.content.unwrap_or_default());
+        }
+
+        for call in &response.tool_calls {
+            let result = dispatch_tool_call(call, graph, get_sql);
+            messages.push(Message::tool_result(call.id.clone(), result));
+        }
+    }
+
+    Err(LlmError::Request(format!(
+        "impact agent exceeded max tool-call iterations ({MAX_ITERATIONS})"
+    )))
+}
+
+/// A changed model that depends on a source currently reported stale (not a
+/// clean `pass`) by `dbt source freshness`. Rebuilding on stale source data
+/// is risky even when the model's own SQL didn't change, so this is
+/// deterministic and doesn't need the LLM tool-use loop above.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleSourceDependency {
+    pub model: String,
+    pub source: String,
+    /// The source's reported status (`warn`, `error`, `runtime error`).
+    pub status: String,
+    pub severity: Severity,
+}
+
+/// Cross-references `changed_models`' direct dependencies against
+/// `freshness`, flagging every changed model that depends on a source
+/// [`crate::artifacts::is_stale`] currently considers stale.
+pub fn detect_stale_source_dependencies(
+    graph: &LineageGraph,
+    changed_models: &[String],
+    freshness: &[SourceFreshness],
+) -> Vec<StaleSourceDependency> {
+    let stale_status_by_source: HashMap<&str, &str> = freshness
+        .iter()
+        .filter(|f| crate::artifacts::is_stale(f))
+        .map(|f| (f.unique_id.as_str(), f.status.as_str()))
+        .collect();
+
+    changed_models
+        .iter()
+        .filter_map(|model| graph.node(model).map(|node| (model, node)))
+        .flat_map(|(model, node)| {
+            let stale_status_by_source = &stale_status_by_source;
+            node.depends_on.iter().filter_map(move |dep| {
+                stale_status_by_source
+                    .get(dep.as_str())
+                    .map(|&status| StaleSourceDependency {
+                        model: model.clone(),
+                        source: dep.clone(),
+                        status: status.to_string(),
+                        severity: Severity::Medium,
+                    })
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{LlmResponse, MockProvider};
+    use crate::manifest::{Access, Materialization, ModelInfo};
+
+    fn model(id: &str, depends_on: &[&str]) -> ModelInfo {
+        ModelInfo {
+            unique_id: id.to_string(),
+            name: id.to_string(),
+            package_name: "trill_shop".to_string(),
+            materialized: Materialization::Table,
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            original_file_path: format!("models/{id}.sql"),
+            patch_path: None,
+            owner: None,
+            group: None,
+            access: Access::default(),
+            tags: Vec::new(),
+            meta: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn resolves_one_tool_call_then_a_final_answer() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.a", &[]),
+            model("model.trill_shop.b", &["model.trill_shop.a"]),
+        ]);
+
+        let provider = MockProvider::new(vec![
+            LlmResponse {
+                content: None,
+                tool_calls: vec![ToolCall {
+                    id: "call_1".to_string(),
+                    name: "get_downstream".to_string(),
+                    arguments: json!({ "model": "model.trill_shop.a" }),
+                }],
+            },
+            LlmResponse {
+                content: Some("Changing a affects b.".to_string()),
+                tool_calls: vec![],
+            },
+        ]);
+
+        let result = run_impact_agent(
+            &provider,
+            &graph,
+            &|_| None,
+            &["model.trill_shop.a".to_string()],
+            AgentLlmSettings::default(),
+            &CancellationToken::new(),
+        )
+        .expect("agent should resolve to a final answer");
+
+        assert_eq!(result, "Changing a affects b.");
+    }
+
+    #[test]
+    fn configured_temperature_reaches_the_request_sent_to_the_provider() {
+        let graph = LineageGraph::from_models(vec![model("model.trill_shop.a", &[])]);
+        let provider = MockProvider::new(vec![LlmResponse {
+            content: Some("ok".to_string()),
+            tool_calls: vec![],
+        }]);
+        let settings = AgentLlmSettings {
+            temperature: 0.05,
+            max_tokens: 512,
+        };
+
+        run_impact_agent(
+            &provider,
+            &graph,
+            &|_| None,
+            &["model.trill_shop.a".to_string()],
+            settings,
+            &CancellationToken::new(),
+        )
+        .expect("agent should resolve to a final answer");
+
+        let request = provider
+            .last_request()
+            .expect("provider should have received a request");
+        assert_eq!(request.temperature, 0.05);
+        assert_eq!(request.max_tokens, 512);
+    }
+
+    #[test]
+    fn cancelling_mid_analysis_returns_promptly_with_the_cancellation_error() {
+        let graph = LineageGraph::from_models(vec![model("model.trill_shop.a", &[])]);
+        let cancellation = CancellationToken::new();
+
+        // Only one scripted response: if the agent didn't check cancellation
+        // before its second provider call, it would fail with "ran out of
+        // scripted responses" instead of the expected Cancelled error.
+        let provider = MockProvider::new(vec![LlmResponse {
+            content: None,
+            tool_calls: vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "get_model_sql".to_string(),
+                arguments: json!({ "model": "model.trill_shop.a" }),
+            }],
+        }]);
+
+        let cancel_after_first_call = cancellation.clone();
+        let result = run_impact_agent(
+            &provider,
+            &graph,
+            &|_| {
+                cancel_after_first_call.cancel();
+                None
+            },
+            &["model.trill_shop.a".to_string()],
+            AgentLlmSettings::default(),
+            &cancellation,
+        );
+
+        assert!(matches!(result, Err(LlmError::Cancelled)));
+    }
+
+    #[test]
+    fn a_changed_model_depending_on_a_stale_source_is_flagged_medium() {
+        let graph = LineageGraph::from_models(vec![model(
+            "model.trill_shop.stg_orders",
+            &["source.trill_shop.raw.orders"],
+        )]);
+        let freshness = vec![SourceFreshness {
+            unique_id: "source.trill_shop.raw.orders".to_string(),
+            status: "error".to_string(),
+        }];
+
+        let findings = detect_stale_source_dependencies(
+            &graph,
+            &["model.trill_shop.stg_orders".to_string()],
+            &freshness,
+        );
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Medium);
+        assert_eq!(findings[0].source, "source.trill_shop.raw.orders");
+    }
+
+    #[test]
+    fn a_changed_model_depending_on_a_fresh_source_is_not_flagged() {
+        let graph = LineageGraph::from_models(vec![model(
+            "model.trill_shop.stg_orders",
+            &["source.trill_shop.raw.orders"],
+        )]);
+        let freshness = vec![SourceFreshness {
+            unique_id: "source.trill_shop.raw.orders".to_string(),
+            status: "pass".to_string(),
+        }];
+
+        assert!(detect_stale_source_dependencies(
+            &graph,
+            &["model.trill_shop.stg_orders".to_string()],
+            &freshness,
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,9 @@
+//! The individual review agents. Each agent inspects the PR from one angle
+//! (impact, performance, quality, ...) and produces its own findings; the
+//! orchestrator (not yet implemented) will run them and merge the results.
+
+pub mod breaking_changes;
+pub mod cost;
+pub mod impact;
+pub mod performance;
+pub mod quality;
@@ -0,0 +1,344 @@
+//! Deterministic, warehouse-specific SQL anti-pattern checks.
+//!
+//! These complement the LLM-driven parts of the performance agent: they run
+//! on the compiled SQL text of a changed model and don't depend on lineage,
+//! so they're cheap and have no false-negative risk from prompt truncation.
+
+use crate::agents::cost::{analyze_cost_impact, CostEstimate};
+use crate::artifacts::NodeConfig;
+use crate::manifest::Materialization;
+use crate::warehouse::Warehouse;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub mod history;
+
+/// A single performance suggestion for a changed model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OptimizationRecommendation {
+    pub model: String,
+    pub warehouse: Warehouse,
+    pub rule: String,
+    pub message: String,
+}
+
+/// One model's wall-clock execution time, as reported by `run_results.json`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ModelExecution {
+    pub unique_id: String,
+    pub execution_time: f64,
+}
+
+/// Extracts `(unique_id, execution_time)` for every result in a parsed
+/// `run_results.json`, independent of any single PR's diff.
+pub fn parse_run_results(run_results: &serde_json::Value) -> Vec<ModelExecution> {
+    let Some(results) = run_results
+        .get("results")
+        .and_then(serde_json::Value::as_array)
+    else {
+        return Vec::new();
+    };
+
+    results
+        .iter()
+        .filter_map(|r| {
+            let unique_id = r.get("unique_id")?.as_str()?.to_string();
+            let execution_time = r.get("execution_time")?.as_f64()?;
+            Some(ModelExecution {
+                unique_id,
+                execution_time,
+            })
+        })
+        .collect()
+}
+
+/// The `top` slowest executions, slowest first.
+pub fn rank_slowest(executions: &[ModelExecution], top: usize) -> Vec<&ModelExecution> {
+    let mut ranked: Vec<&ModelExecution> = executions.iter().collect();
+    ranked.sort_by(|a, b| b.execution_time.total_cmp(&a.execution_time));
+    ranked.truncate(top);
+    ranked
+}
+
+/// Runs the warehouse-specific advisor against the `top` slowest models,
+/// independent of any diff. `sql_by_model` maps a model's `unique_id` to its
+/// compiled SQL; models with no compiled SQL on hand are skipped.
+pub fn analyze_slow_models(
+    executions: &[ModelExecution],
+    sql_by_model: &HashMap<String, String>,
+    warehouse: &Warehouse,
+    top: usize,
+) -> Vec<OptimizationRecommendation> {
+    rank_slowest(executions, top)
+        .into_iter()
+        .filter_map(|execution| {
+            sql_by_model
+                .get(&execution.unique_id)
+                .map(|sql| (execution, sql))
+        })
+        .flat_map(|(execution, sql)| check_anti_patterns(warehouse, &execution.unique_id, sql))
+        .collect()
+}
+
+/// Runs every anti-pattern check applicable to `warehouse` against `sql`.
+pub fn check_anti_patterns(
+    warehouse: &Warehouse,
+    model: &str,
+    sql: &str,
+) -> Vec<OptimizationRecommendation> {
+    match warehouse {
+        Warehouse::BigQuery => check_bigquery(model, sql),
+        Warehouse::Snowflake => check_snowflake(model, sql),
+        Warehouse::Redshift => check_redshift(model, sql),
+        _ => Vec::new(),
+    }
+}
+
+/// The config key that changed between `base` and `head`, if any of the
+/// three that force dbt to do a full-table rebuild instead of an
+/// incremental merge on an already-incremental model's next run.
+fn changed_full_refresh_forcing_key(base: &NodeConfig, head: &NodeConfig) -> Option<&'static str> {
+    if base.partition_by != head.partition_by {
+        Some("partition_by")
+    } else if base.cluster_by != head.cluster_by {
+        Some("cluster_by")
+    } else if base.on_schema_change != head.on_schema_change {
+        Some("on_schema_change")
+    } else {
+        None
+    }
+}
+
+/// Flags a `partition_by`/`cluster_by`/`on_schema_change` edit on an
+/// already-incremental model, since dbt silently does a full-table rebuild
+/// on the next run instead of an incremental merge — expensive on a large
+/// table, and easy to miss in review since no row-producing SQL changed.
+/// Estimates the cost the same way a fresh materialization to `Table` would
+/// be estimated, since a forced full refresh rebuilds the whole table just
+/// like one.
+pub fn detect_full_refresh_forcing_change(
+    warehouse: &Warehouse,
+    model: &str,
+    base_config: &NodeConfig,
+    head_config: &NodeConfig,
+    row_count: Option<u64>,
+    credit_price_usd: f64,
+) -> Option<CostEstimate> {
+    let both_incremental = base_config.materialized.as_deref() == Some("incremental")
+        && head_config.materialized.as_deref() == Some("incremental");
+    if !both_incremental {
+        return None;
+    }
+
+    let key = changed_full_refresh_forcing_key(base_config, head_config)?;
+    let mut estimate = analyze_cost_impact(
+        warehouse,
+        model,
+        None,
+        Materialization::Table,
+        row_count,
+        credit_price_usd,
+    )?;
+    estimate.note = format!(
+        "changing '{key}' on an already-incremental model forces a full-refresh rebuild on the next run; {}",
+        estimate.note
+    );
+    Some(estimate)
+}
+
+fn contains_select_star(sql: &str) -> bool {
+    sql.to_ascii_lowercase().contains("select *")
+}
+
+fn check_bigquery(model: &str, sql: &str) -> Vec<OptimizationRecommendation> {
+    let mut out = Vec::new();
+    let lower = sql.to_ascii_lowercase();
+
+    if contains_select_star(sql) {
+        out.push(OptimizationRecommendation {
+            model: model.to_string(),
+            warehouse: Warehouse::BigQuery,
+            rule: "bq-select-star".to_string(),
+            message: "SELECT * scans every column and is billed on bytes scanned in BigQuery; select only the columns you need.".to_string(),
+        });
+    }
+
+    if lower.contains("where") && lower.contains("date(") && !lower.contains("_partitiontime") {
+        out.push(OptimizationRecommendation {
+            model: model.to_string(),
+            warehouse: Warehouse::BigQuery,
+            rule: "bq-non-partitioned-date-filter".to_string(),
+            message: "Wrapping a date column in DATE() in a WHERE clause prevents BigQuery from pruning partitions; filter on the raw partitioning column instead.".to_string(),
+        });
+    }
+
+    out
+}
+
+fn check_snowflake(model: &str, sql: &str) -> Vec<OptimizationRecommendation> {
+    let lower = sql.to_ascii_lowercase();
+    let mut out = Vec::new();
+
+    let looks_large = lower.contains("group by") || lower.contains("join");
+    if looks_large && !lower.contains("cluster by") {
+        out.push(OptimizationRecommendation {
+            model: model.to_string(),
+            warehouse: Warehouse::Snowflake,
+            rule: "sf-missing-cluster-by".to_string(),
+            message: "This model joins or aggregates but declares no CLUSTER BY; large Snowflake tables without clustering re-scan more micro-partitions than necessary.".to_string(),
+        });
+    }
+
+    out
+}
+
+fn check_redshift(model: &str, sql: &str) -> Vec<OptimizationRecommendation> {
+    let lower = sql.to_ascii_lowercase();
+    let mut out = Vec::new();
+
+    if lower.contains("distinct") {
+        out.push(OptimizationRecommendation {
+            model: model.to_string(),
+            warehouse: Warehouse::Redshift,
+            rule: "rs-distinct-high-cardinality".to_string(),
+            message: "DISTINCT on a high-cardinality column forces a full sort in Redshift; consider a GROUP BY with a pre-aggregated key or a bloom-filter-friendly approach.".to_string(),
+        });
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_select_star_on_bigquery() {
+        let recs = check_anti_patterns(
+            &Warehouse::BigQuery,
+            "stg_orders",
+            "select * from raw.orders",
+        );
+        assert!(recs.iter().any(|r| r.rule == "bq-select-star"));
+    }
+
+    #[test]
+    fn flags_missing_cluster_by_on_snowflake() {
+        let sql = "select customer_id, count(*) from orders group by customer_id";
+        let recs = check_anti_patterns(&Warehouse::Snowflake, "orders_by_customer", sql);
+        assert!(recs.iter().any(|r| r.rule == "sf-missing-cluster-by"));
+    }
+
+    #[test]
+    fn clean_snowflake_query_with_cluster_by_is_not_flagged() {
+        let sql =
+            "select customer_id, count(*) from orders group by customer_id cluster by customer_id";
+        let recs = check_anti_patterns(&Warehouse::Snowflake, "orders_by_customer", sql);
+        assert!(recs.is_empty());
+    }
+
+    #[test]
+    fn changing_partition_by_on_a_large_incremental_raises_a_cost_warning() {
+        let base = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            partition_by: Some(serde_json::json!({"field": "created_at"})),
+            ..Default::default()
+        };
+        let head = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            partition_by: Some(serde_json::json!({"field": "updated_at"})),
+            ..Default::default()
+        };
+
+        let estimate = detect_full_refresh_forcing_change(
+            &Warehouse::Snowflake,
+            "orders_summary",
+            &base,
+            &head,
+            Some(50_000_000),
+            2.0,
+        )
+        .expect("changing partition_by on an incremental model should raise a cost warning");
+
+        assert!(estimate.estimated_dollars > 0.0);
+        assert!(estimate.note.contains("partition_by"));
+    }
+
+    #[test]
+    fn an_unrelated_config_change_on_an_incremental_model_is_not_flagged() {
+        let base = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            unique_key: Some("order_id".to_string()),
+            ..Default::default()
+        };
+        let head = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            unique_key: Some("customer_id".to_string()),
+            ..Default::default()
+        };
+
+        assert!(detect_full_refresh_forcing_change(
+            &Warehouse::Snowflake,
+            "orders_summary",
+            &base,
+            &head,
+            Some(50_000_000),
+            2.0,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn a_model_that_was_not_already_incremental_is_not_flagged() {
+        let base = NodeConfig {
+            materialized: Some("view".to_string()),
+            ..Default::default()
+        };
+        let head = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            partition_by: Some(serde_json::json!({"field": "created_at"})),
+            ..Default::default()
+        };
+
+        assert!(detect_full_refresh_forcing_change(
+            &Warehouse::Snowflake,
+            "orders_summary",
+            &base,
+            &head,
+            Some(50_000_000),
+            2.0,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn slowest_model_is_analyzed_first_and_gets_a_recommendation() {
+        let executions = vec![
+            ModelExecution {
+                unique_id: "model.trill_shop.fast_model".to_string(),
+                execution_time: 1.0,
+            },
+            ModelExecution {
+                unique_id: "model.trill_shop.slow_model".to_string(),
+                execution_time: 120.0,
+            },
+        ];
+        let mut sql_by_model = HashMap::new();
+        sql_by_model.insert(
+            "model.trill_shop.fast_model".to_string(),
+            "select id from t".to_string(),
+        );
+        sql_by_model.insert(
+            "model.trill_shop.slow_model".to_string(),
+            "select * from raw.orders".to_string(),
+        );
+
+        let ranked = rank_slowest(&executions, 1);
+        assert_eq!(ranked[0].unique_id, "model.trill_shop.slow_model");
+
+        let recs = analyze_slow_models(&executions, &sql_by_model, &Warehouse::BigQuery, 1);
+        assert_eq!(recs.len(), 1);
+        assert_eq!(recs[0].model, "model.trill_shop.slow_model");
+        assert_eq!(recs[0].rule, "bq-select-star");
+    }
+}
@@ -0,0 +1,281 @@
+//! Persistent per-model execution-time history, ingested from
+//! `run_results.json` over successive runs, so slow-model detection can
+//! compare against a real baseline instead of only ranking within a single
+//! run.
+//!
+//! Like [`crate::llm::cache::DiskCache`], this is a plain JSON-backed store
+//! rather than a database (this crate has no database dependency): one JSON
+//! object per ingested execution, appended as a line to `path`
+//! (conventionally [`DEFAULT_HISTORY_PATH`]).
+
+use super::{ModelExecution, OptimizationRecommendation};
+use crate::warehouse::Warehouse;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Where [`ExecutionHistoryStore`] is conventionally kept within a project
+/// checkout, alongside other agent state.
+pub const DEFAULT_HISTORY_PATH: &str = ".dbt-pr-agent/history/executions.jsonl";
+
+/// One model's execution time as recorded at ingest time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub unique_id: String,
+    pub execution_time: f64,
+    pub recorded_at: SystemTime,
+}
+
+/// A JSON-lines file of [`HistoryEntry`] rows, one ingest appending one line
+/// per model. `path`'s parent directory and the file itself are created
+/// lazily on the first [`Self::ingest`]; reading a store that was never
+/// ingested into returns an empty history rather than an error.
+pub struct ExecutionHistoryStore {
+    path: PathBuf,
+}
+
+impl ExecutionHistoryStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Appends one entry per execution in `executions`, all timestamped
+    /// `now`.
+    pub fn ingest(&self, executions: &[ModelExecution], now: SystemTime) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        for execution in executions {
+            let entry = HistoryEntry {
+                unique_id: execution.unique_id.clone(),
+                execution_time: execution.execution_time,
+                recorded_at: now,
+            };
+            let line = serde_json::to_string(&entry).expect("HistoryEntry always serializes");
+            writeln!(file, "{line}")?;
+        }
+        Ok(())
+    }
+
+    /// Every recorded entry, in ingest order.
+    pub fn read_all(&self) -> std::io::Result<Vec<HistoryEntry>> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// The mean execution time recorded for `unique_id` across all history —
+    /// the baseline [`is_regression`] compares a new run's execution time
+    /// against. `None` when the model has no history yet.
+    pub fn baseline_for(&self, unique_id: &str) -> std::io::Result<Option<f64>> {
+        let matching: Vec<f64> = self
+            .read_all()?
+            .into_iter()
+            .filter(|e| e.unique_id == unique_id)
+            .map(|e| e.execution_time)
+            .collect();
+        if matching.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(matching.iter().sum::<f64>() / matching.len() as f64))
+    }
+}
+
+/// How far above its historical baseline a new execution time must be to
+/// count as a regression, expressed as a fraction of the baseline (`0.5`
+/// means "50% slower than baseline").
+pub const DEFAULT_REGRESSION_THRESHOLD: f64 = 0.5;
+
+/// True when `execution_time` exceeds `baseline` by more than `threshold`.
+/// A `baseline` of zero never regresses (there's nothing to divide by, and a
+/// historically-instant model going from 0s to anything isn't a meaningful
+/// percentage).
+pub fn is_regression(execution_time: f64, baseline: f64, threshold: f64) -> bool {
+    baseline > 0.0 && execution_time > baseline * (1.0 + threshold)
+}
+
+/// Flags every execution in `executions` that regressed against its
+/// historical baseline in `store` (see [`is_regression`]), independent of
+/// whether it also ranks among the `top` slowest in this run — a model can
+/// regress badly and still not be the single slowest in the project.
+/// A model with no recorded history yet is never flagged, since there's no
+/// baseline to compare against.
+pub fn detect_regressions(
+    store: &ExecutionHistoryStore,
+    executions: &[ModelExecution],
+    warehouse: &Warehouse,
+    threshold: f64,
+) -> std::io::Result<Vec<OptimizationRecommendation>> {
+    let mut out = Vec::new();
+    for execution in executions {
+        let Some(baseline) = store.baseline_for(&execution.unique_id)? else {
+            continue;
+        };
+        if is_regression(execution.execution_time, baseline, threshold) {
+            out.push(OptimizationRecommendation {
+                model: execution.unique_id.clone(),
+                warehouse: warehouse.clone(),
+                rule: "execution-time-regression".to_string(),
+                message: format!(
+                    "execution time {:.1}s is more than {:.0}% slower than its {:.1}s historical baseline",
+                    execution.execution_time,
+                    threshold * 100.0,
+                    baseline,
+                ),
+            });
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_history_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "dbt-pr-agent-history-test-{name}-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ))
+    }
+
+    fn execution(unique_id: &str, execution_time: f64) -> ModelExecution {
+        ModelExecution {
+            unique_id: unique_id.to_string(),
+            execution_time,
+        }
+    }
+
+    #[test]
+    fn reading_a_store_that_was_never_ingested_into_returns_an_empty_history() {
+        let store = ExecutionHistoryStore::new(temp_history_path("never-ingested"));
+
+        assert_eq!(store.read_all().unwrap(), Vec::new());
+        assert_eq!(store.baseline_for("model.trill_shop.orders").unwrap(), None);
+    }
+
+    #[test]
+    fn ingesting_creates_the_parent_directory_and_appends_one_line_per_execution() {
+        let path = temp_history_path("ingest-appends");
+        let _ = std::fs::remove_file(&path);
+        let store = ExecutionHistoryStore::new(&path);
+        let now = SystemTime::now();
+
+        store
+            .ingest(
+                &[execution("model.trill_shop.orders", 1.0), execution("model.trill_shop.customers", 2.0)],
+                now,
+            )
+            .unwrap();
+
+        let entries = store.read_all().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].unique_id, "model.trill_shop.orders");
+        assert_eq!(entries[1].unique_id, "model.trill_shop.customers");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn a_second_ingest_appends_rather_than_overwriting_the_first() {
+        let path = temp_history_path("ingest-twice");
+        let _ = std::fs::remove_file(&path);
+        let store = ExecutionHistoryStore::new(&path);
+        let now = SystemTime::now();
+
+        store.ingest(&[execution("model.trill_shop.orders", 1.0)], now).unwrap();
+        store.ingest(&[execution("model.trill_shop.orders", 3.0)], now).unwrap();
+
+        assert_eq!(store.read_all().unwrap().len(), 2);
+        assert_eq!(
+            store.baseline_for("model.trill_shop.orders").unwrap(),
+            Some(2.0)
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn baseline_for_averages_only_the_matching_models_history() {
+        let path = temp_history_path("baseline-filters-by-model");
+        let _ = std::fs::remove_file(&path);
+        let store = ExecutionHistoryStore::new(&path);
+        let now = SystemTime::now();
+
+        store
+            .ingest(
+                &[execution("model.trill_shop.orders", 10.0), execution("model.trill_shop.customers", 100.0)],
+                now,
+            )
+            .unwrap();
+
+        assert_eq!(
+            store.baseline_for("model.trill_shop.orders").unwrap(),
+            Some(10.0)
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn is_regression_flags_an_execution_more_than_the_threshold_slower_than_baseline() {
+        assert!(is_regression(16.0, 10.0, 0.5));
+        assert!(!is_regression(14.0, 10.0, 0.5));
+    }
+
+    #[test]
+    fn is_regression_never_fires_against_a_zero_baseline() {
+        assert!(!is_regression(5.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn detect_regressions_flags_only_the_model_that_exceeds_its_baseline() {
+        let path = temp_history_path("detect-regressions");
+        let _ = std::fs::remove_file(&path);
+        let store = ExecutionHistoryStore::new(&path);
+        let now = SystemTime::now();
+        store
+            .ingest(
+                &[execution("model.trill_shop.orders", 10.0), execution("model.trill_shop.customers", 10.0)],
+                now,
+            )
+            .unwrap();
+
+        let regressions = detect_regressions(
+            &store,
+            &[execution("model.trill_shop.orders", 20.0), execution("model.trill_shop.customers", 11.0)],
+            &Warehouse::BigQuery,
+            DEFAULT_REGRESSION_THRESHOLD,
+        )
+        .unwrap();
+
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].model, "model.trill_shop.orders");
+        assert_eq!(regressions[0].rule, "execution-time-regression");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn detect_regressions_skips_models_with_no_recorded_history() {
+        let store = ExecutionHistoryStore::new(temp_history_path("detect-regressions-no-history"));
+
+        let regressions = detect_regressions(
+            &store,
+            &[execution("model.trill_shop.orders", 999.0)],
+            &Warehouse::Snowflake,
+            DEFAULT_REGRESSION_THRESHOLD,
+        )
+        .unwrap();
+
+        assert!(regressions.is_empty());
+    }
+}
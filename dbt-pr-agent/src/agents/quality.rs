@@ -0,0 +1,1393 @@
+//! The quality agent: SQL/test/doc hygiene findings anchored to a file and,
+//! where possible, a specific line so they can be posted inline on the diff.
+
+pub mod junit;
+pub mod sarif;
+pub mod sql_rules;
+
+use crate::artifacts::{ManifestNode, NodeConfig};
+use crate::config::AgentKind;
+use crate::report::{Priority, Recommendation};
+use crate::severity::Severity;
+use crate::warehouse::Warehouse;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A single quality finding. `line_number` is only set when the issue can be
+/// anchored to a specific line of the changed file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityIssue {
+    pub file_path: String,
+    pub line_number: Option<u32>,
+    pub message: String,
+    pub severity: Severity,
+}
+
+/// Flags a model that just became `incremental` (per `base_config` vs
+/// `head_config`) but has no `unique_key`, which causes duplicate rows on
+/// the next merge/delete+insert run. `append` doesn't rebuild existing rows,
+/// so it doesn't need a `unique_key` and is exempt.
+pub fn detect_missing_unique_key(
+    model: &str,
+    file_path: &str,
+    base_config: Option<&NodeConfig>,
+    head_config: &NodeConfig,
+) -> Option<QualityIssue> {
+    let is_incremental = |c: &NodeConfig| c.materialized.as_deref() == Some("incremental");
+    let newly_incremental = is_incremental(head_config) && !base_config.is_some_and(is_incremental);
+    if !newly_incremental {
+        return None;
+    }
+
+    let strategy = head_config
+        .incremental_strategy
+        .as_deref()
+        .unwrap_or("merge");
+    if strategy == "append" || head_config.unique_key.is_some() {
+        return None;
+    }
+
+    Some(QualityIssue {
+        file_path: file_path.to_string(),
+        line_number: None,
+        message: format!(
+            "{model}: newly configured as incremental with strategy '{strategy}' but has no unique_key; \
+             this will produce duplicate rows on merge"
+        ),
+        severity: Severity::High,
+    })
+}
+
+/// The `incremental_strategy` values each warehouse actually supports.
+/// `None` means the warehouse is unrecognized ([`Warehouse::Other`]) and
+/// can't be validated, rather than "supports nothing".
+fn allowed_incremental_strategies(warehouse: &Warehouse) -> Option<&'static [&'static str]> {
+    match warehouse {
+        Warehouse::BigQuery => Some(&["merge", "insert_overwrite"]),
+        Warehouse::Snowflake => Some(&["merge", "delete+insert", "append"]),
+        Warehouse::Redshift => Some(&["merge", "delete+insert", "append"]),
+        Warehouse::Postgres => Some(&["append", "delete+insert"]),
+        Warehouse::Other(_) => None,
+    }
+}
+
+/// Flags an incremental model's `incremental_strategy` when it isn't one
+/// `warehouse` actually supports, since dbt only fails on that at `dbt run`
+/// time, well after this PR was reviewed and merged. Unset defaults to
+/// `merge`, dbt's own default across adapters.
+pub fn detect_unsupported_incremental_strategy(
+    warehouse: &Warehouse,
+    model: &str,
+    file_path: &str,
+    config: &NodeConfig,
+) -> Option<QualityIssue> {
+    if config.materialized.as_deref() != Some("incremental") {
+        return None;
+    }
+    let allowed = allowed_incremental_strategies(warehouse)?;
+    let strategy = config.incremental_strategy.as_deref().unwrap_or("merge");
+    if allowed.contains(&strategy) {
+        return None;
+    }
+
+    Some(QualityIssue {
+        file_path: file_path.to_string(),
+        line_number: None,
+        message: format!(
+            "{model}: incremental_strategy '{strategy}' is not supported on {warehouse}; \
+             supported strategies are {}",
+            allowed.join(", ")
+        ),
+        severity: Severity::High,
+    })
+}
+
+/// The generic dbt test types we distinguish for coverage scoring.
+///
+/// `not_null` on its own is the weakest possible signal (it says nothing
+/// about duplicates or referential integrity), so it's weighted far below
+/// the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestType {
+    Uniqueness,
+    NotNull,
+    Relationships,
+    AcceptedValues,
+    Custom,
+}
+
+impl TestType {
+    /// How much this test type contributes to a model's weighted coverage
+    /// score, relative to the other types.
+    fn weight(self) -> u32 {
+        match self {
+            TestType::NotNull => 1,
+            TestType::Custom => 1,
+            TestType::AcceptedValues => 2,
+            TestType::Relationships => 3,
+            TestType::Uniqueness => 3,
+        }
+    }
+
+    fn all() -> [TestType; 5] {
+        [
+            TestType::Uniqueness,
+            TestType::NotNull,
+            TestType::Relationships,
+            TestType::AcceptedValues,
+            TestType::Custom,
+        ]
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TestType::Uniqueness => "uniqueness",
+            TestType::NotNull => "not_null",
+            TestType::Relationships => "relationships",
+            TestType::AcceptedValues => "accepted_values",
+            TestType::Custom => "custom",
+        }
+    }
+}
+
+/// A quality-weighted view of one model's test coverage, as opposed to the
+/// coarse "has at least one test" presence check.
+#[derive(Debug, Clone)]
+pub struct ModelTestCoverage {
+    pub model: String,
+    pub present: HashSet<TestType>,
+    /// Weighted score, normalized to 0.0-1.0 against every known test type
+    /// being present.
+    pub score: f64,
+    /// True when `present` is non-empty but too weak to trust, e.g. a lone
+    /// `not_null`.
+    pub insufficient: bool,
+    /// Number of dbt `unit_test` nodes (dbt 1.8+) targeting this model,
+    /// counted separately from `present`/`score`: unit tests exercise a
+    /// model's SQL logic against fixture inputs rather than validating
+    /// already-materialized data, so they don't contribute to the data-test
+    /// weighting above.
+    pub unit_test_count: usize,
+}
+
+fn total_weight() -> u32 {
+    TestType::all().iter().map(|t| t.weight()).sum()
+}
+
+/// Classifies and scores each model's tests from `tests_by_model` (as parsed
+/// from the manifest's generic test nodes), flagging any model whose tests
+/// are present but score below `min_score` as insufficiently tested.
+/// `unit_test_counts` (see [`unit_tests_by_model`]) is carried through
+/// unweighted, for reporting only.
+pub fn validate_test_coverage(
+    tests_by_model: &HashMap<String, Vec<TestType>>,
+    unit_test_counts: &HashMap<String, usize>,
+    min_score: f64,
+) -> Vec<ModelTestCoverage> {
+    let max_weight = total_weight() as f64;
+
+    tests_by_model
+        .iter()
+        .map(|(model, tests)| {
+            let present: HashSet<TestType> = tests.iter().copied().collect();
+            let weight: u32 = present.iter().map(|t| t.weight()).sum();
+            let score = weight as f64 / max_weight;
+
+            ModelTestCoverage {
+                model: model.clone(),
+                insufficient: !present.is_empty() && score < min_score,
+                present,
+                score,
+                unit_test_count: unit_test_counts.get(model).copied().unwrap_or(0),
+            }
+        })
+        .collect()
+}
+
+/// Counts dbt `unit_test` manifest nodes (dbt 1.8+) by the `unique_id` of
+/// the model each one targets, from `depends_on.nodes` — a `unit_test` node
+/// depends on exactly the model it exercises. Distinct from generic/singular
+/// data tests, which validate already-materialized data rather than a
+/// model's SQL logic against fixture inputs.
+pub fn unit_tests_by_model(nodes: &[ManifestNode]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for node in nodes.iter().filter(|n| n.resource_type == "unit_test") {
+        if let Some(target) = node.depends_on.nodes.first() {
+            *counts.entry(target.clone()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Flags a changed model whose `JOIN` count piles up past
+/// [`MAX_JOINS_BEFORE_FLAGGING`] (the same complexity heuristic as
+/// [`detect_high_complexity`]) but has zero dbt `unit_test`s — a data test
+/// alone can't catch a logic bug in a model complex enough to need
+/// splitting, since it only validates the output of whatever the SQL
+/// happens to compute today.
+pub fn detect_missing_unit_test(
+    model: &str,
+    sql: &str,
+    unit_test_count: usize,
+) -> Option<Recommendation> {
+    if unit_test_count > 0 || count_joins(sql) <= MAX_JOINS_BEFORE_FLAGGING {
+        return None;
+    }
+
+    Some(Recommendation {
+        source: AgentKind::Quality,
+        message: format!(
+            "{model}: complex SQL with no unit_test coverage; add a unit_test to pin down its logic before the next refactor"
+        ),
+        priority: Priority::Medium,
+        confidence: None,
+    })
+}
+
+/// Models whose tests are present but classified as [`insufficient`](ModelTestCoverage::insufficient).
+pub fn insufficient_test_coverage(coverage: &[ModelTestCoverage]) -> Vec<&ModelTestCoverage> {
+    coverage.iter().filter(|c| c.insufficient).collect()
+}
+
+/// Whether `column` looks like a primary key by dbt naming convention: named
+/// exactly `id`, or ending in `_id`.
+fn looks_like_primary_key(column: &str) -> bool {
+    let column = column.to_ascii_lowercase();
+    column == "id" || column.ends_with("_id")
+}
+
+/// Flags changed models whose apparent primary key column(s) — by naming
+/// heuristic, cross-referenced against the manifest's existing tests to
+/// avoid false positives — lack both a `unique` and `not_null` test. This is
+/// one of the highest-value dbt tests and the most commonly forgotten on new
+/// models.
+pub fn detect_missing_pk_tests(
+    model: &str,
+    columns: &[String],
+    tests_by_column: &HashMap<String, Vec<TestType>>,
+) -> Vec<Recommendation> {
+    columns
+        .iter()
+        .filter(|column| looks_like_primary_key(column))
+        .filter(|column| {
+            let present: HashSet<TestType> =
+                tests_by_column.get(column.as_str()).into_iter().flatten().copied().collect();
+            !present.contains(&TestType::Uniqueness) || !present.contains(&TestType::NotNull)
+        })
+        .map(|column| Recommendation {
+            source: AgentKind::Quality,
+            message: format!("{model}.{column}: looks like a primary key but is missing a unique and/or not_null test"),
+            priority: Priority::High,
+            confidence: None,
+        })
+        .collect()
+}
+
+/// One recommendation per model with missing test types, naming exactly
+/// which types would raise its coverage score.
+pub fn missing_test_recommendations(coverage: &[ModelTestCoverage]) -> Vec<Recommendation> {
+    coverage
+        .iter()
+        .filter(|c| c.insufficient || c.present.is_empty())
+        .map(|c| {
+            let missing: Vec<&str> = TestType::all()
+                .iter()
+                .filter(|t| !c.present.contains(t))
+                .map(|t| t.name())
+                .collect();
+
+            Recommendation {
+                source: AgentKind::Quality,
+                message: format!(
+                    "{}: add {} test(s) to strengthen coverage",
+                    c.model,
+                    missing.join(", ")
+                ),
+                priority: if c.present.is_empty() {
+                    Priority::High
+                } else {
+                    Priority::Medium
+                },
+                confidence: None,
+            }
+        })
+        .collect()
+}
+
+/// Strips `--` line comments and `/* */` block comments so a `*` inside a
+/// comment can't be mistaken for a star-select.
+fn strip_sql_comments(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '-' && chars.peek() == Some(&'-') {
+            for nc in chars.by_ref() {
+                if nc == '\n' {
+                    out.push('\n');
+                    break;
+                }
+            }
+        } else if c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            while let Some(nc) = chars.next() {
+                if nc == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Collapses `sql` down to a form that's equal for two models with
+/// identical logic but cosmetic differences: comments stripped, lowercased,
+/// and runs of whitespace collapsed to a single space.
+fn normalize_sql(sql: &str) -> String {
+    let uncommented = strip_sql_comments(sql).to_ascii_lowercase();
+    uncommented.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A cheap fingerprint of a model's logic: two models with the same hash
+/// are byte-for-byte identical after [`normalize_sql`]. Used instead of
+/// comparing normalized strings pairwise so a changed model can be checked
+/// against every existing model in the manifest without re-normalizing (or
+/// holding onto) their full SQL text.
+fn hash_sql(normalized: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Flags `model` when its compiled SQL, once normalized, is an exact match
+/// for another model already in the manifest — a cheap, LLM-free stand-in
+/// for embeddings-based similarity that catches the common case teams
+/// actually hit: a model copy-pasted (and maybe cosmetically edited) from
+/// an existing one instead of referencing or extending it. Returns the
+/// first duplicate found; `nodes` should exclude `model` itself.
+pub fn detect_duplicate_model(
+    model: &str,
+    file_path: &str,
+    sql: &str,
+    nodes: &[ManifestNode],
+) -> Option<QualityIssue> {
+    let target_hash = hash_sql(&normalize_sql(sql));
+    let duplicate_of = nodes.iter().find(|n| {
+        n.unique_id != model
+            && n.compiled_code
+                .as_deref()
+                .is_some_and(|other_sql| hash_sql(&normalize_sql(other_sql)) == target_hash)
+    })?;
+
+    Some(QualityIssue {
+        file_path: file_path.to_string(),
+        line_number: None,
+        message: format!(
+            "{model}: logic is identical to '{}' after normalizing whitespace and comments; consider consolidating into one model",
+            duplicate_of.unique_id
+        ),
+        severity: Severity::Medium,
+    })
+}
+
+/// True when `sql` selects a bare or qualified star (`select *`, `select
+/// t.*`, BigQuery's `select * except (...)`) rather than a function call
+/// that merely takes `*` as an argument (`count(*)`, `any_value(*)`), which
+/// is distinguished by the `*` being immediately preceded by `(`.
+fn has_propagating_star(sql: &str) -> bool {
+    let cleaned = strip_sql_comments(sql);
+    let chars: Vec<char> = cleaned.chars().collect();
+    chars.iter().enumerate().any(|(i, &c)| {
+        if c != '*' {
+            return false;
+        }
+        chars[..i].iter().rev().find(|c| !c.is_whitespace()) != Some(&'(')
+    })
+}
+
+/// A `SELECT *` (or BigQuery `SELECT * EXCEPT (...)`) in a changed model
+/// means any upstream schema change silently propagates through it without
+/// this PR touching a single column. Flags the model and names the
+/// downstream models that would be affected, since those are the ones a
+/// reviewer needs to re-check after any future upstream schema change.
+pub fn detect_select_star_propagation(
+    model: &str,
+    sql: &str,
+    downstream: &[String],
+) -> Option<Recommendation> {
+    if !has_propagating_star(sql) {
+        return None;
+    }
+
+    let message = if downstream.is_empty() {
+        format!("{model}: uses SELECT * — any upstream schema change will silently propagate through it")
+    } else {
+        format!(
+            "{model}: uses SELECT * — any upstream schema change will silently propagate through it and \
+             its downstream consumer(s): {}",
+            downstream.join(", ")
+        )
+    };
+
+    Some(Recommendation {
+        source: AgentKind::Quality,
+        message,
+        priority: Priority::Medium,
+        confidence: None,
+    })
+}
+
+/// Note appended to every finding from [`analyze_new_model_file`], so a
+/// reviewer knows a finding came from SQL-only analysis of a model the
+/// manifest doesn't know about yet, not the full lineage-aware pipeline.
+const NEW_MODEL_NOTE: &str = "new model, not yet in the manifest — limited lineage analysis";
+
+/// Runs the deterministic, SQL-text-only checks against a model file with
+/// no manifest node — typically one added in this PR, which a
+/// base-branch-generated manifest has no entry for (see
+/// [`crate::manifest::discover_new_model_files`]) and which
+/// [`crate::manifest::discover_changed_models`] therefore drops, leaving it
+/// with zero analysis even though it's exactly the kind of change that most
+/// needs review. Checks that need a manifest node — downstream impact,
+/// `unique_key`/incremental-strategy config — are skipped, since there's no
+/// lineage or config for a model dbt hasn't parsed yet.
+pub fn analyze_new_model_file(model: &str, sql: &str) -> Vec<Recommendation> {
+    let mut findings = Vec::new();
+
+    if let Some(mut rec) = detect_select_star_propagation(model, sql, &[]) {
+        rec.message = format!("{} ({NEW_MODEL_NOTE})", rec.message);
+        findings.push(CategorizedRecommendation {
+            category: "select_star".to_string(),
+            recommendation: rec,
+        });
+    }
+    if let Some(mut rec) = detect_high_complexity(model, sql) {
+        rec.message = format!("{} ({NEW_MODEL_NOTE})", rec.message);
+        findings.push(CategorizedRecommendation {
+            category: "complexity".to_string(),
+            recommendation: rec,
+        });
+    }
+    for issue in sql_rules::lint(model, sql, &sql_rules::SqlLintConfig::default()) {
+        let recommendation = Recommendation {
+            source: AgentKind::Quality,
+            message: match issue.line_number {
+                Some(line) => format!("{model}:{line}: {} ({NEW_MODEL_NOTE})", issue.message),
+                None => format!("{model}: {} ({NEW_MODEL_NOTE})", issue.message),
+            },
+            priority: Priority::from_severity(issue.severity),
+            confidence: None,
+        };
+        findings.push(CategorizedRecommendation {
+            category: "sql_lint".to_string(),
+            recommendation,
+        });
+    }
+
+    let directives = parse_ignore_directives(sql);
+    let (kept, _suppressed) = apply_ignore_directives(findings, &directives);
+    kept
+}
+
+/// Heuristic complexity threshold: this many `JOIN`s in a single model is a
+/// strong signal it should be split into staging models, or at least
+/// deserves the reviewer's extra attention.
+const MAX_JOINS_BEFORE_FLAGGING: usize = 4;
+
+fn count_joins(sql: &str) -> usize {
+    let cleaned = strip_sql_comments(sql).to_ascii_lowercase();
+    cleaned
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| *word == "join")
+        .count()
+}
+
+/// Flags a model whose `JOIN` count piles up past [`MAX_JOINS_BEFORE_FLAGGING`],
+/// since a model like that is hard to review for correctness in a single pass.
+pub fn detect_high_complexity(model: &str, sql: &str) -> Option<Recommendation> {
+    let joins = count_joins(sql);
+    if joins <= MAX_JOINS_BEFORE_FLAGGING {
+        return None;
+    }
+
+    Some(Recommendation {
+        source: AgentKind::Quality,
+        message: format!(
+            "{model}: {joins} joins in a single model is hard to review in one pass; \
+             consider splitting into staging models"
+        ),
+        priority: Priority::Low,
+        confidence: None,
+    })
+}
+
+/// A [`Recommendation`] tagged with the category an ignore directive (see
+/// [`parse_ignore_directives`]) can target it by. `Recommendation` itself
+/// stays category-free since it's shared by every agent, not just the
+/// quality checks that support suppression.
+#[derive(Debug, Clone)]
+pub struct CategorizedRecommendation {
+    pub category: String,
+    pub recommendation: Recommendation,
+}
+
+/// A `-- dbt-pr-agent: ignore [category]` directive found in a model's SQL
+/// comments. `category: None` is a blanket ignore that suppresses every
+/// suppressible finding for the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreDirective {
+    pub category: Option<String>,
+}
+
+const IGNORE_MARKER: &str = "dbt-pr-agent: ignore";
+
+/// Returns the text of every `--` line comment and `/* */` block comment in
+/// `sql`, in source order. The inverse of [`strip_sql_comments`]: that
+/// function discards comment text, this one is only interested in it.
+fn extract_comments(sql: &str) -> Vec<String> {
+    let mut comments = Vec::new();
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '-' && chars.peek() == Some(&'-') {
+            chars.next();
+            let mut comment = String::new();
+            for nc in chars.by_ref() {
+                if nc == '\n' {
+                    break;
+                }
+                comment.push(nc);
+            }
+            comments.push(comment);
+        } else if c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            let mut comment = String::new();
+            while let Some(nc) = chars.next() {
+                if nc == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    break;
+                }
+                comment.push(nc);
+            }
+            comments.push(comment);
+        }
+    }
+    comments
+}
+
+/// Parses every [`IgnoreDirective`] out of `sql`'s comments, like a lint
+/// tool's inline suppression comment, so a reviewer can mark a specific,
+/// already-triaged finding as accepted without touching agent config.
+pub fn parse_ignore_directives(sql: &str) -> Vec<IgnoreDirective> {
+    extract_comments(sql)
+        .iter()
+        .filter_map(|comment| {
+            let rest = comment.trim().strip_prefix(IGNORE_MARKER)?;
+            let category = rest.trim();
+            Some(IgnoreDirective {
+                category: if category.is_empty() {
+                    None
+                } else {
+                    Some(category.to_ascii_lowercase())
+                },
+            })
+        })
+        .collect()
+}
+
+/// Filters `findings` against `directives`, dropping any finding covered by
+/// a blanket ignore or whose category matches an `ignore <category>`
+/// directive. Returns the surviving recommendations plus how many were
+/// suppressed, so the report can state "N findings suppressed by directive"
+/// instead of a finding just silently vanishing.
+pub fn apply_ignore_directives(
+    findings: Vec<CategorizedRecommendation>,
+    directives: &[IgnoreDirective],
+) -> (Vec<Recommendation>, usize) {
+    let blanket = directives.iter().any(|d| d.category.is_none());
+    let ignored_categories: HashSet<&str> = directives
+        .iter()
+        .filter_map(|d| d.category.as_deref())
+        .collect();
+
+    let mut suppressed = 0;
+    let mut kept = Vec::new();
+    for finding in findings {
+        if blanket || ignored_categories.contains(finding.category.as_str()) {
+            suppressed += 1;
+        } else {
+            kept.push(finding.recommendation);
+        }
+    }
+    (kept, suppressed)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SchemaYml {
+    #[serde(default)]
+    models: Vec<SchemaModel>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SchemaModel {
+    name: String,
+    #[serde(default)]
+    columns: Vec<SchemaColumn>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SchemaColumn {
+    name: String,
+    #[serde(default)]
+    tests: Vec<serde_yaml::Value>,
+}
+
+/// A test entry in dbt's `schema.yml` is either a bare name (`unique`) or a
+/// single-key mapping carrying its arguments (`relationships: {to: ..., field: ...}`).
+/// Either way, the key is the test name; anything not one of the four
+/// built-ins is a `Custom` (generic) test.
+fn classify_test(value: &serde_yaml::Value) -> TestType {
+    let name = match value {
+        serde_yaml::Value::String(s) => Some(s.as_str()),
+        serde_yaml::Value::Mapping(m) => m.keys().next().and_then(|k| k.as_str()),
+        _ => None,
+    };
+
+    match name {
+        Some("unique") => TestType::Uniqueness,
+        Some("not_null") => TestType::NotNull,
+        Some("relationships") => TestType::Relationships,
+        Some("accepted_values") => TestType::AcceptedValues,
+        _ => TestType::Custom,
+    }
+}
+
+/// Per-model, per-column test types, as declared in a `schema.yml`.
+pub type ModelColumnTests = HashMap<String, HashMap<String, Vec<TestType>>>;
+
+/// Parses a `schema.yml`'s `models:` block into [`ModelColumnTests`]. A file
+/// that fails to parse (e.g. a diff side that doesn't exist yet, for a newly
+/// added file) logs a warning and yields an empty map rather than failing
+/// the whole analysis.
+pub fn parse_schema_yml_tests(yaml: &str) -> ModelColumnTests {
+    let parsed: SchemaYml = match serde_yaml::from_str(yaml) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            log::warn!("failed to parse schema.yml for test-coverage diffing: {e}");
+            return HashMap::new();
+        }
+    };
+
+    parsed
+        .models
+        .into_iter()
+        .map(|model| {
+            let columns = model
+                .columns
+                .into_iter()
+                .map(|column| {
+                    (
+                        column.name,
+                        column.tests.iter().map(classify_test).collect(),
+                    )
+                })
+                .collect();
+            (model.name, columns)
+        })
+        .collect()
+}
+
+/// Whether a [`TestChange`] added or removed a test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestChangeKind {
+    Added,
+    Removed,
+}
+
+/// One test added or removed on a single model/column between two
+/// `schema.yml` diff sides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestChange {
+    pub model: String,
+    pub column: String,
+    pub test_type: TestType,
+    pub kind: TestChangeKind,
+}
+
+/// Diffs two `schema.yml` contents (before/after a PR) and reports every
+/// test added or removed per model/column, so coverage analysis can be
+/// diff-aware instead of just counting the after-state.
+pub fn diff_schema_yml_tests(base_yaml: &str, head_yaml: &str) -> Vec<TestChange> {
+    let base = parse_schema_yml_tests(base_yaml);
+    let head = parse_schema_yml_tests(head_yaml);
+
+    let models: HashSet<&String> = base.keys().chain(head.keys()).collect();
+    let mut changes = Vec::new();
+
+    for model in models {
+        let base_columns = base.get(model);
+        let head_columns = head.get(model);
+        let columns: HashSet<&String> = base_columns
+            .into_iter()
+            .flat_map(HashMap::keys)
+            .chain(head_columns.into_iter().flat_map(HashMap::keys))
+            .collect();
+
+        for column in columns {
+            let base_tests: HashSet<TestType> = base_columns
+                .and_then(|c| c.get(column))
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect();
+            let head_tests: HashSet<TestType> = head_columns
+                .and_then(|c| c.get(column))
+                .into_iter()
+                .flatten()
+                .copied()
+                .collect();
+
+            for &test_type in head_tests.difference(&base_tests) {
+                changes.push(TestChange {
+                    model: model.clone(),
+                    column: column.clone(),
+                    test_type,
+                    kind: TestChangeKind::Added,
+                });
+            }
+            for &test_type in base_tests.difference(&head_tests) {
+                changes.push(TestChange {
+                    model: model.clone(),
+                    column: column.clone(),
+                    test_type,
+                    kind: TestChangeKind::Removed,
+                });
+            }
+        }
+    }
+
+    changes.sort_by(|a, b| {
+        (&a.model, &a.column, a.test_type.name()).cmp(&(&b.model, &b.column, b.test_type.name()))
+    });
+    changes
+}
+
+/// Flags every test removed from a model in `changed_models` as a coverage
+/// regression: a test that already existed and was deliberately (or
+/// accidentally) dropped is a stronger signal than a model that was simply
+/// never tested.
+pub fn detect_removed_tests(
+    schema_yml_path: &str,
+    changed_models: &[String],
+    changes: &[TestChange],
+) -> Vec<QualityIssue> {
+    changes
+        .iter()
+        .filter(|change| {
+            change.kind == TestChangeKind::Removed
+                && changed_models.iter().any(|m| m == &change.model)
+        })
+        .map(|change| QualityIssue {
+            file_path: schema_yml_path.to_string(),
+            line_number: None,
+            message: format!(
+                "{}.{}: {} test removed from changed model",
+                change.model,
+                change.column,
+                change.test_type.name()
+            ),
+            severity: Severity::Medium,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn incremental_model_missing_unique_key_is_flagged_high() {
+        let base = NodeConfig {
+            materialized: Some("view".to_string()),
+            ..Default::default()
+        };
+        let head = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            ..Default::default()
+        };
+
+        let issue = detect_missing_unique_key(
+            "orders_summary",
+            "models/orders_summary.sql",
+            Some(&base),
+            &head,
+        )
+        .expect("newly incremental model with no unique_key should be flagged");
+
+        assert_eq!(issue.severity, Severity::High);
+        assert!(issue.message.contains("unique_key"));
+    }
+
+    #[test]
+    fn append_strategy_does_not_require_a_unique_key() {
+        let base = NodeConfig {
+            materialized: Some("view".to_string()),
+            ..Default::default()
+        };
+        let head = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            incremental_strategy: Some("append".to_string()),
+            ..Default::default()
+        };
+
+        assert!(
+            detect_missing_unique_key("events", "models/events.sql", Some(&base), &head).is_none()
+        );
+    }
+
+    #[test]
+    fn insert_overwrite_on_snowflake_is_flagged() {
+        let config = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            incremental_strategy: Some("insert_overwrite".to_string()),
+            ..Default::default()
+        };
+
+        let issue = detect_unsupported_incremental_strategy(
+            &Warehouse::Snowflake,
+            "orders_summary",
+            "models/orders_summary.sql",
+            &config,
+        )
+        .expect("insert_overwrite is not supported on Snowflake");
+
+        assert_eq!(issue.severity, Severity::High);
+        assert!(issue.message.contains("insert_overwrite"));
+    }
+
+    #[test]
+    fn merge_on_snowflake_is_accepted() {
+        let config = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            incremental_strategy: Some("merge".to_string()),
+            ..Default::default()
+        };
+
+        assert!(detect_unsupported_incremental_strategy(
+            &Warehouse::Snowflake,
+            "orders_summary",
+            "models/orders_summary.sql",
+            &config,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn insert_overwrite_is_accepted_on_bigquery() {
+        let config = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            incremental_strategy: Some("insert_overwrite".to_string()),
+            ..Default::default()
+        };
+
+        assert!(detect_unsupported_incremental_strategy(
+            &Warehouse::BigQuery,
+            "orders_summary",
+            "models/orders_summary.sql",
+            &config,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn a_non_incremental_model_is_never_flagged() {
+        let config = NodeConfig {
+            materialized: Some("table".to_string()),
+            incremental_strategy: Some("insert_overwrite".to_string()),
+            ..Default::default()
+        };
+
+        assert!(detect_unsupported_incremental_strategy(
+            &Warehouse::Snowflake,
+            "orders_summary",
+            "models/orders_summary.sql",
+            &config,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn an_unrecognized_warehouse_is_not_validated() {
+        let config = NodeConfig {
+            materialized: Some("incremental".to_string()),
+            incremental_strategy: Some("insert_overwrite".to_string()),
+            ..Default::default()
+        };
+
+        assert!(detect_unsupported_incremental_strategy(
+            &Warehouse::Other("databricks".to_string()),
+            "orders_summary",
+            "models/orders_summary.sql",
+            &config,
+        )
+        .is_none());
+    }
+
+    fn manifest_node_with_sql(unique_id: &str, file_path: &str, sql: &str) -> ManifestNode {
+        ManifestNode {
+            unique_id: unique_id.to_string(),
+            name: unique_id.to_string(),
+            resource_type: "model".to_string(),
+            original_file_path: file_path.to_string(),
+            patch_path: None,
+            depends_on: crate::artifacts::DependsOn::default(),
+            config: NodeConfig::default(),
+            compiled_code: Some(sql.to_string()),
+            access: None,
+        }
+    }
+
+    #[test]
+    fn a_model_duplicating_an_existing_one_after_normalizing_whitespace_and_comments_is_flagged() {
+        let existing = manifest_node_with_sql(
+            "model.trill_shop.orders_summary",
+            "models/orders_summary.sql",
+            "select order_id, sum(amount) as total\nfrom {{ ref('orders') }}\ngroup by order_id",
+        );
+        let changed_sql = "-- copied from orders_summary\nSELECT   order_id,   SUM(amount) AS total\nFROM {{ ref('orders') }}\nGROUP BY   order_id";
+
+        let issue = detect_duplicate_model(
+            "model.trill_shop.orders_summary_v2",
+            "models/orders_summary_v2.sql",
+            changed_sql,
+            std::slice::from_ref(&existing),
+        )
+        .expect("near-identical SQL should be flagged as a duplicate");
+
+        assert!(issue.message.contains("orders_summary"));
+        assert_eq!(issue.file_path, "models/orders_summary_v2.sql");
+    }
+
+    #[test]
+    fn a_distinct_model_is_not_flagged_as_a_duplicate() {
+        let existing = manifest_node_with_sql(
+            "model.trill_shop.orders_summary",
+            "models/orders_summary.sql",
+            "select order_id, sum(amount) as total from {{ ref('orders') }} group by order_id",
+        );
+
+        assert!(detect_duplicate_model(
+            "model.trill_shop.customers_summary",
+            "models/customers_summary.sql",
+            "select customer_id, count(*) as order_count from {{ ref('orders') }} group by customer_id",
+            std::slice::from_ref(&existing),
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn untested_order_id_column_on_a_new_model_is_flagged() {
+        let columns = vec!["order_id".to_string(), "amount".to_string()];
+        let tests_by_column = HashMap::new();
+
+        let recs = detect_missing_pk_tests("orders", &columns, &tests_by_column);
+
+        assert_eq!(recs.len(), 1);
+        assert_eq!(recs[0].priority, Priority::High);
+        assert!(recs[0].message.contains("order_id"));
+    }
+
+    #[test]
+    fn a_primary_key_with_both_tests_already_present_is_not_flagged() {
+        let columns = vec!["order_id".to_string()];
+        let mut tests_by_column = HashMap::new();
+        tests_by_column.insert(
+            "order_id".to_string(),
+            vec![TestType::Uniqueness, TestType::NotNull],
+        );
+
+        assert!(detect_missing_pk_tests("orders", &columns, &tests_by_column).is_empty());
+    }
+
+    #[test]
+    fn lone_not_null_is_insufficient_despite_being_tested() {
+        let mut tests_by_model = HashMap::new();
+        tests_by_model.insert("stg_orders".to_string(), vec![TestType::NotNull]);
+
+        let coverage = validate_test_coverage(&tests_by_model, &HashMap::new(), 0.5);
+
+        assert_eq!(coverage.len(), 1);
+        assert!(
+            !coverage[0].present.is_empty(),
+            "model should still count as tested"
+        );
+        assert!(coverage[0].insufficient);
+        assert_eq!(insufficient_test_coverage(&coverage).len(), 1);
+    }
+
+    #[test]
+    fn uniqueness_and_not_null_together_clear_the_default_threshold() {
+        let mut tests_by_model = HashMap::new();
+        tests_by_model.insert(
+            "stg_orders".to_string(),
+            vec![TestType::Uniqueness, TestType::NotNull],
+        );
+
+        let coverage = validate_test_coverage(&tests_by_model, &HashMap::new(), 0.3);
+
+        assert!(!coverage[0].insufficient);
+    }
+
+    #[test]
+    fn missing_test_recommendation_names_the_absent_types() {
+        let mut tests_by_model = HashMap::new();
+        tests_by_model.insert("stg_orders".to_string(), vec![TestType::NotNull]);
+        let coverage = validate_test_coverage(&tests_by_model, &HashMap::new(), 0.5);
+
+        let recs = missing_test_recommendations(&coverage);
+        assert_eq!(recs.len(), 1);
+        assert!(recs[0].message.contains("uniqueness"));
+    }
+
+    #[test]
+    fn select_star_is_flagged_but_count_star_is_not() {
+        assert!(
+            detect_select_star_propagation("stg_orders", "select * from raw.orders", &[],)
+                .is_some()
+        );
+
+        assert!(detect_select_star_propagation(
+            "order_counts",
+            "select customer_id, count(*) as order_count from raw.orders group by 1",
+            &[],
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn a_star_hidden_in_a_comment_is_not_flagged() {
+        assert!(detect_select_star_propagation(
+            "stg_orders",
+            "-- select * was removed here\nselect order_id, customer_id from raw.orders",
+            &[],
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn qualified_and_bigquery_except_stars_are_flagged_with_downstreams_named() {
+        let downstream = vec!["orders_summary".to_string(), "orders_wide".to_string()];
+
+        let rec = detect_select_star_propagation(
+            "stg_orders",
+            "select o.* except (internal_notes) from raw.orders o",
+            &downstream,
+        )
+        .expect("qualified star with EXCEPT should still be flagged");
+
+        assert_eq!(rec.priority, Priority::Medium);
+        assert!(rec.message.contains("orders_summary"));
+        assert!(rec.message.contains("orders_wide"));
+    }
+
+    const FIVE_JOIN_SQL: &str = "select * from a \
+         join b on a.id = b.a_id \
+         join c on b.id = c.b_id \
+         join d on c.id = d.c_id \
+         join e on d.id = e.d_id \
+         join f on e.id = f.e_id";
+
+    #[test]
+    fn a_model_with_more_than_four_joins_is_flagged_low_priority() {
+        let rec = detect_high_complexity("orders_wide", FIVE_JOIN_SQL)
+            .expect("5 joins should be flagged");
+        assert_eq!(rec.priority, Priority::Low);
+        assert!(rec.message.contains('5'));
+    }
+
+    #[test]
+    fn a_model_with_few_joins_is_not_flagged() {
+        assert!(
+            detect_high_complexity("stg_orders", "select * from a join b on a.id = b.a_id")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn a_new_model_with_select_star_produces_a_finding_noting_limited_lineage_analysis() {
+        let recs = analyze_new_model_file("stg_new_model", "select * from raw.new_source");
+
+        // The compiled-SQL propagation check and the source-line SQL lint
+        // both flag the same `SELECT *`, at different granularity (see
+        // `SqlLintRule::SelectStar`'s doc comment) — both carry the note.
+        assert_eq!(recs.len(), 2);
+        assert!(recs.iter().all(|r| r.message.contains("limited lineage analysis")));
+    }
+
+    #[test]
+    fn a_clean_new_model_produces_no_findings() {
+        let recs = analyze_new_model_file(
+            "stg_new_model",
+            "select order_id, customer_id from raw.new_source",
+        );
+
+        assert!(recs.is_empty());
+    }
+
+    #[test]
+    fn a_blanket_ignore_directive_is_distinguished_from_a_categorized_one() {
+        assert_eq!(
+            parse_ignore_directives("-- dbt-pr-agent: ignore\nselect 1"),
+            vec![IgnoreDirective { category: None }]
+        );
+        assert_eq!(
+            parse_ignore_directives("-- dbt-pr-agent: ignore complexity\nselect 1"),
+            vec![IgnoreDirective {
+                category: Some("complexity".to_string())
+            }]
+        );
+    }
+
+    #[test]
+    fn a_directive_hidden_in_a_block_comment_is_also_parsed() {
+        assert_eq!(
+            parse_ignore_directives("/* dbt-pr-agent: ignore select_star */\nselect * from a"),
+            vec![IgnoreDirective {
+                category: Some("select_star".to_string())
+            }]
+        );
+    }
+
+    #[test]
+    fn an_ignore_complexity_directive_suppresses_the_complexity_finding_but_not_an_unrelated_one() {
+        let sql = format!("-- dbt-pr-agent: ignore complexity\n{FIVE_JOIN_SQL}");
+
+        let complexity =
+            detect_high_complexity("orders_wide", &sql).expect("5 joins should be flagged");
+        let star = detect_select_star_propagation("orders_wide", &sql, &[])
+            .expect("select * should be flagged");
+
+        let findings = vec![
+            CategorizedRecommendation {
+                category: "complexity".to_string(),
+                recommendation: complexity,
+            },
+            CategorizedRecommendation {
+                category: "select_star".to_string(),
+                recommendation: star,
+            },
+        ];
+        let directives = parse_ignore_directives(&sql);
+
+        let (kept, suppressed) = apply_ignore_directives(findings, &directives);
+
+        assert_eq!(suppressed, 1);
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0].message.contains("SELECT *"));
+    }
+
+    const SCHEMA_YML_WITH_UNIQUE: &str = "
+models:
+  - name: orders
+    columns:
+      - name: order_id
+        tests:
+          - unique
+          - not_null
+";
+
+    const SCHEMA_YML_WITHOUT_UNIQUE: &str = "
+models:
+  - name: orders
+    columns:
+      - name: order_id
+        tests:
+          - not_null
+";
+
+    #[test]
+    fn parses_bare_and_mapping_style_tests_into_test_types() {
+        let yaml = "
+models:
+  - name: orders
+    columns:
+      - name: order_id
+        tests:
+          - unique
+      - name: customer_id
+        tests:
+          - relationships:
+              to: ref('customers')
+              field: id
+";
+        let parsed = parse_schema_yml_tests(yaml);
+
+        assert_eq!(parsed["orders"]["order_id"], vec![TestType::Uniqueness]);
+        assert_eq!(
+            parsed["orders"]["customer_id"],
+            vec![TestType::Relationships]
+        );
+    }
+
+    #[test]
+    fn removing_a_unique_test_from_a_model_is_detected_and_flagged() {
+        let changes = diff_schema_yml_tests(SCHEMA_YML_WITH_UNIQUE, SCHEMA_YML_WITHOUT_UNIQUE);
+
+        assert_eq!(
+            changes,
+            vec![TestChange {
+                model: "orders".to_string(),
+                column: "order_id".to_string(),
+                test_type: TestType::Uniqueness,
+                kind: TestChangeKind::Removed,
+            }]
+        );
+
+        let issues = detect_removed_tests("models/orders.yml", &["orders".to_string()], &changes);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Medium);
+        assert!(issues[0].message.contains("uniqueness"));
+        assert!(issues[0].message.contains("removed from changed model"));
+    }
+
+    #[test]
+    fn an_added_test_is_reported_as_added_not_removed() {
+        let changes = diff_schema_yml_tests(SCHEMA_YML_WITHOUT_UNIQUE, SCHEMA_YML_WITH_UNIQUE);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, TestChangeKind::Added);
+        assert_eq!(changes[0].test_type, TestType::Uniqueness);
+        assert!(
+            detect_removed_tests("models/orders.yml", &["orders".to_string()], &changes).is_empty()
+        );
+    }
+
+    #[test]
+    fn a_removed_test_on_a_model_outside_the_changeset_is_not_flagged() {
+        let changes = diff_schema_yml_tests(SCHEMA_YML_WITH_UNIQUE, SCHEMA_YML_WITHOUT_UNIQUE);
+
+        assert!(detect_removed_tests(
+            "models/orders.yml",
+            &["some_other_model".to_string()],
+            &changes
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn a_blanket_ignore_directive_suppresses_every_finding() {
+        let sql = format!("-- dbt-pr-agent: ignore\n{FIVE_JOIN_SQL}");
+
+        let complexity =
+            detect_high_complexity("orders_wide", &sql).expect("5 joins should be flagged");
+        let star = detect_select_star_propagation("orders_wide", &sql, &[])
+            .expect("select * should be flagged");
+        let findings = vec![
+            CategorizedRecommendation {
+                category: "complexity".to_string(),
+                recommendation: complexity,
+            },
+            CategorizedRecommendation {
+                category: "select_star".to_string(),
+                recommendation: star,
+            },
+        ];
+
+        let (kept, suppressed) = apply_ignore_directives(findings, &parse_ignore_directives(&sql));
+
+        assert_eq!(suppressed, 2);
+        assert!(kept.is_empty());
+    }
+
+    fn unit_test_node(name: &str, target: &str) -> ManifestNode {
+        ManifestNode {
+            unique_id: format!("unit_test.trill_shop.{name}"),
+            name: name.to_string(),
+            resource_type: "unit_test".to_string(),
+            original_file_path: String::new(),
+            patch_path: None,
+            depends_on: crate::artifacts::DependsOn {
+                nodes: vec![target.to_string()],
+            },
+            config: NodeConfig::default(),
+            compiled_code: None,
+            access: None,
+        }
+    }
+
+    #[test]
+    fn unit_tests_are_counted_against_the_model_they_target() {
+        let nodes = vec![
+            unit_test_node(
+                "test_orders_summary_totals",
+                "model.trill_shop.orders_summary",
+            ),
+            unit_test_node(
+                "test_orders_summary_no_nulls",
+                "model.trill_shop.orders_summary",
+            ),
+        ];
+
+        let counts = unit_tests_by_model(&nodes);
+
+        assert_eq!(counts.get("model.trill_shop.orders_summary"), Some(&2));
+        assert_eq!(counts.get("model.trill_shop.stg_orders"), None);
+    }
+
+    #[test]
+    fn a_complex_model_with_no_unit_test_is_flagged() {
+        let counts = unit_tests_by_model(&[]);
+
+        let rec = detect_missing_unit_test(
+            "orders_wide",
+            FIVE_JOIN_SQL,
+            counts.get("orders_wide").copied().unwrap_or(0),
+        )
+        .expect("complex model with no unit_test coverage should be flagged");
+
+        assert_eq!(rec.priority, Priority::Medium);
+        assert!(rec.message.contains("unit_test"));
+    }
+
+    #[test]
+    fn a_complex_model_covered_by_a_unit_test_is_not_flagged() {
+        let nodes = vec![unit_test_node(
+            "test_orders_wide",
+            "model.trill_shop.orders_wide",
+        )];
+        let counts = unit_tests_by_model(&nodes);
+
+        let count = counts
+            .get("model.trill_shop.orders_wide")
+            .copied()
+            .unwrap_or(0);
+        assert!(detect_missing_unit_test("orders_wide", FIVE_JOIN_SQL, count).is_none());
+    }
+
+    #[test]
+    fn a_simple_model_with_no_unit_test_is_not_flagged() {
+        assert!(detect_missing_unit_test("stg_orders", "select * from raw.orders", 0).is_none());
+    }
+
+    #[test]
+    fn validate_test_coverage_reports_unit_test_count_separately_from_data_test_score() {
+        let mut tests_by_model = HashMap::new();
+        tests_by_model.insert(
+            "orders_summary".to_string(),
+            vec![TestType::Uniqueness, TestType::NotNull],
+        );
+        let mut unit_test_counts = HashMap::new();
+        unit_test_counts.insert("orders_summary".to_string(), 3);
+
+        let coverage = validate_test_coverage(&tests_by_model, &unit_test_counts, 0.3);
+
+        assert_eq!(coverage[0].unit_test_count, 3);
+        assert!(
+            !coverage[0].insufficient,
+            "unit_test_count shouldn't affect the data-test score"
+        );
+    }
+}
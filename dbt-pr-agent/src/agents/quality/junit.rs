@@ -0,0 +1,121 @@
+//! JUnit XML output for [`QualityIssue`]s, so CI systems that already parse
+//! JUnit results (Jenkins, GitLab CI, ...) can render this agent's findings
+//! natively and fail the build on them, without a GitHub-specific
+//! integration.
+//!
+//! One `<testsuite>` covers all `issues`, one `<testcase>` per issue. There's
+//! no notion of a "passing" quality check here — every entry in `issues` is
+//! something the agent flagged, so every testcase carries a `<failure>`. A
+//! quality run that raises zero issues renders a `<testsuite>` with no test
+//! cases at all, which JUnit consumers treat as a clean pass.
+
+use super::QualityIssue;
+use crate::severity::Severity;
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn testcase_name(issue: &QualityIssue) -> String {
+    match issue.line_number {
+        Some(line) => format!("{}:{line}", issue.file_path),
+        None => issue.file_path.clone(),
+    }
+}
+
+fn failure_type(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "critical",
+        Severity::High => "high",
+        Severity::Medium => "medium",
+        Severity::Low => "low",
+    }
+}
+
+/// Renders `issues` as a single JUnit `<testsuite>` XML document, one
+/// `<testcase>`/`<failure>` pair per issue.
+pub fn render_junit_xml(issues: &[QualityIssue]) -> String {
+    let testcases: String = issues
+        .iter()
+        .map(|issue| {
+            let name = escape_xml(&testcase_name(issue));
+            let message = escape_xml(&issue.message);
+            let failure_type = failure_type(issue.severity);
+            format!(
+                "  <testcase name=\"{name}\" classname=\"dbt-pr-agent.quality\">\n    \
+                 <failure message=\"{message}\" type=\"{failure_type}\">{message}</failure>\n  \
+                 </testcase>\n"
+            )
+        })
+        .collect();
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuite name=\"dbt-pr-agent.quality\" tests=\"{count}\" failures=\"{count}\">\n\
+         {testcases}</testsuite>\n",
+        count = issues.len(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_empty_issue_list_renders_a_testsuite_with_zero_tests() {
+        let xml = render_junit_xml(&[]);
+
+        assert!(xml.contains("tests=\"0\""));
+        assert!(xml.contains("failures=\"0\""));
+        assert!(!xml.contains("<testcase"));
+    }
+
+    #[test]
+    fn one_issue_renders_one_testcase_with_a_failure_typed_by_severity() {
+        let issues = vec![QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: Some(12),
+            message: "avoid SELECT *".to_string(),
+            severity: Severity::Critical,
+        }];
+
+        let xml = render_junit_xml(&issues);
+
+        assert!(xml.contains("tests=\"1\""));
+        assert!(xml.contains("name=\"models/marts/orders.sql:12\""));
+        assert!(xml.contains("type=\"critical\""));
+        assert!(xml.contains("avoid SELECT *"));
+    }
+
+    #[test]
+    fn a_whole_file_finding_uses_the_file_path_alone_as_the_testcase_name() {
+        let issues = vec![QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: None,
+            message: "whole-file finding".to_string(),
+            severity: Severity::Low,
+        }];
+
+        let xml = render_junit_xml(&issues);
+
+        assert!(xml.contains("name=\"models/marts/orders.sql\""));
+    }
+
+    #[test]
+    fn special_characters_in_the_message_are_escaped() {
+        let issues = vec![QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: None,
+            message: "uses <script> & \"quotes\"".to_string(),
+            severity: Severity::Medium,
+        }];
+
+        let xml = render_junit_xml(&issues);
+
+        assert!(xml.contains("&lt;script&gt; &amp; &quot;quotes&quot;"));
+    }
+}
@@ -0,0 +1,178 @@
+//! SARIF 2.1.0 output for [`QualityIssue`]s, so findings can be uploaded to
+//! GitHub Code Scanning and shown inline on the PR diff.
+//!
+//! `QualityIssue` doesn't carry a stable rule identifier, only a free-form
+//! message, so [`rule_id`] derives one heuristically from the message's
+//! static wording (stripping a leading `model: `/`model.column: ` prefix,
+//! then slugifying the first few words of what's left). This is imperfect —
+//! a message whose dynamic value sits early (e.g. a quoted keyword) can
+//! still produce a distinct id per instance of what's really one rule — but
+//! it's good enough to group and suppress findings by rule in Code
+//! Scanning, which is what SARIF's `ruleId` is for.
+
+use super::QualityIssue;
+use crate::severity::Severity;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+const TOOL_NAME: &str = "dbt-pr-agent";
+
+/// A stable-ish rule id derived from `message`'s static wording; see the
+/// module doc comment for the heuristic and its limits.
+fn rule_id(message: &str) -> String {
+    let body = message.rsplit_once(": ").map_or(message, |(_, rest)| rest);
+    let words: Vec<String> = body
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .take(6)
+        .map(|w| w.to_ascii_lowercase())
+        .collect();
+
+    if words.is_empty() {
+        format!("{TOOL_NAME}/quality-issue")
+    } else {
+        format!("{TOOL_NAME}/{}", words.join("-"))
+    }
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low => "note",
+    }
+}
+
+/// Renders `issues` as a SARIF 2.1.0 log: one run, one result per issue, and
+/// one deduplicated rule definition per distinct [`rule_id`]. A missing
+/// `line_number` (a whole-file finding) is reported at line 1, since SARIF's
+/// `region` requires a start line.
+pub fn render_sarif(issues: &[QualityIssue]) -> Value {
+    let mut rules: BTreeMap<String, String> = BTreeMap::new();
+
+    let results: Vec<Value> = issues
+        .iter()
+        .map(|issue| {
+            let id = rule_id(&issue.message);
+            rules
+                .entry(id.clone())
+                .or_insert_with(|| issue.message.clone());
+            json!({
+                "ruleId": id,
+                "level": sarif_level(issue.severity),
+                "message": { "text": issue.message },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": issue.file_path },
+                        "region": { "startLine": issue.line_number.unwrap_or(1) },
+                    },
+                }],
+            })
+        })
+        .collect();
+
+    let rule_defs: Vec<Value> = rules
+        .into_iter()
+        .map(|(id, message)| {
+            json!({
+                "id": id,
+                "shortDescription": { "text": message },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/master/Schemata/sarif-schema-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "rules": rule_defs,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_high_severity_issue_maps_to_the_sarif_error_level() {
+        let issues = vec![QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: Some(12),
+            message: "avoid SELECT * — upstream schema changes propagate silently".to_string(),
+            severity: Severity::High,
+        }];
+
+        let sarif = render_sarif(&issues);
+
+        assert_eq!(sarif["runs"][0]["results"][0]["level"], "error");
+        assert_eq!(
+            sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]
+                ["artifactLocation"]["uri"],
+            "models/marts/orders.sql"
+        );
+        assert_eq!(
+            sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"]
+                ["startLine"],
+            12
+        );
+    }
+
+    #[test]
+    fn a_whole_file_finding_with_no_line_number_defaults_to_line_one() {
+        let issues = vec![QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: None,
+            message: "orders: whole-file finding".to_string(),
+            severity: Severity::Low,
+        }];
+
+        let sarif = render_sarif(&issues);
+
+        assert_eq!(
+            sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"]
+                ["startLine"],
+            1
+        );
+        assert_eq!(sarif["runs"][0]["results"][0]["level"], "note");
+    }
+
+    #[test]
+    fn two_issues_from_the_same_rule_share_one_rule_definition() {
+        let issues = vec![
+            QualityIssue {
+                file_path: "models/marts/orders.sql".to_string(),
+                line_number: Some(1),
+                message: "orders_summary: newly configured as incremental with strategy 'merge' \
+                           but has no unique_key"
+                    .to_string(),
+                severity: Severity::High,
+            },
+            QualityIssue {
+                file_path: "models/marts/customers.sql".to_string(),
+                line_number: Some(1),
+                message: "customers_summary: newly configured as incremental with strategy \
+                           'merge' but has no unique_key"
+                    .to_string(),
+                severity: Severity::High,
+            },
+        ];
+
+        let sarif = render_sarif(&issues);
+
+        let rules = sarif["runs"][0]["tool"]["driver"]["rules"]
+            .as_array()
+            .expect("rules is an array");
+        assert_eq!(rules.len(), 1);
+        assert_eq!(
+            sarif["runs"][0]["results"][0]["ruleId"],
+            sarif["runs"][0]["results"][1]["ruleId"]
+        );
+    }
+}
@@ -0,0 +1,474 @@
+//! Deterministic, rule-based SQL style checks for a changed model's source
+//! file.
+//!
+//! These run against the model's *source* SQL (as written in the PR) rather
+//! than dbt's compiled SQL, so findings can carry a real line number a
+//! reviewer can jump to on the diff. There's no SQL parser dependency in
+//! this crate, so each rule below is a cheap, line-oriented heuristic —
+//! the same tradeoff [`super::strip_sql_comments`] and
+//! `super::has_propagating_star` already make for the compiled-SQL checks.
+
+use super::QualityIssue;
+use crate::severity::Severity;
+use serde::{Deserialize, Serialize};
+
+/// A single lint check [`lint`] can apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SqlLintRule {
+    /// Reserved keywords (`select`, `from`, `join`, ...) should all use the
+    /// same case throughout the file.
+    KeywordCasing,
+    /// `select *` (or `select t.*`) silently propagates upstream schema
+    /// changes, the same risk [`super::detect_select_star_propagation`]
+    /// flags against the compiled model — this catches it at the source
+    /// line instead.
+    SelectStar,
+    /// A comma-separated `from a, b` with no `join`/`on` is an implicit
+    /// cross join: easy to write by accident, and it silently multiplies
+    /// row counts instead of erroring.
+    ImplicitCrossJoin,
+    /// A derived table (`from (select ...)`) with no alias afterwards is
+    /// rejected by some warehouses and confusing to read on the ones that
+    /// allow it.
+    UnaliasedSubquery,
+    /// Parenthesized expressions (CTEs and subqueries) nested past
+    /// [`SqlLintConfig::max_nesting_depth`] are hard to review; this is a
+    /// paren-nesting proxy for CTE/subquery depth, not a true CTE-reference
+    /// graph.
+    NestedCteDepth,
+}
+
+impl SqlLintRule {
+    /// Every rule, in the fixed order [`lint`] runs them.
+    pub const ALL: [SqlLintRule; 5] = [
+        SqlLintRule::KeywordCasing,
+        SqlLintRule::SelectStar,
+        SqlLintRule::ImplicitCrossJoin,
+        SqlLintRule::UnaliasedSubquery,
+        SqlLintRule::NestedCteDepth,
+    ];
+}
+
+/// The case reserved keywords are expected to use, for
+/// [`SqlLintRule::KeywordCasing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordCase {
+    Upper,
+    #[default]
+    Lower,
+}
+
+/// Reserved words [`SqlLintRule::KeywordCasing`] checks. Not exhaustive of
+/// the SQL standard — just the keywords common enough in dbt model SQL that
+/// inconsistent casing is worth flagging.
+const KEYWORDS: &[&str] = &[
+    "select", "from", "where", "join", "on", "group", "order", "by", "and", "or", "as", "with",
+    "left", "right", "inner", "outer", "union", "having", "limit", "case", "when", "then", "else",
+    "end", "distinct",
+];
+
+const DEFAULT_MAX_NESTING_DEPTH: usize = 3;
+
+/// Which rules run and their tunables. `Default` enables every rule with
+/// dbt-labs style conventions (lowercase keywords, nesting capped at 3).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SqlLintConfig {
+    #[serde(default = "all_rules")]
+    pub enabled_rules: Vec<SqlLintRule>,
+    #[serde(default)]
+    pub keyword_case: KeywordCase,
+    #[serde(default = "default_max_nesting_depth")]
+    pub max_nesting_depth: usize,
+}
+
+fn all_rules() -> Vec<SqlLintRule> {
+    SqlLintRule::ALL.to_vec()
+}
+
+fn default_max_nesting_depth() -> usize {
+    DEFAULT_MAX_NESTING_DEPTH
+}
+
+impl Default for SqlLintConfig {
+    fn default() -> Self {
+        Self {
+            enabled_rules: all_rules(),
+            keyword_case: KeywordCase::Lower,
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
+        }
+    }
+}
+
+/// Strips `--` and `/* */` comments while preserving line structure (unlike
+/// [`super::strip_sql_comments`], which collapses a multi-line block comment
+/// to nothing and would throw off every line number after it), returning
+/// `sql` split into comment-free lines.
+fn strip_comments_by_line(sql: &str) -> Vec<String> {
+    let mut lines_out = Vec::new();
+    let mut in_block_comment = false;
+    for line in sql.lines() {
+        let mut result = String::with_capacity(line.len());
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if in_block_comment {
+                if c == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    in_block_comment = false;
+                }
+                continue;
+            }
+            if c == '-' && chars.peek() == Some(&'-') {
+                break;
+            }
+            if c == '/' && chars.peek() == Some(&'*') {
+                chars.next();
+                in_block_comment = true;
+                continue;
+            }
+            result.push(c);
+        }
+        lines_out.push(result);
+    }
+    lines_out
+}
+
+/// Splits `line` into (word, start_index) pairs on non-alphanumeric,
+/// non-underscore boundaries, so a keyword check doesn't match a substring
+/// of a longer identifier (`order_id` isn't `order`).
+fn words(line: &str) -> Vec<(&str, usize)> {
+    let mut out = Vec::new();
+    let mut start = None;
+    for (i, c) in line.char_indices() {
+        if c.is_alphanumeric() || c == '_' {
+            if start.is_none() {
+                start = Some(i);
+            }
+        } else if let Some(s) = start.take() {
+            out.push((&line[s..i], s));
+        }
+    }
+    if let Some(s) = start {
+        out.push((&line[s..], s));
+    }
+    out
+}
+
+fn keyword_casing(lines: &[String], case: KeywordCase) -> Vec<QualityIssue> {
+    let mut issues = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        for (word, _) in words(line) {
+            if !KEYWORDS.contains(&word.to_ascii_lowercase().as_str()) {
+                continue;
+            }
+            let matches_case = match case {
+                KeywordCase::Upper => word == word.to_ascii_uppercase(),
+                KeywordCase::Lower => word == word.to_ascii_lowercase(),
+            };
+            if !matches_case {
+                let expected = match case {
+                    KeywordCase::Upper => "uppercase",
+                    KeywordCase::Lower => "lowercase",
+                };
+                issues.push(QualityIssue {
+                    file_path: String::new(),
+                    line_number: Some(i as u32 + 1),
+                    message: format!("keyword '{word}' should be {expected} for consistency"),
+                    severity: Severity::Low,
+                });
+            }
+        }
+    }
+    issues
+}
+
+fn select_star(lines: &[String]) -> Vec<QualityIssue> {
+    let mut issues = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        let flagged = chars.iter().enumerate().any(|(j, &c)| {
+            c == '*' && chars[..j].iter().rev().find(|c| !c.is_whitespace()) != Some(&'(')
+        });
+        if flagged {
+            issues.push(QualityIssue {
+                file_path: String::new(),
+                line_number: Some(i as u32 + 1),
+                message: "avoid SELECT * — upstream schema changes propagate silently".to_string(),
+                severity: Severity::Medium,
+            });
+        }
+    }
+    issues
+}
+
+fn implicit_cross_join(lines: &[String]) -> Vec<QualityIssue> {
+    let mut issues = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let lower = line.to_ascii_lowercase();
+        let Some(from_at) = words(&lower)
+            .into_iter()
+            .find(|(w, _)| *w == "from")
+            .map(|(_, idx)| idx)
+        else {
+            continue;
+        };
+
+        let rest = &line[from_at + "from".len()..];
+        let mut depth: i32 = 0;
+        let mut has_comma_at_top_level = false;
+        for c in rest.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                ',' if depth == 0 => has_comma_at_top_level = true,
+                _ => {}
+            }
+        }
+        let has_join_keyword = words(&lower).iter().any(|(w, _)| *w == "join");
+        if has_comma_at_top_level && !has_join_keyword {
+            issues.push(QualityIssue {
+                file_path: String::new(),
+                line_number: Some(i as u32 + 1),
+                message: "comma-separated tables in FROM with no JOIN look like an implicit \
+                          cross join — use an explicit JOIN with an ON condition"
+                    .to_string(),
+                severity: Severity::Medium,
+            });
+        }
+    }
+    issues
+}
+
+fn unaliased_subquery(lines: &[String]) -> Vec<QualityIssue> {
+    let mut issues = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let lower = line.to_ascii_lowercase();
+        if !lower.contains("(select") && !lower.contains("( select") {
+            continue;
+        }
+        let Some(close_idx) = line.rfind(')') else {
+            continue;
+        };
+        let after = line[close_idx + 1..].trim_start();
+        let after_lower = after.to_ascii_lowercase();
+        let aliased = !after.is_empty()
+            && (after_lower.starts_with("as ")
+                || words(after).first().is_some_and(|(w, _)| {
+                    !KEYWORDS.contains(&w.to_ascii_lowercase().as_str())
+                }));
+        if !aliased {
+            issues.push(QualityIssue {
+                file_path: String::new(),
+                line_number: Some(i as u32 + 1),
+                message: "derived table has no alias — some warehouses reject this, and it's \
+                          hard to reference in an outer clause"
+                    .to_string(),
+                severity: Severity::Low,
+            });
+        }
+    }
+    issues
+}
+
+fn nested_cte_depth(lines: &[String], max_nesting_depth: usize) -> Vec<QualityIssue> {
+    let mut depth: usize = 0;
+    let mut max_depth = 0;
+    let mut max_depth_line = 0;
+    for (i, line) in lines.iter().enumerate() {
+        for c in line.chars() {
+            match c {
+                '(' => {
+                    depth += 1;
+                    if depth > max_depth {
+                        max_depth = depth;
+                        max_depth_line = i;
+                    }
+                }
+                ')' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+    }
+    if max_depth > max_nesting_depth {
+        vec![QualityIssue {
+            file_path: String::new(),
+            line_number: Some(max_depth_line as u32 + 1),
+            message: format!(
+                "nesting depth {max_depth} exceeds the configured maximum of {max_nesting_depth} \
+                 — consider pulling nested logic into its own CTE"
+            ),
+            severity: Severity::Medium,
+        }]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Runs every rule in `config.enabled_rules` against `sql`, returning
+/// [`QualityIssue`]s anchored to `file_path` with real source line numbers.
+pub fn lint(file_path: &str, sql: &str, config: &SqlLintConfig) -> Vec<QualityIssue> {
+    let lines = strip_comments_by_line(sql);
+    let mut issues = Vec::new();
+
+    for rule in &config.enabled_rules {
+        let mut found = match rule {
+            SqlLintRule::KeywordCasing => keyword_casing(&lines, config.keyword_case),
+            SqlLintRule::SelectStar => select_star(&lines),
+            SqlLintRule::ImplicitCrossJoin => implicit_cross_join(&lines),
+            SqlLintRule::UnaliasedSubquery => unaliased_subquery(&lines),
+            SqlLintRule::NestedCteDepth => nested_cte_depth(&lines, config.max_nesting_depth),
+        };
+        for issue in &mut found {
+            issue.file_path = file_path.to_string();
+        }
+        issues.extend(found);
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_uppercase_keyword_is_flagged_under_the_default_lowercase_convention() {
+        let issues = lint(
+            "models/staging/stg_orders.sql",
+            "SELECT id from orders",
+            &SqlLintConfig::default(),
+        );
+
+        assert!(issues
+            .iter()
+            .any(|i| i.message.contains("SELECT") && i.line_number == Some(1)));
+    }
+
+    #[test]
+    fn consistent_lowercase_keywords_are_not_flagged() {
+        let issues = lint(
+            "models/staging/stg_orders.sql",
+            "select id from orders",
+            &SqlLintConfig::default(),
+        );
+
+        assert!(issues.iter().all(|i| !i.message.contains("keyword")));
+    }
+
+    #[test]
+    fn select_star_is_flagged_with_its_line_number() {
+        let sql = "select\n  *\nfrom orders";
+        let issues = lint("m.sql", sql, &SqlLintConfig::default());
+
+        let star_issue = issues
+            .iter()
+            .find(|i| i.message.contains("SELECT *"))
+            .expect("select * should be flagged");
+        assert_eq!(star_issue.line_number, Some(2));
+    }
+
+    #[test]
+    fn count_star_is_not_flagged_as_select_star() {
+        let issues = lint("m.sql", "select count(*) from orders", &SqlLintConfig::default());
+        assert!(issues.iter().all(|i| !i.message.contains("SELECT *")));
+    }
+
+    #[test]
+    fn comma_separated_tables_with_no_join_are_flagged_as_an_implicit_cross_join() {
+        let issues = lint(
+            "m.sql",
+            "select * from orders o, customers c where o.customer_id = c.id",
+            &SqlLintConfig::default(),
+        );
+
+        assert!(issues.iter().any(|i| i.message.contains("cross join")));
+    }
+
+    #[test]
+    fn an_explicit_join_is_not_flagged_as_an_implicit_cross_join() {
+        let issues = lint(
+            "m.sql",
+            "select * from orders o join customers c on o.customer_id = c.id",
+            &SqlLintConfig::default(),
+        );
+
+        assert!(issues.iter().all(|i| !i.message.contains("cross join")));
+    }
+
+    #[test]
+    fn an_unaliased_derived_table_is_flagged() {
+        let issues = lint(
+            "m.sql",
+            "select * from (select id from orders)",
+            &SqlLintConfig::default(),
+        );
+
+        assert!(issues.iter().any(|i| i.message.contains("no alias")));
+    }
+
+    #[test]
+    fn an_aliased_derived_table_is_not_flagged() {
+        let issues = lint(
+            "m.sql",
+            "select * from (select id from orders) o",
+            &SqlLintConfig::default(),
+        );
+
+        assert!(issues.iter().all(|i| !i.message.contains("no alias")));
+    }
+
+    #[test]
+    fn nesting_past_the_configured_max_depth_is_flagged() {
+        let config = SqlLintConfig {
+            max_nesting_depth: 1,
+            ..SqlLintConfig::default()
+        };
+        let sql = "select * from (select * from (select id from orders))";
+
+        let issues = lint("m.sql", sql, &config);
+
+        assert!(issues.iter().any(|i| i.message.contains("nesting depth")));
+    }
+
+    #[test]
+    fn nesting_within_the_configured_max_depth_is_not_flagged() {
+        let config = SqlLintConfig {
+            max_nesting_depth: 3,
+            ..SqlLintConfig::default()
+        };
+        let sql = "select * from (select id from orders)";
+
+        let issues = lint("m.sql", sql, &config);
+
+        assert!(issues.iter().all(|i| !i.message.contains("nesting depth")));
+    }
+
+    #[test]
+    fn disabling_a_rule_suppresses_its_findings() {
+        let config = SqlLintConfig {
+            enabled_rules: vec![SqlLintRule::SelectStar],
+            ..SqlLintConfig::default()
+        };
+        let sql = "SELECT * from orders o, customers c";
+
+        let issues = lint("m.sql", sql, &config);
+
+        assert!(issues.iter().all(|i| !i.message.contains("keyword")));
+        assert!(issues.iter().any(|i| i.message.contains("SELECT *")));
+    }
+
+    #[test]
+    fn every_issue_is_anchored_to_the_given_file_path() {
+        let issues = lint(
+            "models/staging/stg_orders.sql",
+            "SELECT * from orders o, customers c",
+            &SqlLintConfig::default(),
+        );
+
+        assert!(!issues.is_empty());
+        assert!(issues
+            .iter()
+            .all(|i| i.file_path == "models/staging/stg_orders.sql"));
+    }
+}
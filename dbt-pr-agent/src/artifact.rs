@@ -0,0 +1,279 @@
+//! Loads and caches the dbt artifacts (`manifest.json`, `catalog.json`) so a
+//! single analysis run parses each one exactly once, no matter how many
+//! agents ask for it.
+
+use crate::artifacts::{parse_sources_freshness, SourceFreshness};
+use serde_json::Value;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+/// A concurrency-safe, lazily-loaded artifact cache.
+///
+/// Construct one `ArtifactParser` per analysis run and hand every agent an
+/// `Arc<ArtifactParser>` (or a plain reference); the first call to
+/// [`manifest`](Self::manifest) reads and parses the file, and every
+/// subsequent call — from any agent, on any thread — gets back the same
+/// `Arc<Value>` without touching disk again.
+pub struct ArtifactParser<F: Fn() -> io::Result<String>> {
+    load_manifest: F,
+    manifest: Mutex<Option<Arc<Value>>>,
+    /// Optional loader for `sources.json`, since not every project runs
+    /// `dbt source freshness`. `None` means the caller never registered one;
+    /// the loader itself returns `Ok(None)` for "ran but the file doesn't
+    /// exist", distinct from an `Err` for a real read failure.
+    load_sources_freshness: Option<Box<dyn Fn() -> io::Result<Option<String>>>>,
+    sources_freshness: Mutex<Option<Arc<Vec<SourceFreshness>>>>,
+}
+
+impl<F: Fn() -> io::Result<String>> ArtifactParser<F> {
+    pub fn new(load_manifest: F) -> Self {
+        Self {
+            load_manifest,
+            manifest: Mutex::new(None),
+            load_sources_freshness: None,
+            sources_freshness: Mutex::new(None),
+        }
+    }
+
+    /// Registers a `sources.json` loader for [`load_sources_freshness`](Self::load_sources_freshness).
+    pub fn with_sources_freshness(
+        mut self,
+        load_sources_freshness: impl Fn() -> io::Result<Option<String>> + 'static,
+    ) -> Self {
+        self.load_sources_freshness = Some(Box::new(load_sources_freshness));
+        self
+    }
+
+    pub fn manifest(&self) -> io::Result<Arc<Value>> {
+        let mut cached = self.manifest.lock().expect("manifest cache lock poisoned");
+        if let Some(value) = cached.as_ref() {
+            return Ok(value.clone());
+        }
+
+        let text = (self.load_manifest)()?;
+        let value: Value = serde_json::from_str(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let value = Arc::new(value);
+        *cached = Some(value.clone());
+        Ok(value)
+    }
+
+    /// Reads and parses `sources.json` if a loader was registered via
+    /// [`with_sources_freshness`](Self::with_sources_freshness) and it
+    /// exists, otherwise returns an empty list — a project that's never run
+    /// `dbt source freshness` shouldn't fail analysis, just skip this check.
+    pub fn load_sources_freshness(&self) -> io::Result<Arc<Vec<SourceFreshness>>> {
+        let mut cached = self
+            .sources_freshness
+            .lock()
+            .expect("sources freshness cache lock poisoned");
+        if let Some(freshness) = cached.as_ref() {
+            return Ok(freshness.clone());
+        }
+
+        let freshness = match &self.load_sources_freshness {
+            Some(loader) => match loader()? {
+                Some(text) => {
+                    let value: Value = serde_json::from_str(&text)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    parse_sources_freshness(&value)
+                }
+                None => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+        let freshness = Arc::new(freshness);
+        *cached = Some(freshness.clone());
+        Ok(freshness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn manifest_is_read_from_disk_exactly_once_per_analysis() {
+        let read_count = Cell::new(0);
+        let parser = ArtifactParser::new(|| {
+            read_count.set(read_count.get() + 1);
+            Ok("{\"nodes\": {}}".to_string())
+        });
+
+        let first = parser.manifest().unwrap();
+        let second = parser.manifest().unwrap();
+        let third = parser.manifest().unwrap();
+
+        assert_eq!(read_count.get(), 1);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(Arc::ptr_eq(&second, &third));
+    }
+
+    #[test]
+    fn missing_sources_json_yields_no_freshness_results_instead_of_erroring() {
+        let parser = ArtifactParser::new(|| Ok("{\"nodes\": {}}".to_string()))
+            .with_sources_freshness(|| Ok(None));
+
+        let freshness = parser.load_sources_freshness().unwrap();
+
+        assert!(freshness.is_empty());
+    }
+
+    #[test]
+    fn a_present_sources_json_is_parsed_and_cached() {
+        let read_count = Rc::new(Cell::new(0));
+        let read_count_for_closure = read_count.clone();
+        let parser = ArtifactParser::new(|| Ok("{\"nodes\": {}}".to_string())).with_sources_freshness(move || {
+            read_count_for_closure.set(read_count_for_closure.get() + 1);
+            Ok(Some(
+                "{\"results\": [{\"unique_id\": \"source.trill_shop.raw.orders\", \"status\": \"error\"}]}".to_string(),
+            ))
+        });
+
+        let first = parser.load_sources_freshness().unwrap();
+        let second = parser.load_sources_freshness().unwrap();
+
+        assert_eq!(read_count.get(), 1);
+        assert_eq!(first.len(), 1);
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+}
+
+/// Typed, streaming manifest parsing (feature-gated).
+///
+/// Deserializes `manifest.json` directly into [`ModelInfo`] via
+/// `serde_json::from_reader`, which walks the input incrementally instead of
+/// building an intermediate `Value` tree for the whole file first. This is
+/// the low-memory path for very large manifests; the default `Value`-based
+/// `ArtifactParser::manifest` path remains available for callers that need
+/// to inspect arbitrary/unknown fields.
+#[cfg(feature = "streaming-manifest")]
+pub mod streaming {
+    use crate::manifest::{Materialization, ModelInfo};
+    use serde::Deserialize;
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::io;
+
+    #[derive(Deserialize)]
+    struct RawManifest {
+        #[serde(default)]
+        nodes: HashMap<String, RawNode>,
+    }
+
+    #[derive(Deserialize)]
+    struct RawNode {
+        name: String,
+        #[serde(default)]
+        package_name: String,
+        #[serde(default)]
+        original_file_path: String,
+        #[serde(default)]
+        patch_path: Option<String>,
+        #[serde(default)]
+        depends_on: RawDependsOn,
+        #[serde(default)]
+        config: RawConfig,
+    }
+
+    #[derive(Deserialize, Default)]
+    struct RawDependsOn {
+        #[serde(default)]
+        nodes: Vec<String>,
+    }
+
+    #[derive(Deserialize, Default)]
+    struct RawConfig {
+        materialized: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        meta: HashMap<String, Value>,
+    }
+
+    fn parse_materialization(raw: Option<&str>) -> Materialization {
+        match raw {
+            Some("table") => Materialization::Table,
+            Some("incremental") => Materialization::Incremental,
+            Some("ephemeral") => Materialization::Ephemeral,
+            Some("seed") => Materialization::Seed,
+            Some("snapshot") => Materialization::Snapshot,
+            _ => Materialization::View,
+        }
+    }
+
+    /// Parses `manifest.json` from `reader` into [`ModelInfo`]s without ever
+    /// holding the full document as a generic `Value` tree.
+    pub fn parse_manifest_streaming<R: io::Read>(reader: R) -> io::Result<Vec<ModelInfo>> {
+        let raw: RawManifest = serde_json::from_reader(reader)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        Ok(raw
+            .nodes
+            .into_iter()
+            .map(|(unique_id, node)| ModelInfo {
+                unique_id,
+                name: node.name,
+                package_name: node.package_name,
+                materialized: parse_materialization(node.config.materialized.as_deref()),
+                depends_on: node.depends_on.nodes,
+                original_file_path: node.original_file_path,
+                patch_path: node.patch_path,
+                owner: node
+                    .config
+                    .meta
+                    .get("owner")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                group: None,
+                access: crate::manifest::Access::default(),
+                tags: node.config.tags,
+                meta: node.config.meta,
+            })
+            .collect())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::io::Cursor;
+
+        #[test]
+        fn parses_a_large_generated_manifest() {
+            let node_count = 2_000;
+            let mut nodes = String::new();
+            for i in 0..node_count {
+                if i > 0 {
+                    nodes.push(',');
+                }
+                nodes.push_str(&format!(
+                    "\"model.pkg.m{i}\": {{\"name\": \"m{i}\", \"package_name\": \"pkg\", \
+                     \"original_file_path\": \"models/m{i}.sql\", \
+                     \"depends_on\": {{\"nodes\": []}}, \"config\": {{\"materialized\": \"table\"}}}}"
+                ));
+            }
+            let manifest = format!("{{\"nodes\": {{{nodes}}}}}");
+
+            let models =
+                parse_manifest_streaming(Cursor::new(manifest)).expect("manifest should parse");
+            assert_eq!(models.len(), node_count);
+        }
+
+        #[test]
+        fn tags_and_meta_owner_are_parsed_onto_the_model_info() {
+            let manifest = "{\"nodes\": {\"model.pkg.m0\": {\"name\": \"m0\", \
+                 \"package_name\": \"pkg\", \"original_file_path\": \"models/m0.sql\", \
+                 \"depends_on\": {\"nodes\": []}, \
+                 \"config\": {\"materialized\": \"table\", \"tags\": [\"nightly\"], \
+                 \"meta\": {\"owner\": \"data-eng\"}}}}}";
+
+            let models =
+                parse_manifest_streaming(Cursor::new(manifest)).expect("manifest should parse");
+
+            assert_eq!(models[0].tags, vec!["nightly".to_string()]);
+            assert_eq!(models[0].owner.as_deref(), Some("data-eng"));
+        }
+    }
+}
@@ -0,0 +1,754 @@
+//! Typed views over the raw `manifest.json` / `catalog.json` artifacts.
+//!
+//! These replace ad hoc `serde_json::Value::get(...)` chains with structs
+//! that fail to compile (or clearly `None`) when a field is missing, rather
+//! than silently returning `null` deep in an agent.
+
+use crate::warehouse::Warehouse;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
+pub struct NodeConfig {
+    #[serde(default)]
+    pub materialized: Option<String>,
+    #[serde(default)]
+    pub unique_key: Option<String>,
+    /// `merge` (the default), `delete+insert`, or `append`.
+    #[serde(default)]
+    pub incremental_strategy: Option<String>,
+    /// The dbt `groups` name this model belongs to, if any.
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Arbitrary key/value metadata a team attaches via `meta:` in
+    /// `dbt_project.yml`/`schema.yml`, e.g. `owner`, `criticality`, `pii`.
+    #[serde(default)]
+    pub meta: HashMap<String, Value>,
+    /// BigQuery partitioning config. A string (column name) or an object
+    /// (`{field, data_type, granularity, ...}`) depending on the adapter, so
+    /// kept as raw JSON rather than a typed shape.
+    #[serde(default)]
+    pub partition_by: Option<Value>,
+    /// Clustering columns. A single column name or a list, depending on the
+    /// adapter, so kept as raw JSON rather than a typed shape.
+    #[serde(default)]
+    pub cluster_by: Option<Value>,
+    /// How an incremental model reacts to a change in its column set:
+    /// `ignore` (the default), `fail`, `append_new_columns`, or `sync_all_columns`.
+    #[serde(default)]
+    pub on_schema_change: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct DependsOn {
+    #[serde(default)]
+    pub nodes: Vec<String>,
+}
+
+/// A typed `manifest.json` node (model, seed, or snapshot).
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestNode {
+    pub unique_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub resource_type: String,
+    #[serde(default)]
+    pub original_file_path: String,
+    #[serde(default)]
+    pub patch_path: Option<String>,
+    #[serde(default)]
+    pub depends_on: DependsOn,
+    #[serde(default)]
+    pub config: NodeConfig,
+    /// The model's fully-rendered SQL. Present after `dbt compile`/`dbt run`;
+    /// absent from a `dbt parse`-only manifest.
+    #[serde(default)]
+    pub compiled_code: Option<String>,
+    /// Who may reference this model (`public`, `protected`, `private`).
+    /// dbt records this at the node's top level rather than in `config`.
+    #[serde(default)]
+    pub access: Option<String>,
+}
+
+/// A `dbt parse` manifest has no compiled SQL for any node, since parsing
+/// resolves the DAG without rendering Jinja. SQL-content checks (performance,
+/// some quality rules) need `compiled_code` and must be skipped in that case;
+/// lineage and naming checks, which only need the DAG, still work fine.
+pub fn is_parse_only_manifest(nodes: &[ManifestNode]) -> bool {
+    !nodes.is_empty() && nodes.iter().all(|n| n.compiled_code.is_none())
+}
+
+/// A human-readable note for the report when [`is_parse_only_manifest`] is
+/// true, explaining why SQL-content findings are absent.
+pub const PARSE_ONLY_NOTE: &str =
+    "Manifest has no compiled SQL (looks like `dbt parse` output) — skipping SQL-content checks. Run `dbt compile` for full analysis.";
+
+/// Converts manifest nodes into the [`ModelInfo`] shape lineage analysis
+/// needs. Works the same whether or not the manifest is parse-only, since
+/// lineage only depends on the DAG, not compiled SQL.
+pub fn manifest_nodes_to_model_infos(nodes: &[ManifestNode]) -> Vec<crate::manifest::ModelInfo> {
+    nodes
+        .iter()
+        .map(|n| crate::manifest::ModelInfo {
+            unique_id: n.unique_id.clone(),
+            name: n.name.clone(),
+            package_name: n
+                .unique_id
+                .split('.')
+                .nth(1)
+                .unwrap_or_default()
+                .to_string(),
+            materialized: n
+                .config
+                .materialized
+                .as_deref()
+                .and_then(|m| serde_json::from_value(Value::String(m.to_string())).ok())
+                .unwrap_or(crate::manifest::Materialization::View),
+            depends_on: n.depends_on.nodes.clone(),
+            original_file_path: n.original_file_path.clone(),
+            patch_path: n.patch_path.clone(),
+            owner: n
+                .config
+                .meta
+                .get("owner")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .or_else(|| n.config.group.clone()),
+            group: n.config.group.clone(),
+            access: n
+                .access
+                .as_deref()
+                .and_then(|a| serde_json::from_value(Value::String(a.to_string())).ok())
+                .unwrap_or_default(),
+            tags: n.config.tags.clone(),
+            meta: n.config.meta.clone(),
+        })
+        .collect()
+}
+
+/// The compiled SQL for the model at `file_path`, for analyzers falling back
+/// to whole-file analysis when a diff isn't available (e.g. GitHub omits
+/// `patch` for very large files). `None` when no node matches or the
+/// manifest has no compiled SQL for it (see [`is_parse_only_manifest`]).
+pub fn get_model_definition<'a>(nodes: &'a [ManifestNode], file_path: &str) -> Option<&'a str> {
+    nodes
+        .iter()
+        .find(|n| n.original_file_path == file_path)?
+        .compiled_code
+        .as_deref()
+}
+
+/// Default cap on how large a single on-disk file a model-file read will
+/// load before refusing, when the manifest lacks `compiled_code` and an
+/// agent falls back to reading the source `.sql` directly for LLM context.
+pub const DEFAULT_MAX_MODEL_FILE_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum ReadModelFileError {
+    #[error("{0}: resolves outside the project directory")]
+    PathTraversal(String),
+    #[error("{path}: {size} bytes exceeds the {limit} byte limit")]
+    TooLarge { path: String, size: u64, limit: u64 },
+    #[error("{0}: {1}")]
+    Io(String, std::io::Error),
+}
+
+/// Reads `relative_path` (as recorded in the manifest's `original_file_path`)
+/// under `project_dir` for LLM context, guarding against the two ways an
+/// unbounded `fs::read_to_string` on manifest-supplied input could hurt the
+/// process: a path that resolves outside `project_dir` (traversal via `../`
+/// or an absolute path smuggled into a crafted manifest), and a file larger
+/// than `max_bytes` (a pathologically large file, or a symlink to something
+/// like `/dev/zero`, that would otherwise hang or OOM the read).
+pub fn read_model_file(
+    project_dir: &Path,
+    relative_path: &str,
+    max_bytes: u64,
+) -> Result<String, ReadModelFileError> {
+    let canonical_dir = project_dir
+        .canonicalize()
+        .map_err(|e| ReadModelFileError::Io(project_dir.display().to_string(), e))?;
+    let candidate = canonical_dir.join(relative_path);
+    let canonical_file = candidate
+        .canonicalize()
+        .map_err(|e| ReadModelFileError::Io(relative_path.to_string(), e))?;
+
+    if !canonical_file.starts_with(&canonical_dir) {
+        return Err(ReadModelFileError::PathTraversal(relative_path.to_string()));
+    }
+
+    let size = std::fs::metadata(&canonical_file)
+        .map_err(|e| ReadModelFileError::Io(relative_path.to_string(), e))?
+        .len();
+    if size > max_bytes {
+        return Err(ReadModelFileError::TooLarge {
+            path: relative_path.to_string(),
+            size,
+            limit: max_bytes,
+        });
+    }
+
+    std::fs::read_to_string(&canonical_file)
+        .map_err(|e| ReadModelFileError::Io(relative_path.to_string(), e))
+}
+
+/// Per-column stats as reported in `catalog.json`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColumnStats {
+    #[serde(rename = "type", default)]
+    pub data_type: String,
+}
+
+/// A typed `catalog.json` node.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CatalogNode {
+    pub unique_id: String,
+    #[serde(default)]
+    pub columns: HashMap<String, ColumnStats>,
+    /// Warehouse-reported table stats (row count, byte size, ...), keyed by
+    /// stat name; left untyped since the key set varies per warehouse.
+    #[serde(default)]
+    pub stats: HashMap<String, Value>,
+}
+
+/// Row count and byte size, normalized across adapters. `catalog.json`'s
+/// `stats` object uses different key names per adapter (BigQuery:
+/// `num_rows`/`num_bytes`; Snowflake: `row_count`/`bytes`); cost estimators
+/// should depend on this instead of an adapter-specific key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CatalogStats {
+    pub row_count: u64,
+    pub byte_size: u64,
+}
+
+/// JSON Pointer paths (RFC 6901) into a `catalog.json` node's `stats` object
+/// for the row-count and byte-size entries, since each adapter names and
+/// nests them differently. `catalog.json` reports every stat as `{"id":
+/// ..., "label": ..., "value": ..., "include": ...}`, so the pointer always
+/// ends in `/value`; only the stat's key name varies per adapter.
+fn stats_pointers(warehouse: &Warehouse) -> Option<(&'static str, &'static str)> {
+    match warehouse {
+        Warehouse::BigQuery => Some(("/num_rows/value", "/num_bytes/value")),
+        Warehouse::Snowflake => Some(("/row_count/value", "/bytes/value")),
+        Warehouse::Redshift => Some(("/rows/value", "/size/value")),
+        Warehouse::Postgres | Warehouse::Other(_) => None,
+    }
+}
+
+/// Extracts [`CatalogStats`] from `node.stats` by resolving `warehouse`'s
+/// adapter-specific [`stats_pointers`] against it as data-driven JSON
+/// Pointer paths, rather than branching on adapter in extraction code.
+/// Returns `None` for an adapter with no known stats keys, or when the
+/// catalog was generated without stats (e.g. `dbt docs generate
+/// --no-compile` on some adapters).
+pub fn extract_catalog_stats(node: &CatalogNode, warehouse: &Warehouse) -> Option<CatalogStats> {
+    let (rows_pointer, bytes_pointer) = stats_pointers(warehouse)?;
+    let stats = Value::Object(node.stats.clone().into_iter().collect());
+    let row_count = stats.pointer(rows_pointer)?.as_u64()?;
+    let byte_size = stats.pointer(bytes_pointer)?.as_u64()?;
+    Some(CatalogStats {
+        row_count,
+        byte_size,
+    })
+}
+
+/// Whether [`extract_catalog_stats`] would find usable stats for `node`.
+pub fn has_catalog_stats(node: &CatalogNode, warehouse: &Warehouse) -> bool {
+    extract_catalog_stats(node, warehouse).is_some()
+}
+
+/// Parses every node out of a `manifest.json` document already loaded as a
+/// [`Value`] (see [`crate::artifact::ArtifactParser`]).
+pub fn parse_manifest_nodes(manifest: &Value) -> Vec<ManifestNode> {
+    let Some(nodes) = manifest.get("nodes").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    nodes
+        .values()
+        .filter_map(|node| serde_json::from_value(node.clone()).ok())
+        .collect()
+}
+
+/// A typed `manifest.json` entry from the `sources` map (a `dbt source`
+/// declaration, as opposed to [`SourceFreshness`], which is that source's
+/// most recent freshness *result* from `sources.json`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestSource {
+    pub unique_id: String,
+    pub source_name: String,
+    pub name: String,
+    #[serde(default)]
+    pub database: Option<String>,
+    #[serde(default)]
+    pub schema: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Parses every entry out of a `manifest.json` document's `sources` map.
+/// Absent (older manifests parsed with no sources declared) or malformed
+/// entries are skipped rather than failing the whole parse, matching
+/// [`parse_manifest_nodes`].
+pub fn parse_manifest_sources(manifest: &Value) -> Vec<ManifestSource> {
+    let Some(sources) = manifest.get("sources").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    sources
+        .values()
+        .filter_map(|source| serde_json::from_value(source.clone()).ok())
+        .collect()
+}
+
+/// A typed `manifest.json` entry from the `exposures` map: a dashboard,
+/// notebook, or application declared to depend on one or more models.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestExposure {
+    pub unique_id: String,
+    pub name: String,
+    /// `dashboard`, `notebook`, `analysis`, `ml`, or `application`.
+    #[serde(default, rename = "type")]
+    pub exposure_type: Option<String>,
+    #[serde(default)]
+    pub depends_on: DependsOn,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Parses every entry out of a `manifest.json` document's `exposures` map.
+pub fn parse_manifest_exposures(manifest: &Value) -> Vec<ManifestExposure> {
+    let Some(exposures) = manifest.get("exposures").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    exposures
+        .values()
+        .filter_map(|exposure| serde_json::from_value(exposure.clone()).ok())
+        .collect()
+}
+
+/// A typed `manifest.json` entry from the `metrics` map: a named,
+/// reusable metric definition (e.g. "weekly active users").
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManifestMetric {
+    pub unique_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub depends_on: DependsOn,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Parses every entry out of a `manifest.json` document's `metrics` map.
+pub fn parse_manifest_metrics(manifest: &Value) -> Vec<ManifestMetric> {
+    let Some(metrics) = manifest.get("metrics").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    metrics
+        .values()
+        .filter_map(|metric| serde_json::from_value(metric.clone()).ok())
+        .collect()
+}
+
+/// The dbt manifest schema version (e.g. `12` for a manifest generated by a
+/// dbt-core release that emits schema `v12`), read from
+/// `metadata.dbt_schema_version`'s trailing `vN` segment
+/// (`https://schemas.getdbt.com/dbt/manifest/v12.json`). `None` if the field
+/// is missing or doesn't match the expected shape, which every parser above
+/// tolerates the same way it tolerates any other missing field: every
+/// manifest from v7 through v12 that this crate has been run against uses
+/// the same node/source/exposure field names this module reads, so no
+/// version-specific branching has been needed yet — this is here so a
+/// caller that DOES hit a breaking future schema change has something to
+/// log and gate on.
+pub fn manifest_schema_version(manifest: &Value) -> Option<u32> {
+    let raw = manifest
+        .get("metadata")?
+        .get("dbt_schema_version")?
+        .as_str()?;
+    let version = raw.rsplit('/').next()?.strip_prefix('v')?;
+    version.trim_end_matches(".json").parse().ok()
+}
+
+/// Parses every node out of a `catalog.json` document already loaded as a
+/// [`Value`].
+pub fn parse_catalog_nodes(catalog: &Value) -> Vec<CatalogNode> {
+    let Some(nodes) = catalog.get("nodes").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    nodes
+        .values()
+        .filter_map(|node| serde_json::from_value(node.clone()).ok())
+        .collect()
+}
+
+/// One source's result from `dbt source freshness`, as recorded in
+/// `sources.json`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SourceFreshness {
+    pub unique_id: String,
+    /// `pass`, `warn`, `error`, or `runtime error`.
+    pub status: String,
+}
+
+/// Whether `freshness` should block trust in a rebuild against this source:
+/// anything short of a clean `pass`.
+pub fn is_stale(freshness: &SourceFreshness) -> bool {
+    freshness.status != "pass"
+}
+
+/// Parses every source's freshness result out of a `sources.json` document
+/// already loaded as a [`Value`]. `sources.json` nests results under
+/// `results` rather than `nodes`, unlike `manifest.json`/`catalog.json`.
+pub fn parse_sources_freshness(sources: &Value) -> Vec<SourceFreshness> {
+    let Some(results) = sources.get("results").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    results
+        .iter()
+        .filter_map(|result| serde_json::from_value(result.clone()).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deserializes_a_sample_manifest_node() {
+        let manifest = json!({
+            "nodes": {
+                "model.trill_shop.stg_orders": {
+                    "unique_id": "model.trill_shop.stg_orders",
+                    "name": "stg_orders",
+                    "resource_type": "model",
+                    "original_file_path": "models/staging/stg_orders.sql",
+                    "depends_on": { "nodes": ["source.trill_shop.raw.orders"] },
+                    "config": { "materialized": "view" },
+                }
+            }
+        });
+
+        let nodes = parse_manifest_nodes(&manifest);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].name, "stg_orders");
+        assert_eq!(nodes[0].config.materialized.as_deref(), Some("view"));
+        assert_eq!(
+            nodes[0].depends_on.nodes,
+            vec!["source.trill_shop.raw.orders".to_string()]
+        );
+    }
+
+    #[test]
+    fn parses_sources_and_exposures_out_of_a_manifest() {
+        let manifest = json!({
+            "nodes": {},
+            "sources": {
+                "source.trill_shop.raw.orders": {
+                    "unique_id": "source.trill_shop.raw.orders",
+                    "source_name": "raw",
+                    "name": "orders",
+                    "database": "trill_shop",
+                    "schema": "raw",
+                }
+            },
+            "exposures": {
+                "exposure.trill_shop.orders_dashboard": {
+                    "unique_id": "exposure.trill_shop.orders_dashboard",
+                    "name": "orders_dashboard",
+                    "type": "dashboard",
+                    "depends_on": { "nodes": ["model.trill_shop.orders_summary"] },
+                }
+            },
+        });
+
+        let sources = parse_manifest_sources(&manifest);
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].source_name, "raw");
+        assert_eq!(sources[0].name, "orders");
+
+        let exposures = parse_manifest_exposures(&manifest);
+        assert_eq!(exposures.len(), 1);
+        assert_eq!(exposures[0].name, "orders_dashboard");
+        assert_eq!(
+            exposures[0].depends_on.nodes,
+            vec!["model.trill_shop.orders_summary".to_string()]
+        );
+    }
+
+    #[test]
+    fn a_manifest_with_no_sources_or_exposures_parses_to_empty_lists() {
+        let manifest = json!({ "nodes": {} });
+
+        assert!(parse_manifest_sources(&manifest).is_empty());
+        assert!(parse_manifest_exposures(&manifest).is_empty());
+        assert!(parse_manifest_metrics(&manifest).is_empty());
+    }
+
+    #[test]
+    fn parses_a_metric_and_its_dependencies() {
+        let manifest = json!({
+            "nodes": {},
+            "metrics": {
+                "metric.trill_shop.weekly_active_users": {
+                    "unique_id": "metric.trill_shop.weekly_active_users",
+                    "name": "weekly_active_users",
+                    "depends_on": { "nodes": ["model.trill_shop.orders_summary"] },
+                }
+            },
+        });
+
+        let metrics = parse_manifest_metrics(&manifest);
+
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].name, "weekly_active_users");
+        assert_eq!(
+            metrics[0].depends_on.nodes,
+            vec!["model.trill_shop.orders_summary".to_string()]
+        );
+    }
+
+    #[test]
+    fn manifest_schema_version_reads_the_trailing_version_segment() {
+        let manifest = json!({
+            "metadata": { "dbt_schema_version": "https://schemas.getdbt.com/dbt/manifest/v12.json" },
+        });
+
+        assert_eq!(manifest_schema_version(&manifest), Some(12));
+    }
+
+    #[test]
+    fn manifest_schema_version_is_none_when_metadata_is_absent() {
+        let manifest = json!({ "nodes": {} });
+
+        assert_eq!(manifest_schema_version(&manifest), None);
+    }
+
+    #[test]
+    fn parse_only_manifest_still_produces_a_lineage_report_without_erroring() {
+        let manifest = json!({
+            "nodes": {
+                "model.trill_shop.stg_orders": {
+                    "unique_id": "model.trill_shop.stg_orders",
+                    "name": "stg_orders",
+                    "resource_type": "model",
+                    "original_file_path": "models/staging/stg_orders.sql",
+                    "depends_on": { "nodes": [] },
+                    "config": { "materialized": "view" },
+                },
+                "model.trill_shop.orders_summary": {
+                    "unique_id": "model.trill_shop.orders_summary",
+                    "name": "orders_summary",
+                    "resource_type": "model",
+                    "original_file_path": "models/marts/orders_summary.sql",
+                    "depends_on": { "nodes": ["model.trill_shop.stg_orders"] },
+                    "config": { "materialized": "table" },
+                }
+            }
+        });
+
+        let nodes = parse_manifest_nodes(&manifest);
+        assert!(is_parse_only_manifest(&nodes));
+
+        let models = manifest_nodes_to_model_infos(&nodes);
+        let graph = crate::lineage::LineageGraph::from_models(models);
+        let impacts = graph.analyze_impact(&["model.trill_shop.stg_orders".to_string()]);
+
+        assert_eq!(impacts.len(), 1);
+        assert_eq!(impacts[0].unique_id, "model.trill_shop.orders_summary");
+    }
+
+    #[test]
+    fn tags_and_meta_owner_are_parsed_onto_the_model_info() {
+        let manifest = json!({
+            "nodes": {
+                "model.trill_shop.stg_orders": {
+                    "unique_id": "model.trill_shop.stg_orders",
+                    "name": "stg_orders",
+                    "resource_type": "model",
+                    "original_file_path": "models/staging/stg_orders.sql",
+                    "depends_on": { "nodes": [] },
+                    "config": {
+                        "materialized": "view",
+                        "tags": ["staging", "nightly"],
+                        "meta": { "owner": "data-eng" },
+                    },
+                }
+            }
+        });
+
+        let nodes = parse_manifest_nodes(&manifest);
+        let models = manifest_nodes_to_model_infos(&nodes);
+
+        assert_eq!(models[0].tags, vec!["staging".to_string(), "nightly".to_string()]);
+        assert_eq!(models[0].owner.as_deref(), Some("data-eng"));
+    }
+
+    #[test]
+    fn deserializes_a_sample_catalog_node() {
+        let catalog = json!({
+            "nodes": {
+                "model.trill_shop.stg_orders": {
+                    "unique_id": "model.trill_shop.stg_orders",
+                    "columns": { "order_id": { "type": "INT64" } },
+                    "stats": { "num_rows": 1000 },
+                }
+            }
+        });
+
+        let nodes = parse_catalog_nodes(&catalog);
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].columns.get("order_id").unwrap().data_type, "INT64");
+    }
+
+    #[test]
+    fn normalizes_bigquery_style_stats() {
+        let catalog = json!({
+            "nodes": {
+                "model.trill_shop.stg_orders": {
+                    "unique_id": "model.trill_shop.stg_orders",
+                    "stats": {
+                        "num_rows": { "id": "num_rows", "label": "# Rows", "value": 1000, "include": true },
+                        "num_bytes": { "id": "num_bytes", "label": "Approximate Size", "value": 50000, "include": true },
+                    },
+                }
+            }
+        });
+        let node = &parse_catalog_nodes(&catalog)[0];
+
+        let stats = extract_catalog_stats(node, &Warehouse::BigQuery)
+            .expect("bigquery stats should be found");
+        assert_eq!(
+            stats,
+            CatalogStats {
+                row_count: 1000,
+                byte_size: 50000
+            }
+        );
+    }
+
+    #[test]
+    fn normalizes_snowflake_style_stats() {
+        let catalog = json!({
+            "nodes": {
+                "model.trill_shop.stg_orders": {
+                    "unique_id": "model.trill_shop.stg_orders",
+                    "stats": {
+                        "row_count": { "id": "row_count", "label": "Row Count", "value": 2000, "include": true },
+                        "bytes": { "id": "bytes", "label": "Bytes", "value": 90000, "include": true },
+                    },
+                }
+            }
+        });
+        let node = &parse_catalog_nodes(&catalog)[0];
+
+        let stats = extract_catalog_stats(node, &Warehouse::Snowflake)
+            .expect("snowflake stats should be found");
+        assert_eq!(
+            stats,
+            CatalogStats {
+                row_count: 2000,
+                byte_size: 90000
+            }
+        );
+    }
+
+    #[test]
+    fn missing_stats_return_none_instead_of_erroring() {
+        let catalog = json!({
+            "nodes": {
+                "model.trill_shop.stg_orders": {
+                    "unique_id": "model.trill_shop.stg_orders",
+                    "stats": {},
+                }
+            }
+        });
+        let node = &parse_catalog_nodes(&catalog)[0];
+
+        assert!(!has_catalog_stats(node, &Warehouse::BigQuery));
+    }
+
+    #[test]
+    fn parses_a_stale_source_out_of_a_sources_json_fixture() {
+        let sources = json!({
+            "results": [
+                { "unique_id": "source.trill_shop.raw.orders", "status": "error" },
+                { "unique_id": "source.trill_shop.raw.customers", "status": "pass" },
+            ]
+        });
+
+        let freshness = parse_sources_freshness(&sources);
+
+        assert_eq!(freshness.len(), 2);
+        let orders = freshness
+            .iter()
+            .find(|f| f.unique_id == "source.trill_shop.raw.orders")
+            .unwrap();
+        let customers = freshness
+            .iter()
+            .find(|f| f.unique_id == "source.trill_shop.raw.customers")
+            .unwrap();
+        assert!(is_stale(orders));
+        assert!(!is_stale(customers));
+    }
+
+    #[test]
+    fn a_missing_results_key_produces_no_freshness_results() {
+        assert!(parse_sources_freshness(&json!({})).is_empty());
+    }
+
+    fn temp_project_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "dbt-pr-agent-read-model-file-test-{name}-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("models")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn reads_a_model_file_within_the_size_limit() {
+        let dir = temp_project_dir("happy-path");
+        std::fs::write(dir.join("models/stg_orders.sql"), "select 1").unwrap();
+
+        let sql =
+            read_model_file(&dir, "models/stg_orders.sql", DEFAULT_MAX_MODEL_FILE_BYTES).unwrap();
+
+        assert_eq!(sql, "select 1");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_file_larger_than_the_limit_is_rejected() {
+        let dir = temp_project_dir("oversized");
+        std::fs::write(dir.join("models/huge.sql"), "select 1 -- padding").unwrap();
+
+        let err = read_model_file(&dir, "models/huge.sql", 5).unwrap_err();
+
+        assert!(matches!(err, ReadModelFileError::TooLarge { limit: 5, .. }));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn a_path_that_escapes_the_project_dir_is_rejected() {
+        let dir = temp_project_dir("traversal");
+        let outside = dir
+            .parent()
+            .unwrap()
+            .join(format!("outside-{}.sql", std::process::id()));
+        std::fs::write(&outside, "select 1").unwrap();
+
+        let relative = format!("../{}", outside.file_name().unwrap().to_str().unwrap());
+        let err = read_model_file(&dir, &relative, DEFAULT_MAX_MODEL_FILE_BYTES).unwrap_err();
+
+        assert!(matches!(err, ReadModelFileError::PathTraversal(_)));
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_file(&outside);
+    }
+}
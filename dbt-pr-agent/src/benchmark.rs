@@ -0,0 +1,163 @@
+//! Per-phase timing for profiling an analysis run.
+//!
+//! There's no async runtime in this crate (see [`crate::cancellation`]), so
+//! timing a phase is just a wall-clock measurement around a synchronous
+//! closure; [`time_phase`] is the seam every phase in the `benchmark`
+//! subcommand (see `main.rs`) goes through.
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// A stage of the analysis pipeline, timed independently so a slow run can
+/// be attributed to a specific stage instead of just "it was slow".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    ManifestLoad,
+    GraphBuild,
+    ImpactTraversal,
+    Quality,
+    Performance,
+    ReportSynthesis,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 6] = [
+        Phase::ManifestLoad,
+        Phase::GraphBuild,
+        Phase::ImpactTraversal,
+        Phase::Quality,
+        Phase::Performance,
+        Phase::ReportSynthesis,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Phase::ManifestLoad => "manifest load",
+            Phase::GraphBuild => "graph build",
+            Phase::ImpactTraversal => "impact traversal",
+            Phase::Quality => "quality",
+            Phase::Performance => "performance",
+            Phase::ReportSynthesis => "report synthesis",
+        }
+    }
+}
+
+/// Runs `f`, returning its result alongside how long it took.
+pub fn time_phase<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+/// Mean and p95 timing for one [`Phase`] across every iteration of a
+/// [`BenchmarkReport`], in milliseconds (JSON output is meant for tracking
+/// over time in an external system, where a plain number is easier to graph
+/// than a serialized [`Duration`]).
+#[derive(Debug, Clone, Serialize)]
+pub struct PhaseStats {
+    pub phase: String,
+    pub mean_ms: f64,
+    pub p95_ms: f64,
+}
+
+/// Summarizes `samples` (one per benchmark iteration) for `phase`. Panics if
+/// `samples` is empty; callers always supply one sample per iteration and
+/// [`run`] never calls this with zero iterations.
+fn summarize(phase: Phase, samples: &mut [Duration]) -> PhaseStats {
+    samples.sort();
+    let mean = samples.iter().sum::<Duration>() / samples.len() as u32;
+    // Nearest-rank method: the smallest sample at or above the 95th percentile.
+    let p95_index = ((samples.len() as f64 * 0.95).ceil() as usize)
+        .saturating_sub(1)
+        .min(samples.len() - 1);
+    PhaseStats {
+        phase: phase.label().to_string(),
+        mean_ms: mean.as_secs_f64() * 1000.0,
+        p95_ms: samples[p95_index].as_secs_f64() * 1000.0,
+    }
+}
+
+/// The result of running [`run`]: mean/p95 timings for every [`Phase`],
+/// across `iterations` runs of the pipeline.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    pub iterations: usize,
+    pub phases: Vec<PhaseStats>,
+}
+
+impl BenchmarkReport {
+    /// Builds a report from per-iteration samples: one [`Duration`] per
+    /// [`Phase`] per iteration, keyed by [`Phase::ALL`]'s order.
+    pub fn from_samples(iterations: usize, mut samples_by_phase: Vec<(Phase, Vec<Duration>)>) -> Self {
+        let phases = samples_by_phase
+            .iter_mut()
+            .map(|(phase, samples)| summarize(*phase, samples))
+            .collect();
+        Self { iterations, phases }
+    }
+
+    /// Renders as a plain-text table, one row per phase.
+    pub fn to_table(&self) -> String {
+        let mut out = format!(
+            "{:<20} {:>12} {:>12}\n",
+            "phase", "mean (ms)", "p95 (ms)"
+        );
+        for stats in &self.phases {
+            out.push_str(&format!(
+                "{:<20} {:>12.2} {:>12.2}\n",
+                stats.phase, stats.mean_ms, stats.p95_ms
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_phase_returns_the_closures_result_and_a_nonzero_duration() {
+        let (value, elapsed) = time_phase(|| {
+            std::thread::sleep(Duration::from_millis(1));
+            42
+        });
+
+        assert_eq!(value, 42);
+        assert!(elapsed >= Duration::from_millis(1));
+    }
+
+    #[test]
+    fn report_contains_a_timing_entry_for_every_phase() {
+        let samples_by_phase: Vec<(Phase, Vec<Duration>)> = Phase::ALL
+            .iter()
+            .map(|&phase| (phase, vec![Duration::from_millis(1), Duration::from_millis(3)]))
+            .collect();
+
+        let report = BenchmarkReport::from_samples(2, samples_by_phase);
+
+        assert_eq!(report.phases.len(), Phase::ALL.len());
+        for phase in Phase::ALL {
+            assert!(
+                report.phases.iter().any(|p| p.phase == phase.label()),
+                "missing timing entry for {}",
+                phase.label()
+            );
+        }
+    }
+
+    #[test]
+    fn mean_and_p95_are_computed_from_the_samples() {
+        let samples = vec![
+            Duration::from_millis(10),
+            Duration::from_millis(20),
+            Duration::from_millis(30),
+            Duration::from_millis(40),
+        ];
+        let report = BenchmarkReport::from_samples(4, vec![(Phase::Quality, samples)]);
+
+        let stats = &report.phases[0];
+        assert_eq!(stats.mean_ms, 25.0);
+        assert_eq!(stats.p95_ms, 40.0);
+    }
+}
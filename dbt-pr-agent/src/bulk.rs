@@ -0,0 +1,126 @@
+//! Repo-wide analysis across every open PR, for a nightly risk report.
+//!
+//! This crate has no async runtime (see [`crate::cancellation`]), so "bounded
+//! concurrency" here means real OS threads capped at a chunk size, not an
+//! async executor's task queue.
+
+use crate::github::OpenPr;
+use crate::report::{Priority, Recommendation};
+
+/// One PR's contribution to the nightly report: its recommendations reduced
+/// to a single sortable number.
+#[derive(Debug, Clone)]
+pub struct PrRiskSummary {
+    pub pr_number: u64,
+    pub title: String,
+    pub risk_score: u32,
+    pub recommendations: Vec<Recommendation>,
+}
+
+/// Weights each recommendation by [`Priority`] and sums them, so a PR with
+/// one high-priority finding ranks above one with several low-priority ones.
+pub fn risk_score(recommendations: &[Recommendation]) -> u32 {
+    recommendations
+        .iter()
+        .map(|r| match r.priority {
+            Priority::Low => 1,
+            Priority::Medium => 3,
+            Priority::High => 5,
+        })
+        .sum()
+}
+
+/// Runs `analyze_one` over every PR in `prs`, at most `max_concurrent` at a
+/// time, and returns summaries ranked highest-risk first.
+///
+/// Respects GitHub's rate limits by construction: chunking bounds the number
+/// of in-flight requests `analyze_one` can issue at once, rather than firing
+/// all of them at once.
+pub fn analyze_repo<F>(prs: &[OpenPr], max_concurrent: usize, analyze_one: F) -> Vec<PrRiskSummary>
+where
+    F: Fn(&OpenPr) -> Vec<Recommendation> + Sync,
+{
+    let max_concurrent = max_concurrent.max(1);
+    let mut summaries = Vec::with_capacity(prs.len());
+
+    for chunk in prs.chunks(max_concurrent) {
+        let chunk_summaries: Vec<PrRiskSummary> = std::thread::scope(|scope| {
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|pr| {
+                    scope.spawn(|| {
+                        let recommendations = analyze_one(pr);
+                        PrRiskSummary {
+                            pr_number: pr.number,
+                            title: pr.title.clone(),
+                            risk_score: risk_score(&recommendations),
+                            recommendations,
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("analysis thread panicked"))
+                .collect()
+        });
+        summaries.extend(chunk_summaries);
+    }
+
+    summaries.sort_by(|a, b| {
+        b.risk_score
+            .cmp(&a.risk_score)
+            .then_with(|| a.pr_number.cmp(&b.pr_number))
+    });
+    summaries
+}
+
+/// Renders a plain-text table for terminal/log output, highest risk first.
+pub fn render_table(summaries: &[PrRiskSummary]) -> String {
+    summaries
+        .iter()
+        .map(|s| format!("#{:<6} risk={:<4} {}", s.pr_number, s.risk_score, s.title))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AgentKind;
+
+    #[test]
+    fn two_mocked_prs_are_analyzed_and_ranked_by_risk() {
+        let response = serde_json::json!([
+            { "number": 12, "title": "Add stg_payments", "head": { "sha": "aaa111" } },
+            { "number": 13, "title": "Fix orders_summary join", "head": { "sha": "bbb222" } },
+        ]);
+        let prs = crate::github::parse_open_prs(&response);
+
+        let summaries = analyze_repo(&prs, 2, |pr| {
+            if pr.number == 12 {
+                vec![Recommendation {
+                    source: AgentKind::Quality,
+                    message: "missing test".to_string(),
+                    priority: Priority::High,
+                    confidence: None,
+                }]
+            } else {
+                vec![Recommendation {
+                    source: AgentKind::Quality,
+                    message: "minor style nit".to_string(),
+                    priority: Priority::Low,
+                    confidence: None,
+                }]
+            }
+        });
+
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(
+            summaries[0].pr_number, 12,
+            "the high-priority finding should rank first"
+        );
+        assert_eq!(summaries[0].risk_score, 5);
+        assert_eq!(summaries[1].pr_number, 13);
+    }
+}
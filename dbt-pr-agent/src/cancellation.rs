@@ -0,0 +1,47 @@
+//! A minimal, dependency-free cancellation signal for long-running analyses
+//! (LLM tool-call loops, big graph traversals) so a caller can request a
+//! prompt abort without the run leaking partial state.
+//!
+//! There's no async runtime in this crate, so this is a plain
+//! `Arc<AtomicBool>` flag checked cooperatively between steps, not a tokio
+//! `CancellationToken`. Each cancellable loop is expected to check
+//! [`CancellationToken::is_cancelled`] before starting its next unit of work.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A cloneable, cooperative cancellation flag. Cloning shares the same
+/// underlying signal, so cancelling any clone cancels all of them.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation. Idempotent.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelling_a_clone_is_visible_on_the_original() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        clone.cancel();
+        assert!(token.is_cancelled());
+    }
+}
@@ -0,0 +1,975 @@
+//! Runtime configuration for an analysis run.
+//!
+//! Configuration is normally loaded from a config file, but individual
+//! options can be overridden per-PR via a directive block in the PR
+//! description (see [`PrOverrides::parse_from_description`]).
+
+use crate::filter::FileFilter;
+use crate::notify::NotifyConfig;
+use crate::orchestrator::{DEFAULT_MAX_PARALLEL_AGENTS, DEFAULT_SUMMARY_MODE_THRESHOLD};
+use crate::risk_rules::RiskRule;
+use crate::severity::Severity;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Which built-in agent produced a given finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentKind {
+    Impact,
+    Performance,
+    Quality,
+}
+
+/// Minimum severity that should cause the overall gate to fail.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum FailOn {
+    /// Never fail the gate regardless of findings.
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// The set of options that control a single analysis run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeOptions {
+    pub agents: HashSet<AgentKind>,
+    pub fail_on: FailOn,
+    /// Fraction (0.0-1.0) of test coverage required to approve a PR.
+    pub min_coverage: f64,
+    #[serde(default)]
+    pub file_filter: FileFilter,
+    /// Above how many changed models a PR falls back to summary-only
+    /// analysis (see [`crate::orchestrator::AnalysisMode`]).
+    #[serde(default = "default_summary_mode_threshold")]
+    pub summary_mode_threshold: usize,
+    /// Model names or globs (e.g. `revenue_mart`, `finance_*`) that always
+    /// require extra scrutiny when touched directly or by a close
+    /// downstream change, regardless of computed risk.
+    #[serde(default)]
+    pub protected_models: Vec<String>,
+    /// How many changed models' agent analysis runs concurrently (see
+    /// [`crate::orchestrator::run_detailed_or_summary_concurrent`]).
+    #[serde(default = "default_max_parallel_agents")]
+    pub max_parallel_agents: usize,
+    /// Turn a stale-artifact warning (see
+    /// [`crate::orchestrator::stale_artifact_anomaly`]) into a hard error
+    /// instead of just a report finding.
+    #[serde(default)]
+    pub require_fresh_artifacts: bool,
+    /// Org-declared rules that escalate the computed risk level (see
+    /// [`crate::risk_rules::apply_risk_rules`]).
+    #[serde(default)]
+    pub risk_rules: Vec<RiskRule>,
+    /// Apply the same blocking gate to draft PRs as to ready-for-review
+    /// ones. Off by default: a draft is still in progress, so findings are
+    /// reported but capped at [`ApprovalStatus::ChangesRequested`] rather
+    /// than [`ApprovalStatus::Blocked`] (see [`approval_status`]).
+    #[serde(default)]
+    pub gate_drafts: bool,
+    /// Minimum [`crate::report::Recommendation::confidence`] a finding needs
+    /// to appear in the main report body; anything below is moved to the
+    /// low-confidence appendix instead of being dropped outright (see
+    /// [`crate::report::partition_by_confidence`]). A finding with no
+    /// recorded confidence (deterministic, non-LLM checks) always passes.
+    #[serde(default = "default_min_finding_confidence")]
+    pub min_finding_confidence: f64,
+    /// Lines of surrounding SQL to include above and below a finding's line
+    /// when rendering it (see [`crate::render::render_context_block`]), so a
+    /// reviewer can judge a finding without opening the file.
+    #[serde(default = "default_diff_context_lines")]
+    pub diff_context_lines: usize,
+    /// Maximum additional warehouse cost (see
+    /// [`crate::agents::cost::CostAnalysis::total_estimated_dollars`]) a PR
+    /// may add before the gate blocks it. `None` means no cost gate at all,
+    /// the same "opt-in threshold" convention as
+    /// [`protected_models`](Self::protected_models) being empty.
+    #[serde(default)]
+    pub max_cost_increase_dollars: Option<f64>,
+    /// Where to route findings by model ownership (see
+    /// [`crate::notify::route_by_owner`]), in addition to the usual
+    /// summary comment. `None` means notifications are off, the same
+    /// "opt-in" convention [`max_cost_increase_dollars`](Self::max_cost_increase_dollars) uses.
+    #[serde(default)]
+    pub notify: Option<NotifyConfig>,
+}
+
+fn default_min_finding_confidence() -> f64 {
+    DEFAULT_MIN_FINDING_CONFIDENCE
+}
+
+/// See [`RuntimeOptions::min_finding_confidence`].
+pub const DEFAULT_MIN_FINDING_CONFIDENCE: f64 = 0.6;
+
+fn default_diff_context_lines() -> usize {
+    DEFAULT_DIFF_CONTEXT_LINES
+}
+
+/// See [`RuntimeOptions::diff_context_lines`].
+pub const DEFAULT_DIFF_CONTEXT_LINES: usize = 3;
+
+fn default_summary_mode_threshold() -> usize {
+    DEFAULT_SUMMARY_MODE_THRESHOLD
+}
+
+fn default_max_parallel_agents() -> usize {
+    crate::orchestrator::DEFAULT_MAX_PARALLEL_AGENTS
+}
+
+impl Default for RuntimeOptions {
+    fn default() -> Self {
+        Self {
+            agents: [
+                AgentKind::Impact,
+                AgentKind::Performance,
+                AgentKind::Quality,
+            ]
+            .into_iter()
+            .collect(),
+            fail_on: FailOn::High,
+            min_coverage: 0.7,
+            file_filter: FileFilter::default(),
+            summary_mode_threshold: DEFAULT_SUMMARY_MODE_THRESHOLD,
+            protected_models: Vec::new(),
+            max_parallel_agents: DEFAULT_MAX_PARALLEL_AGENTS,
+            require_fresh_artifacts: false,
+            risk_rules: Vec::new(),
+            gate_drafts: false,
+            min_finding_confidence: DEFAULT_MIN_FINDING_CONFIDENCE,
+            diff_context_lines: DEFAULT_DIFF_CONTEXT_LINES,
+            max_cost_increase_dollars: None,
+            notify: None,
+        }
+    }
+}
+
+/// A named strictness preset. Selectable via `--profile` or config, and
+/// still overridable field-by-field afterward.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    Strict,
+    Balanced,
+    Lenient,
+}
+
+impl Profile {
+    /// Populates a fresh [`RuntimeOptions`] with this profile's presets.
+    pub fn apply(self, mut options: RuntimeOptions) -> RuntimeOptions {
+        let (fail_on, min_coverage) = match self {
+            Profile::Strict => (FailOn::Medium, 0.9),
+            Profile::Balanced => (FailOn::High, 0.7),
+            Profile::Lenient => (FailOn::Critical, 0.0),
+        };
+        options.fail_on = fail_on;
+        options.min_coverage = min_coverage;
+        options
+    }
+}
+
+/// Whether the PR is cleared to merge under the resolved [`RuntimeOptions`].
+///
+/// Ordered from most to least permissive, so a caller can escalate a
+/// computed status with `.max(...)` without caring which branch produced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Approved,
+    /// At least one protected model was touched; requires human sign-off
+    /// even though the computed risk alone wouldn't have blocked the PR.
+    ChangesRequested,
+    Blocked,
+}
+
+/// Decides [`ApprovalStatus`] from the worst severity found and measured
+/// coverage, against the thresholds in `options`, then escalates to at least
+/// [`ApprovalStatus::ChangesRequested`] when `protected_model_touched` is
+/// true — a protected model (`options.protected_models`) can only ever raise
+/// the outcome, never relax a `Blocked` computed from severity/coverage.
+///
+/// A draft PR (`is_draft`) is still work-in-progress, so unless
+/// `options.gate_drafts` opts back in, the result is capped at
+/// [`ApprovalStatus::ChangesRequested`] — findings are still reported, but a
+/// draft is never [`ApprovalStatus::Blocked`].
+///
+/// `estimated_cost_increase_dollars` is compared against
+/// `options.max_cost_increase_dollars`; `None` on either side means the cost
+/// gate doesn't apply (no estimate available, or no threshold configured).
+pub fn approval_status(
+    options: &RuntimeOptions,
+    max_severity: Option<Severity>,
+    coverage: f64,
+    protected_model_touched: bool,
+    is_draft: bool,
+    estimated_cost_increase_dollars: Option<f64>,
+) -> ApprovalStatus {
+    let severity_blocks = max_severity.is_some_and(|s| severity_gate_level(s) >= options.fail_on)
+        && options.fail_on != FailOn::None;
+    let coverage_blocks = coverage < options.min_coverage;
+    let cost_blocks = cost_gate_exceeded(options, estimated_cost_increase_dollars);
+
+    let computed = if severity_blocks || coverage_blocks || cost_blocks {
+        ApprovalStatus::Blocked
+    } else {
+        ApprovalStatus::Approved
+    };
+
+    let computed = if protected_model_touched {
+        computed.max(ApprovalStatus::ChangesRequested)
+    } else {
+        computed
+    };
+
+    if is_draft && !options.gate_drafts {
+        computed.min(ApprovalStatus::ChangesRequested)
+    } else {
+        computed
+    }
+}
+
+/// True when `estimated_cost_increase_dollars` exceeds
+/// `options.max_cost_increase_dollars`. `None` on either side (no estimate,
+/// or no configured threshold) never blocks.
+fn cost_gate_exceeded(options: &RuntimeOptions, estimated_cost_increase_dollars: Option<f64>) -> bool {
+    options
+        .max_cost_increase_dollars
+        .zip(estimated_cost_increase_dollars)
+        .is_some_and(|(max, actual)| actual > max)
+}
+
+/// A compact, machine-readable verdict for CI systems (`--gate-output`),
+/// independent of the human-facing report format so a pipeline can parse one
+/// small file instead of the full report to decide downstream job behavior.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateSummary {
+    /// `None` when analysis failed before a status could be computed; see
+    /// [`GateSummary::error`].
+    pub approval_status: Option<ApprovalStatus>,
+    pub overall_risk_level: Option<Severity>,
+    pub blocking: bool,
+    pub failed_gates: Vec<String>,
+    pub exit_code: i32,
+    /// Set when analysis itself failed. The file is still written in this
+    /// case so CI always has something to parse rather than nothing.
+    pub error: Option<String>,
+}
+
+impl GateSummary {
+    /// Builds the verdict for a completed analysis, from the same inputs
+    /// [`approval_status`] uses.
+    pub fn from_result(
+        options: &RuntimeOptions,
+        max_severity: Option<Severity>,
+        coverage: f64,
+        protected_model_touched: bool,
+        is_draft: bool,
+        estimated_cost_increase_dollars: Option<f64>,
+    ) -> Self {
+        let status = approval_status(
+            options,
+            max_severity,
+            coverage,
+            protected_model_touched,
+            is_draft,
+            estimated_cost_increase_dollars,
+        );
+
+        let mut failed_gates = Vec::new();
+        if max_severity.is_some_and(|s| severity_gate_level(s) >= options.fail_on)
+            && options.fail_on != FailOn::None
+        {
+            failed_gates.push(format!(
+                "severity threshold exceeded (fail_on={:?})",
+                options.fail_on
+            ));
+        }
+        if coverage < options.min_coverage {
+            failed_gates.push(format!(
+                "coverage {coverage:.2} below minimum {:.2}",
+                options.min_coverage
+            ));
+        }
+        if cost_gate_exceeded(options, estimated_cost_increase_dollars) {
+            failed_gates.push(format!(
+                "estimated cost increase ${:.2} exceeds maximum ${:.2}",
+                estimated_cost_increase_dollars.unwrap_or(0.0),
+                options.max_cost_increase_dollars.unwrap_or(0.0)
+            ));
+        }
+        if protected_model_touched {
+            failed_gates.push("a protected model was touched".to_string());
+        }
+        if is_draft && !options.gate_drafts && status != ApprovalStatus::Approved {
+            failed_gates.push("PR is a draft; blocking gate downgraded to advisory".to_string());
+        }
+
+        let blocking = status == ApprovalStatus::Blocked;
+        Self {
+            approval_status: Some(status),
+            overall_risk_level: max_severity,
+            blocking,
+            failed_gates,
+            exit_code: if blocking { 1 } else { 0 },
+            error: None,
+        }
+    }
+
+    /// Builds the verdict written when analysis itself failed (e.g. a
+    /// malformed manifest) before a status could be computed. Blocks by
+    /// convention: an unreadable run should never be silently treated as a
+    /// pass.
+    pub fn from_error(message: impl Into<String>) -> Self {
+        Self {
+            approval_status: None,
+            overall_risk_level: None,
+            blocking: true,
+            failed_gates: Vec::new(),
+            exit_code: 2,
+            error: Some(message.into()),
+        }
+    }
+
+    /// Serializes this verdict as JSON to `path`.
+    pub fn write_to(&self, path: &Path) -> std::io::Result<()> {
+        let contents = serde_json::to_string_pretty(self).expect("GateSummary always serializes");
+        std::fs::write(path, contents)
+    }
+
+    /// The process exit code for `--exit-code` mode: one distinct code per
+    /// [`ApprovalStatus`], unlike [`Self::exit_code`] (which only
+    /// distinguishes blocking from non-blocking, for the stable
+    /// `--gate-output` file format). An analysis failure always wins.
+    pub fn process_exit_code(&self) -> i32 {
+        if self.error.is_some() {
+            return 3;
+        }
+        match self.approval_status {
+            Some(ApprovalStatus::Approved) => 0,
+            Some(ApprovalStatus::ChangesRequested) => 1,
+            Some(ApprovalStatus::Blocked) => 2,
+            None => 3,
+        }
+    }
+}
+
+/// The on-disk shape of `dbt-pr-agent.yml`.
+///
+/// `deny_unknown_fields` turns a typo'd key into a load-time error instead
+/// of a silently-ignored no-op, which `serde_yaml` would otherwise do.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    #[serde(default)]
+    pub profile: Option<Profile>,
+    #[serde(default)]
+    pub fail_on: Option<FailOn>,
+    #[serde(default)]
+    pub min_coverage: Option<f64>,
+    #[serde(default)]
+    pub agents: Option<Vec<AgentKind>>,
+    #[serde(default)]
+    pub file_filter: Option<FileFilter>,
+    /// Base URL of the GitHub API, for GitHub Enterprise Server users.
+    /// Defaults to public GitHub when unset.
+    #[serde(default)]
+    pub github_url: Option<String>,
+    /// Above how many changed models a PR falls back to summary-only
+    /// analysis. Defaults to [`DEFAULT_SUMMARY_MODE_THRESHOLD`] when unset.
+    #[serde(default)]
+    pub summary_mode_threshold: Option<usize>,
+    /// Model names or globs that always require extra scrutiny when
+    /// touched. See [`RuntimeOptions::protected_models`].
+    #[serde(default)]
+    pub protected_models: Option<Vec<String>>,
+    /// How many changed models' agent analysis runs concurrently. See
+    /// [`RuntimeOptions::max_parallel_agents`].
+    #[serde(default)]
+    pub max_parallel_agents: Option<usize>,
+    /// See [`RuntimeOptions::require_fresh_artifacts`].
+    #[serde(default)]
+    pub require_fresh_artifacts: Option<bool>,
+    /// See [`RuntimeOptions::risk_rules`].
+    #[serde(default)]
+    pub risk_rules: Option<Vec<RiskRule>>,
+    /// See [`RuntimeOptions::gate_drafts`].
+    #[serde(default)]
+    pub gate_drafts: Option<bool>,
+    /// See [`RuntimeOptions::min_finding_confidence`].
+    #[serde(default)]
+    pub min_finding_confidence: Option<f64>,
+    /// See [`RuntimeOptions::diff_context_lines`].
+    #[serde(default)]
+    pub diff_context_lines: Option<usize>,
+    /// See [`RuntimeOptions::max_cost_increase_dollars`].
+    #[serde(default)]
+    pub max_cost_increase_dollars: Option<f64>,
+    /// See [`RuntimeOptions::notify`].
+    #[serde(default)]
+    pub notify: Option<NotifyConfig>,
+}
+
+impl FileConfig {
+    pub fn from_yaml(yaml: &str) -> Result<Self, String> {
+        serde_yaml::from_str(yaml).map_err(|e| e.to_string())
+    }
+
+    /// Checks value-level invariants that the type system can't express,
+    /// e.g. a coverage fraction out of range.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(coverage) = self.min_coverage {
+            if !(0.0..=1.0).contains(&coverage) {
+                return Err(format!(
+                    "min_coverage: must be between 0.0 and 1.0, got {coverage}"
+                ));
+            }
+        }
+        if let Some(confidence) = self.min_finding_confidence {
+            if !(0.0..=1.0).contains(&confidence) {
+                return Err(format!(
+                    "min_finding_confidence: must be between 0.0 and 1.0, got {confidence}"
+                ));
+            }
+        }
+        if let Some(max_cost_increase_dollars) = self.max_cost_increase_dollars {
+            if max_cost_increase_dollars < 0.0 {
+                return Err(format!(
+                    "max_cost_increase_dollars: must not be negative, got {max_cost_increase_dollars}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Merges `self` (lower precedence) with `other` (higher precedence),
+    /// field by field: whatever `other` sets wins, otherwise `self`'s value
+    /// passes through. This compares *presence* (`Option`), not "differs
+    /// from the default", so a higher layer can deliberately set a field
+    /// back to its documented default and have that decision stick — a
+    /// value-comparison merge couldn't distinguish "explicitly the
+    /// default" from "not set at all".
+    ///
+    /// Intended to be chained across the full precedence order: `file
+    /// .merge_with(env).merge_with(cli)`.
+    pub fn merge_with(self, other: FileConfig) -> FileConfig {
+        FileConfig {
+            profile: other.profile.or(self.profile),
+            fail_on: other.fail_on.or(self.fail_on),
+            min_coverage: other.min_coverage.or(self.min_coverage),
+            agents: other.agents.or(self.agents),
+            file_filter: other.file_filter.or(self.file_filter),
+            github_url: other.github_url.or(self.github_url),
+            summary_mode_threshold: other.summary_mode_threshold.or(self.summary_mode_threshold),
+            protected_models: other.protected_models.or(self.protected_models),
+            max_parallel_agents: other.max_parallel_agents.or(self.max_parallel_agents),
+            require_fresh_artifacts: other
+                .require_fresh_artifacts
+                .or(self.require_fresh_artifacts),
+            risk_rules: other.risk_rules.or(self.risk_rules),
+            gate_drafts: other.gate_drafts.or(self.gate_drafts),
+            min_finding_confidence: other.min_finding_confidence.or(self.min_finding_confidence),
+            diff_context_lines: other.diff_context_lines.or(self.diff_context_lines),
+            max_cost_increase_dollars: other
+                .max_cost_increase_dollars
+                .or(self.max_cost_increase_dollars),
+            notify: other.notify.or(self.notify),
+        }
+    }
+
+    /// Applies every field this (already fully merged) config sets onto
+    /// `options`. Call this once, after chaining every [`merge_with`](Self::merge_with)
+    /// layer, not per layer.
+    pub fn apply(&self, mut options: RuntimeOptions) -> RuntimeOptions {
+        if let Some(profile) = self.profile {
+            options = profile.apply(options);
+        }
+        if let Some(fail_on) = self.fail_on {
+            options.fail_on = fail_on;
+        }
+        if let Some(min_coverage) = self.min_coverage {
+            options.min_coverage = min_coverage;
+        }
+        if let Some(agents) = &self.agents {
+            options.agents = agents.iter().copied().collect();
+        }
+        if let Some(file_filter) = &self.file_filter {
+            options.file_filter = file_filter.clone();
+        }
+        if let Some(threshold) = self.summary_mode_threshold {
+            options.summary_mode_threshold = threshold;
+        }
+        if let Some(protected_models) = &self.protected_models {
+            options.protected_models = protected_models.clone();
+        }
+        if let Some(max_parallel_agents) = self.max_parallel_agents {
+            options.max_parallel_agents = max_parallel_agents;
+        }
+        if let Some(require_fresh_artifacts) = self.require_fresh_artifacts {
+            options.require_fresh_artifacts = require_fresh_artifacts;
+        }
+        if let Some(risk_rules) = &self.risk_rules {
+            options.risk_rules = risk_rules.clone();
+        }
+        if let Some(gate_drafts) = self.gate_drafts {
+            options.gate_drafts = gate_drafts;
+        }
+        if let Some(min_finding_confidence) = self.min_finding_confidence {
+            options.min_finding_confidence = min_finding_confidence;
+        }
+        if let Some(diff_context_lines) = self.diff_context_lines {
+            options.diff_context_lines = diff_context_lines;
+        }
+        if let Some(max_cost_increase_dollars) = self.max_cost_increase_dollars {
+            options.max_cost_increase_dollars = Some(max_cost_increase_dollars);
+        }
+        if let Some(notify) = &self.notify {
+            options.notify = Some(notify.clone());
+        }
+        options
+    }
+}
+
+/// Reads recognized `DBT_PR_AGENT_*` environment variables into a partial
+/// [`FileConfig`], for the environment layer of the defaults < file < env <
+/// CLI precedence chain (see [`FileConfig::merge_with`]). A variable that's
+/// unset or fails to parse is left `None` rather than erroring, since an
+/// absent env layer is the common case.
+pub fn load_from_env() -> FileConfig {
+    FileConfig {
+        profile: std::env::var("DBT_PR_AGENT_PROFILE")
+            .ok()
+            .and_then(|v| serde_yaml::from_str(&v).ok()),
+        fail_on: std::env::var("DBT_PR_AGENT_FAIL_ON")
+            .ok()
+            .and_then(|v| serde_yaml::from_str(&v).ok()),
+        min_coverage: std::env::var("DBT_PR_AGENT_MIN_COVERAGE")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        agents: None,
+        file_filter: None,
+        github_url: std::env::var("DBT_PR_AGENT_GITHUB_URL").ok(),
+        summary_mode_threshold: std::env::var("DBT_PR_AGENT_SUMMARY_MODE_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        protected_models: None,
+        max_parallel_agents: std::env::var("DBT_PR_AGENT_MAX_PARALLEL_AGENTS")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        require_fresh_artifacts: std::env::var("DBT_PR_AGENT_REQUIRE_FRESH_ARTIFACTS")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        risk_rules: None,
+        gate_drafts: std::env::var("DBT_PR_AGENT_GATE_DRAFTS")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        min_finding_confidence: std::env::var("DBT_PR_AGENT_MIN_FINDING_CONFIDENCE")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        diff_context_lines: std::env::var("DBT_PR_AGENT_DIFF_CONTEXT_LINES")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        max_cost_increase_dollars: std::env::var("DBT_PR_AGENT_MAX_COST_INCREASE_DOLLARS")
+            .ok()
+            .and_then(|v| v.parse().ok()),
+        notify: None,
+    }
+}
+
+/// Renders the JSON Schema for [`FileConfig`], for editor autocompletion.
+pub fn config_json_schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(FileConfig)).expect("schema always serializes")
+}
+
+fn severity_gate_level(severity: Severity) -> FailOn {
+    match severity {
+        Severity::Low => FailOn::Low,
+        Severity::Medium => FailOn::Medium,
+        Severity::High => FailOn::High,
+        Severity::Critical => FailOn::Critical,
+    }
+}
+
+/// The recognized directive block, e.g.:
+///
+/// ```text
+/// dbt-pr-agent: { fail_on: none, skip: [performance] }
+/// ```
+///
+/// The block may be YAML or JSON (JSON is a YAML subset, so one parser
+/// handles both) and can appear anywhere in the PR description, typically
+/// inside a fenced code block.
+#[derive(Debug, Clone, Deserialize)]
+struct DirectiveBlock {
+    #[serde(default)]
+    fail_on: Option<FailOn>,
+    #[serde(default)]
+    skip: Vec<String>,
+}
+
+/// The result of scanning a PR description for a `dbt-pr-agent:` directive.
+#[derive(Debug, Clone, Default)]
+pub struct PrOverrides {
+    pub fail_on: Option<FailOn>,
+    pub skip: HashSet<AgentKind>,
+}
+
+impl PrOverrides {
+    /// Scans `description` line by line for a `dbt-pr-agent: { ... }` directive
+    /// and parses its value as YAML/JSON.
+    ///
+    /// Returns `Ok(None)` when no directive is present. A directive that is
+    /// present but malformed is treated as absent: the caller should log a
+    /// warning and continue with the unmodified config.
+    pub fn parse_from_description(description: &str) -> Result<Option<Self>, String> {
+        const PREFIX: &str = "dbt-pr-agent:";
+
+        let Some(line) = description
+            .lines()
+            .find(|line| line.trim_start().starts_with(PREFIX))
+        else {
+            return Ok(None);
+        };
+
+        let value_str = line
+            .trim_start()
+            .strip_prefix(PREFIX)
+            .expect("prefix was just matched")
+            .trim();
+
+        let block: DirectiveBlock = serde_yaml::from_str(value_str)
+            .map_err(|e| format!("malformed dbt-pr-agent directive ({e}): {value_str}"))?;
+
+        let skip = block
+            .skip
+            .iter()
+            .filter_map(|name| match name.as_str() {
+                "impact" => Some(AgentKind::Impact),
+                "performance" => Some(AgentKind::Performance),
+                "quality" => Some(AgentKind::Quality),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Some(Self {
+            fail_on: block.fail_on,
+            skip,
+        }))
+    }
+
+    /// Applies this override on top of `options`, returning the merged result.
+    pub fn apply(&self, mut options: RuntimeOptions) -> RuntimeOptions {
+        if let Some(fail_on) = self.fail_on {
+            options.fail_on = fail_on;
+        }
+        options.agents.retain(|a| !self.skip.contains(a));
+        options
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disables_performance_agent_from_description() {
+        let description = "Please review.\n\n\
+             dbt-pr-agent: { fail_on: none, skip: [performance] }\n\n\
+             Thanks!";
+
+        let overrides = PrOverrides::parse_from_description(description)
+            .expect("directive should parse")
+            .expect("directive should be found");
+
+        let options = overrides.apply(RuntimeOptions::default());
+
+        assert_eq!(options.fail_on, FailOn::None);
+        assert!(!options.agents.contains(&AgentKind::Performance));
+        assert!(options.agents.contains(&AgentKind::Impact));
+    }
+
+    #[test]
+    fn no_directive_returns_none() {
+        let overrides = PrOverrides::parse_from_description("just a normal PR description")
+            .expect("absence of a directive is not an error");
+        assert!(overrides.is_none());
+    }
+
+    #[test]
+    fn malformed_directive_is_an_error_not_a_panic() {
+        let result = PrOverrides::parse_from_description("dbt-pr-agent: { fail_on: [not valid");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn unknown_top_level_key_is_a_descriptive_error() {
+        let err = FileConfig::from_yaml("profile: strict\nfoo_bar: true\n").unwrap_err();
+        assert!(
+            err.contains("foo_bar"),
+            "error should name the offending key: {err}"
+        );
+    }
+
+    #[test]
+    fn strict_and_lenient_profiles_yield_different_approval_for_the_same_pr() {
+        let strict = Profile::Strict.apply(RuntimeOptions::default());
+        let lenient = Profile::Lenient.apply(RuntimeOptions::default());
+
+        let status = approval_status(&strict, Some(Severity::Medium), 0.95, false, false, None);
+        assert_eq!(status, ApprovalStatus::Blocked);
+
+        let status = approval_status(&lenient, Some(Severity::Medium), 0.95, false, false, None);
+        assert_eq!(status, ApprovalStatus::Approved);
+    }
+
+    #[test]
+    fn touching_a_protected_model_forces_changes_requested_even_with_low_risk() {
+        let options = RuntimeOptions::default();
+
+        // No severity findings and full coverage: on its own this would approve.
+        let status = approval_status(&options, None, 1.0, false, false, None);
+        assert_eq!(status, ApprovalStatus::Approved);
+
+        let status = approval_status(&options, None, 1.0, true, false, None);
+        assert_eq!(status, ApprovalStatus::ChangesRequested);
+    }
+
+    #[test]
+    fn a_blocked_gate_summary_reports_the_matching_exit_code() {
+        let options = RuntimeOptions {
+            fail_on: FailOn::High,
+            ..RuntimeOptions::default()
+        };
+
+        let summary =
+            GateSummary::from_result(&options, Some(Severity::Critical), 1.0, false, false, None);
+
+        assert_eq!(summary.approval_status, Some(ApprovalStatus::Blocked));
+        assert!(summary.blocking);
+        assert_eq!(summary.exit_code, 1);
+        assert!(!summary.failed_gates.is_empty());
+        assert!(summary.error.is_none());
+    }
+
+    #[test]
+    fn a_failed_analysis_writes_a_blocking_error_summary() {
+        let summary = GateSummary::from_error("manifest.json was not valid JSON");
+
+        assert!(summary.approval_status.is_none());
+        assert!(summary.blocking);
+        assert_eq!(summary.exit_code, 2);
+        assert_eq!(
+            summary.error.as_deref(),
+            Some("manifest.json was not valid JSON")
+        );
+    }
+
+    #[test]
+    fn layered_config_merge_lets_cli_beat_env_beat_file() {
+        let file = FileConfig {
+            fail_on: Some(FailOn::Low),
+            min_coverage: Some(0.5),
+            profile: Some(Profile::Lenient),
+            ..FileConfig::default()
+        };
+        let env = FileConfig {
+            fail_on: Some(FailOn::Medium),
+            ..FileConfig::default()
+        };
+        let cli = FileConfig {
+            fail_on: Some(FailOn::Critical),
+            ..FileConfig::default()
+        };
+
+        let effective = file.merge_with(env).merge_with(cli);
+
+        assert_eq!(
+            effective.fail_on,
+            Some(FailOn::Critical),
+            "CLI should win when all three layers set fail_on"
+        );
+        assert_eq!(
+            effective.min_coverage,
+            Some(0.5),
+            "file's min_coverage should pass through when env/cli leave it unset"
+        );
+        assert_eq!(effective.profile, Some(Profile::Lenient));
+    }
+
+    #[test]
+    fn a_higher_layer_can_deliberately_reset_a_field_to_its_default() {
+        let file = FileConfig {
+            min_coverage: Some(0.9),
+            ..FileConfig::default()
+        };
+        let cli = FileConfig {
+            min_coverage: Some(RuntimeOptions::default().min_coverage),
+            ..FileConfig::default()
+        };
+
+        let effective = file.merge_with(FileConfig::default()).merge_with(cli);
+
+        assert_eq!(
+            effective.min_coverage,
+            Some(RuntimeOptions::default().min_coverage),
+            "CLI explicitly setting the default value should win, not be treated as absent"
+        );
+    }
+
+    #[test]
+    fn a_protected_model_touch_never_relaxes_an_already_blocked_pr() {
+        let options = Profile::Strict.apply(RuntimeOptions::default());
+
+        let status = approval_status(&options, Some(Severity::Critical), 0.0, true, false, None);
+        assert_eq!(status, ApprovalStatus::Blocked);
+    }
+
+    #[test]
+    fn a_draft_pr_with_critical_issues_reports_them_but_is_never_blocked() {
+        let options = RuntimeOptions {
+            fail_on: FailOn::High,
+            ..RuntimeOptions::default()
+        };
+
+        let summary =
+            GateSummary::from_result(&options, Some(Severity::Critical), 1.0, false, true, None);
+
+        assert_eq!(
+            summary.approval_status,
+            Some(ApprovalStatus::ChangesRequested)
+        );
+        assert!(!summary.blocking);
+        assert!(summary.failed_gates.iter().any(|g| g.contains("draft")));
+    }
+
+    #[test]
+    fn gate_drafts_restores_full_blocking_for_a_draft_pr() {
+        let options = RuntimeOptions {
+            fail_on: FailOn::High,
+            gate_drafts: true,
+            ..RuntimeOptions::default()
+        };
+
+        let status = approval_status(&options, Some(Severity::Critical), 1.0, false, true, None);
+
+        assert_eq!(status, ApprovalStatus::Blocked);
+    }
+
+    #[test]
+    fn a_draft_pr_with_no_findings_is_still_approved() {
+        let options = RuntimeOptions::default();
+
+        let status = approval_status(&options, None, 1.0, false, true, None);
+
+        assert_eq!(status, ApprovalStatus::Approved);
+    }
+
+    #[test]
+    fn min_finding_confidence_rejects_an_out_of_range_value() {
+        let file = FileConfig {
+            min_finding_confidence: Some(1.5),
+            ..FileConfig::default()
+        };
+        assert!(file.validate().is_err());
+    }
+
+    #[test]
+    fn min_finding_confidence_defaults_when_unset_by_any_layer() {
+        let effective = FileConfig::default()
+            .merge_with(FileConfig::default())
+            .merge_with(FileConfig::default())
+            .apply(RuntimeOptions::default());
+
+        assert_eq!(effective.min_finding_confidence, DEFAULT_MIN_FINDING_CONFIDENCE);
+    }
+
+    #[test]
+    fn a_cost_increase_beyond_the_configured_maximum_blocks_approval() {
+        let options = RuntimeOptions {
+            max_cost_increase_dollars: Some(50.0),
+            ..RuntimeOptions::default()
+        };
+
+        let status = approval_status(&options, None, 1.0, false, false, Some(75.0));
+
+        assert_eq!(status, ApprovalStatus::Blocked);
+    }
+
+    #[test]
+    fn a_cost_increase_within_the_configured_maximum_is_not_blocked() {
+        let options = RuntimeOptions {
+            max_cost_increase_dollars: Some(50.0),
+            ..RuntimeOptions::default()
+        };
+
+        let status = approval_status(&options, None, 1.0, false, false, Some(10.0));
+
+        assert_eq!(status, ApprovalStatus::Approved);
+    }
+
+    #[test]
+    fn no_configured_cost_maximum_never_blocks_regardless_of_the_estimate() {
+        let options = RuntimeOptions::default();
+
+        let status = approval_status(&options, None, 1.0, false, false, Some(1_000_000.0));
+
+        assert_eq!(status, ApprovalStatus::Approved);
+    }
+
+    #[test]
+    fn gate_summary_reports_the_cost_gate_in_failed_gates() {
+        let options = RuntimeOptions {
+            max_cost_increase_dollars: Some(50.0),
+            ..RuntimeOptions::default()
+        };
+
+        let summary = GateSummary::from_result(&options, None, 1.0, false, false, Some(75.0));
+
+        assert!(summary.failed_gates.iter().any(|g| g.contains("cost")));
+    }
+
+    #[test]
+    fn max_cost_increase_dollars_rejects_a_negative_value() {
+        let file = FileConfig {
+            max_cost_increase_dollars: Some(-1.0),
+            ..FileConfig::default()
+        };
+        assert!(file.validate().is_err());
+    }
+
+    #[test]
+    fn process_exit_code_distinguishes_all_three_approval_statuses() {
+        let options = RuntimeOptions::default();
+
+        let approved = GateSummary::from_result(&options, None, 1.0, false, false, None);
+        assert_eq!(approved.process_exit_code(), 0);
+
+        let changes_requested = GateSummary::from_result(&options, None, 1.0, true, false, None);
+        assert_eq!(changes_requested.process_exit_code(), 1);
+
+        let blocked = GateSummary::from_result(
+            &RuntimeOptions {
+                fail_on: FailOn::High,
+                ..RuntimeOptions::default()
+            },
+            Some(Severity::Critical),
+            1.0,
+            false,
+            false,
+            None,
+        );
+        assert_eq!(blocked.process_exit_code(), 2);
+    }
+
+    #[test]
+    fn process_exit_code_for_a_failed_analysis_is_distinct_from_every_approval_status() {
+        let summary = GateSummary::from_error("manifest.json was not valid JSON");
+
+        assert_eq!(summary.process_exit_code(), 3);
+    }
+}
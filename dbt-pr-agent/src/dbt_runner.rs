@@ -0,0 +1,213 @@
+//! Compiles a git ref's dbt project into a fresh `manifest.json`, so
+//! [`crate::state::compare_manifests`] can diff base and head from real
+//! compiled artifacts instead of assuming whatever is already on disk under
+//! `target/` is fresh.
+//!
+//! Like [`crate::llm::gemini::GeminiProvider`], this module only decides
+//! which commands to run, with what arguments, and in what order; it
+//! performs no process execution of its own (this crate spawns no
+//! subprocesses directly). Actual execution goes through the injected
+//! [`ProcessRunner`], real subprocess-backed by whichever binary embeds this
+//! crate, and stubbed in tests.
+
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The outcome of running one command to completion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs a single command in `working_dir` and reports its outcome.
+/// Implemented by a real `std::process::Command`-backed runner wherever this
+/// crate is embedded, and by a stub in tests.
+pub trait ProcessRunner {
+    fn run(&self, program: &str, args: &[&str], working_dir: &Path) -> Result<CommandOutput, String>;
+}
+
+/// Why [`compile_ref`] couldn't produce a manifest for a ref.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DbtRunnerError {
+    #[error("git worktree add failed: {0}")]
+    WorktreeAdd(String),
+    #[error("dbt {command} failed: {stderr}")]
+    Compile { command: &'static str, stderr: String },
+}
+
+/// Checks out `git_ref` into `worktree_dir` via `git worktree add --detach`
+/// (leaving the caller's current checkout untouched) and runs `dbt compile`
+/// there, returning the path to the resulting `target/manifest.json`. Pass
+/// `use_parse: true` to run `dbt parse` instead, which is faster but
+/// produces a manifest with no `compiled_code` (see
+/// [`crate::artifacts::is_parse_only_manifest`]).
+pub fn compile_ref(
+    runner: &dyn ProcessRunner,
+    repo_dir: &Path,
+    git_ref: &str,
+    worktree_dir: &Path,
+    use_parse: bool,
+) -> Result<PathBuf, DbtRunnerError> {
+    let worktree_dir_str = worktree_dir.to_string_lossy().into_owned();
+    let add = runner
+        .run(
+            "git",
+            &["worktree", "add", "--detach", &worktree_dir_str, git_ref],
+            repo_dir,
+        )
+        .map_err(DbtRunnerError::WorktreeAdd)?;
+    if !add.success {
+        return Err(DbtRunnerError::WorktreeAdd(add.stderr));
+    }
+
+    let command = if use_parse { "parse" } else { "compile" };
+    let compile = runner
+        .run("dbt", &[command], worktree_dir)
+        .map_err(|stderr| DbtRunnerError::Compile { command, stderr })?;
+    if !compile.success {
+        return Err(DbtRunnerError::Compile {
+            command,
+            stderr: compile.stderr,
+        });
+    }
+
+    Ok(worktree_dir.join("target").join("manifest.json"))
+}
+
+/// Removes a worktree [`compile_ref`] created, via `git worktree remove
+/// --force`, so a caller doesn't leak temporary checkouts across runs.
+pub fn remove_worktree(
+    runner: &dyn ProcessRunner,
+    repo_dir: &Path,
+    worktree_dir: &Path,
+) -> Result<(), String> {
+    let worktree_dir_str = worktree_dir.to_string_lossy().into_owned();
+    let result = runner.run(
+        "git",
+        &["worktree", "remove", "--force", &worktree_dir_str],
+        repo_dir,
+    )?;
+    if result.success {
+        Ok(())
+    } else {
+        Err(result.stderr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    struct StubRunner {
+        calls: RefCell<Vec<(String, Vec<String>)>>,
+        /// Returns `false`-success for any command whose program matches.
+        fail_program: Option<&'static str>,
+    }
+
+    impl StubRunner {
+        fn new() -> Self {
+            Self {
+                calls: RefCell::new(Vec::new()),
+                fail_program: None,
+            }
+        }
+
+        fn failing(program: &'static str) -> Self {
+            Self {
+                calls: RefCell::new(Vec::new()),
+                fail_program: Some(program),
+            }
+        }
+    }
+
+    impl ProcessRunner for StubRunner {
+        fn run(&self, program: &str, args: &[&str], _working_dir: &Path) -> Result<CommandOutput, String> {
+            self.calls.borrow_mut().push((
+                program.to_string(),
+                args.iter().map(|s| s.to_string()).collect(),
+            ));
+            let success = self.fail_program != Some(program);
+            Ok(CommandOutput {
+                success,
+                stdout: String::new(),
+                stderr: if success {
+                    String::new()
+                } else {
+                    format!("{program} failed")
+                },
+            })
+        }
+    }
+
+    #[test]
+    fn compile_ref_checks_out_the_worktree_then_compiles_and_returns_the_manifest_path() {
+        let runner = StubRunner::new();
+
+        let manifest = compile_ref(
+            &runner,
+            Path::new("/repo"),
+            "main",
+            Path::new("/tmp/wt-base"),
+            false,
+        )
+        .expect("compile succeeds");
+
+        assert_eq!(manifest, Path::new("/tmp/wt-base/target/manifest.json"));
+        let calls = runner.calls.borrow();
+        assert_eq!(calls[0].0, "git");
+        assert_eq!(
+            calls[0].1,
+            vec!["worktree", "add", "--detach", "/tmp/wt-base", "main"]
+        );
+        assert_eq!(calls[1].0, "dbt");
+        assert_eq!(calls[1].1, vec!["compile"]);
+    }
+
+    #[test]
+    fn use_parse_runs_dbt_parse_instead_of_compile() {
+        let runner = StubRunner::new();
+
+        compile_ref(&runner, Path::new("/repo"), "main", Path::new("/tmp/wt"), true)
+            .expect("parse succeeds");
+
+        assert_eq!(runner.calls.borrow()[1].1, vec!["parse"]);
+    }
+
+    #[test]
+    fn a_failed_worktree_add_reports_a_worktree_error_without_attempting_to_compile() {
+        let runner = StubRunner::failing("git");
+
+        let result = compile_ref(&runner, Path::new("/repo"), "main", Path::new("/tmp/wt"), false);
+
+        assert!(matches!(result, Err(DbtRunnerError::WorktreeAdd(_))));
+        assert_eq!(runner.calls.borrow().len(), 1);
+    }
+
+    #[test]
+    fn a_failed_dbt_compile_reports_a_compile_error() {
+        let runner = StubRunner::failing("dbt");
+
+        let result = compile_ref(&runner, Path::new("/repo"), "main", Path::new("/tmp/wt"), false);
+
+        assert!(matches!(
+            result,
+            Err(DbtRunnerError::Compile { command: "compile", .. })
+        ));
+    }
+
+    #[test]
+    fn remove_worktree_issues_a_forced_git_worktree_remove() {
+        let runner = StubRunner::new();
+
+        remove_worktree(&runner, Path::new("/repo"), Path::new("/tmp/wt")).expect("removes cleanly");
+
+        let calls = runner.calls.borrow();
+        assert_eq!(
+            calls[0].1,
+            vec!["worktree", "remove", "--force", "/tmp/wt"]
+        );
+    }
+}
@@ -0,0 +1,151 @@
+//! Debounces webhook-triggered re-analysis so a burst of pushes to the same
+//! PR results in one analysis of the final head SHA, not one per push.
+//!
+//! There's no webhook server or async runtime in this crate yet, so this
+//! models the policy only: a caller feeds it events as they arrive and polls
+//! [`Debouncer::is_settled`] before actually running an analysis.
+
+use crate::cancellation::CancellationToken;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Identifies a PR across repos.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PrKey {
+    pub repo: String,
+    pub pr_number: u64,
+}
+
+struct InFlight {
+    head_sha: String,
+    last_event_at: Instant,
+    cancellation: CancellationToken,
+}
+
+/// Tracks in-flight analyses keyed by repo/PR and coalesces rapid pushes: a
+/// new event for a PR that's already queued/running cancels the prior one,
+/// and only the latest head SHA is analyzed once no further event arrives
+/// within `quiet_period`.
+pub struct Debouncer {
+    quiet_period: Duration,
+    in_flight: Mutex<HashMap<PrKey, InFlight>>,
+}
+
+impl Debouncer {
+    pub fn new(quiet_period: Duration) -> Self {
+        Self {
+            quiet_period,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records a webhook event for `key` at `head_sha`, cancelling whatever
+    /// analysis was previously queued/running for the same PR. Returns the
+    /// [`CancellationToken`] the caller's analysis task should watch.
+    pub fn record_event(&self, key: PrKey, head_sha: String, now: Instant) -> CancellationToken {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if let Some(prior) = in_flight.get(&key) {
+            prior.cancellation.cancel();
+        }
+        let cancellation = CancellationToken::new();
+        in_flight.insert(
+            key,
+            InFlight {
+                head_sha,
+                last_event_at: now,
+                cancellation: cancellation.clone(),
+            },
+        );
+        cancellation
+    }
+
+    /// Whether `quiet_period` has elapsed since the last event for `key`,
+    /// i.e. it's safe to actually run the analysis now.
+    pub fn is_settled(&self, key: &PrKey, now: Instant) -> bool {
+        self.in_flight
+            .lock()
+            .unwrap()
+            .get(key)
+            .is_some_and(|f| now.duration_since(f.last_event_at) >= self.quiet_period)
+    }
+
+    /// The most recently reported head SHA for `key`, if any event has been recorded.
+    pub fn latest_head_sha(&self, key: &PrKey) -> Option<String> {
+        self.in_flight
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|f| f.head_sha.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn three_rapid_events_settle_to_a_single_analysis_of_the_final_sha() {
+        let debouncer = Debouncer::new(Duration::from_millis(50));
+        let key = PrKey {
+            repo: "acme/analytics".to_string(),
+            pr_number: 7,
+        };
+        let t0 = Instant::now();
+
+        let tok1 = debouncer.record_event(key.clone(), "sha1".to_string(), t0);
+        let tok2 = debouncer.record_event(
+            key.clone(),
+            "sha2".to_string(),
+            t0 + Duration::from_millis(10),
+        );
+        let tok3 = debouncer.record_event(
+            key.clone(),
+            "sha3".to_string(),
+            t0 + Duration::from_millis(20),
+        );
+
+        assert!(
+            tok1.is_cancelled(),
+            "first event's analysis should be cancelled by the second push"
+        );
+        assert!(
+            tok2.is_cancelled(),
+            "second event's analysis should be cancelled by the third push"
+        );
+        assert!(!tok3.is_cancelled());
+
+        assert!(
+            !debouncer.is_settled(&key, t0 + Duration::from_millis(30)),
+            "still within the quiet period"
+        );
+        assert!(debouncer.is_settled(&key, t0 + Duration::from_millis(80)));
+        assert_eq!(debouncer.latest_head_sha(&key).as_deref(), Some("sha3"));
+    }
+
+    #[test]
+    fn independent_prs_do_not_debounce_each_other() {
+        let debouncer = Debouncer::new(Duration::from_millis(50));
+        let now = Instant::now();
+
+        let tok_a = debouncer.record_event(
+            PrKey {
+                repo: "acme/analytics".to_string(),
+                pr_number: 1,
+            },
+            "sha-a".to_string(),
+            now,
+        );
+        let tok_b = debouncer.record_event(
+            PrKey {
+                repo: "acme/analytics".to_string(),
+                pr_number: 2,
+            },
+            "sha-b".to_string(),
+            now,
+        );
+
+        assert!(!tok_a.is_cancelled());
+        assert!(!tok_b.is_cancelled());
+    }
+}
@@ -0,0 +1,241 @@
+//! Minimal unified-diff parsing: enough to know which lines of a changed
+//! file are actually present in the diff, since GitHub rejects review
+//! comments anchored to lines outside it.
+
+use std::collections::HashSet;
+
+/// Returns the set of new-file line numbers that appear in `patch` (i.e.
+/// added or context lines), which is exactly the set GitHub will accept a
+/// review comment on.
+pub fn lines_in_diff(patch: &str) -> HashSet<u32> {
+    let mut lines = HashSet::new();
+    let mut new_line = 0u32;
+
+    for line in patch.lines() {
+        if let Some(hunk) = line.strip_prefix("@@ ") {
+            if let Some(new_range) = hunk.split("+").nth(1).and_then(|s| s.split(' ').next()) {
+                let start: u32 = new_range
+                    .split(',')
+                    .next()
+                    .unwrap_or("0")
+                    .parse()
+                    .unwrap_or(0);
+                new_line = start;
+            }
+            continue;
+        }
+
+        if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix('+') {
+            let _ = rest;
+            lines.insert(new_line);
+            new_line += 1;
+        } else if line.starts_with(' ') {
+            lines.insert(new_line);
+            new_line += 1;
+        }
+        // Removed ('-') lines don't advance the new-file line counter.
+    }
+
+    lines
+}
+
+/// The raw content of every added and removed line in a unified diff patch,
+/// stripped of the leading `+`/`-` marker. Unlike [`lines_in_diff`], which
+/// only tracks line *numbers*, this keeps the line text itself so a caller
+/// can inspect what actually changed rather than just where.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileDiff {
+    pub added_lines: Vec<String>,
+    pub removed_lines: Vec<String>,
+}
+
+/// Splits `patch` into its added and removed line content, ignoring hunk
+/// headers and the `+++`/`---` file headers.
+pub fn parse_unified_diff(patch: &str) -> FileDiff {
+    let mut diff = FileDiff::default();
+    for line in patch.lines() {
+        if line.starts_with("@@") || line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('+') {
+            diff.added_lines.push(rest.to_string());
+        } else if let Some(rest) = line.strip_prefix('-') {
+            diff.removed_lines.push(rest.to_string());
+        }
+    }
+    diff
+}
+
+/// Every added ('+') line in `patch` paired with its real new-file line
+/// number, the numbering [`lines_in_diff`] computes and the only numbering
+/// GitHub accepts a review comment on. Unlike [`parse_unified_diff`], which
+/// only keeps the text, this is what a caller needs to anchor a finding
+/// computed from the added lines' text (e.g. [`crate::agents::quality::sql_rules::lint`]
+/// run against them) back onto a real line in the diff.
+pub fn added_lines_with_numbers(patch: &str) -> Vec<(u32, String)> {
+    let mut lines = Vec::new();
+    let mut new_line = 0u32;
+
+    for line in patch.lines() {
+        if let Some(hunk) = line.strip_prefix("@@ ") {
+            if let Some(new_range) = hunk.split('+').nth(1).and_then(|s| s.split(' ').next()) {
+                new_line = new_range.split(',').next().unwrap_or("0").parse().unwrap_or(0);
+            }
+            continue;
+        }
+        if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('+') {
+            lines.push((new_line, rest.to_string()));
+            new_line += 1;
+        } else if line.starts_with(' ') {
+            new_line += 1;
+        }
+    }
+
+    lines
+}
+
+/// Added or removed lines that look like part of a dbt `config(...)` block
+/// (a model's materialization, tags, `unique_key`, etc.), trimmed of
+/// surrounding whitespace. This is line-level: a `config(...)` call split
+/// across several lines is reported as several separate entries rather than
+/// merged into one block.
+pub fn changed_jinja_config_blocks(diff: &FileDiff) -> Vec<String> {
+    diff.added_lines
+        .iter()
+        .chain(diff.removed_lines.iter())
+        .map(|line| line.trim())
+        .filter(|line| line.to_ascii_lowercase().contains("config("))
+        .map(str::to_string)
+        .collect()
+}
+
+/// A single `<expr> as <alias>` SELECT-list line, if `line` looks like one.
+fn column_alias(line: &str) -> Option<(String, String)> {
+    let trimmed = line.trim().trim_end_matches(',');
+    let lower = trimmed.to_ascii_lowercase();
+    let pos = lower.rfind(" as ")?;
+    let expr = trimmed[..pos].trim().to_string();
+    let alias = trimmed[pos + 4..].trim().to_string();
+    if expr.is_empty() || alias.is_empty() {
+        None
+    } else {
+        Some((expr, alias))
+    }
+}
+
+/// Column renames visible in the diff: a `<expr> as <old_alias>` line
+/// removed and a `<expr> as <new_alias>` line added for the same `expr`,
+/// returned as `(old_alias, new_alias)`. A unified diff doesn't say which
+/// removed line a given added line replaces, so this pairs on the first
+/// matching `expr` rather than hunk position.
+pub fn renamed_columns(diff: &FileDiff) -> Vec<(String, String)> {
+    let removed: Vec<(String, String)> = diff
+        .removed_lines
+        .iter()
+        .filter_map(|l| column_alias(l))
+        .collect();
+    let added: Vec<(String, String)> = diff
+        .added_lines
+        .iter()
+        .filter_map(|l| column_alias(l))
+        .collect();
+
+    let mut renames = Vec::new();
+    for (old_expr, old_alias) in &removed {
+        if let Some((_, new_alias)) = added
+            .iter()
+            .find(|(new_expr, new_alias)| new_expr == old_expr && new_alias != old_alias)
+        {
+            renames.push((old_alias.clone(), new_alias.clone()));
+        }
+    }
+    renames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_added_lines_after_a_hunk_header() {
+        let patch = "@@ -10,2 +10,3 @@\n context\n+added line\n-removed line\n context again\n";
+        let lines = lines_in_diff(patch);
+        assert!(lines.contains(&11));
+    }
+
+    #[test]
+    fn added_lines_with_numbers_pairs_each_added_line_with_its_new_file_line_number() {
+        let patch = "@@ -10,2 +10,4 @@\n context\n+added one\n+added two\n-removed line\n context again\n";
+
+        let lines = added_lines_with_numbers(patch);
+
+        assert_eq!(
+            lines,
+            vec![(11, "added one".to_string()), (12, "added two".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_unified_diff_separates_added_and_removed_line_content() {
+        let patch = "@@ -1,2 +1,2 @@\n context\n+select 1\n-select 2\n";
+
+        let diff = parse_unified_diff(patch);
+
+        assert_eq!(diff.added_lines, vec!["select 1".to_string()]);
+        assert_eq!(diff.removed_lines, vec!["select 2".to_string()]);
+    }
+
+    #[test]
+    fn changed_jinja_config_blocks_finds_a_materialized_config_line() {
+        let diff = FileDiff {
+            added_lines: vec!["{{ config(materialized='incremental') }}".to_string()],
+            removed_lines: vec!["{{ config(materialized='view') }}".to_string()],
+        };
+
+        let blocks = changed_jinja_config_blocks(&diff);
+
+        assert_eq!(blocks.len(), 2);
+    }
+
+    #[test]
+    fn changed_jinja_config_blocks_ignores_unrelated_lines() {
+        let diff = FileDiff {
+            added_lines: vec!["select status from stg_orders".to_string()],
+            removed_lines: vec![],
+        };
+
+        assert!(changed_jinja_config_blocks(&diff).is_empty());
+    }
+
+    #[test]
+    fn renamed_columns_pairs_a_removed_and_added_alias_of_the_same_expression() {
+        let diff = FileDiff {
+            added_lines: vec!["status as order_state,".to_string()],
+            removed_lines: vec!["status as order_status,".to_string()],
+        };
+
+        let renames = renamed_columns(&diff);
+
+        assert_eq!(
+            renames,
+            vec![("order_status".to_string(), "order_state".to_string())]
+        );
+    }
+
+    #[test]
+    fn renamed_columns_finds_nothing_when_only_the_expression_changes() {
+        let diff = FileDiff {
+            added_lines: vec!["order_status as status,".to_string()],
+            removed_lines: vec!["status as order_status,".to_string()],
+        };
+
+        assert!(renamed_columns(&diff).is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+//! Filtering of a PR's changed files before they're mapped to dbt models.
+//!
+//! Applied once, in [`filter_changed_files`], so every agent downstream sees
+//! the same filtered set instead of re-implementing the glob logic.
+
+use crate::github::ChangedFile;
+use glob::Pattern;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Include/exclude globs matched against a changed file's repo-relative path.
+///
+/// An empty `include` list means "everything is included by default".
+/// `exclude` always wins on conflict.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FileFilter {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl FileFilter {
+    fn matches_any(patterns: &[String], path: &str) -> bool {
+        patterns
+            .iter()
+            .filter_map(|p| Pattern::new(p).ok())
+            .any(|p| p.matches(path))
+    }
+
+    pub fn is_included(&self, path: &str) -> bool {
+        if Self::matches_any(&self.exclude, path) {
+            return false;
+        }
+        self.include.is_empty() || Self::matches_any(&self.include, path)
+    }
+}
+
+/// Filters `files` in place per `filter`, returning the kept files and how
+/// many were dropped so callers can log the count.
+pub fn filter_changed_files(
+    files: Vec<ChangedFile>,
+    filter: &FileFilter,
+) -> (Vec<ChangedFile>, usize) {
+    let total = files.len();
+    let kept: Vec<ChangedFile> = files
+        .into_iter()
+        .filter(|f| filter.is_included(&f.path))
+        .collect();
+    let dropped = total - kept.len();
+    (kept, dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str) -> ChangedFile {
+        ChangedFile {
+            path: path.to_string(),
+            status: "modified".to_string(),
+            additions: 1,
+            deletions: 0,
+            patch: None,
+        }
+    }
+
+    #[test]
+    fn excludes_vendored_package_changes() {
+        let filter = FileFilter {
+            include: vec![],
+            exclude: vec!["dbt_packages/**".to_string()],
+        };
+        let files = vec![
+            file("models/marts/orders.sql"),
+            file("dbt_packages/dbt_utils/macros/foo.sql"),
+        ];
+
+        let (kept, dropped) = filter_changed_files(files, &filter);
+        assert_eq!(dropped, 1);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].path, "models/marts/orders.sql");
+    }
+
+    #[test]
+    fn exclude_wins_over_include_on_conflict() {
+        let filter = FileFilter {
+            include: vec!["models/**".to_string()],
+            exclude: vec!["models/generated/**".to_string()],
+        };
+        assert!(filter.is_included("models/marts/orders.sql"));
+        assert!(!filter.is_included("models/generated/orders.sql"));
+    }
+}
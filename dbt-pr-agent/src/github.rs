@@ -0,0 +1,1217 @@
+//! Types describing the GitHub side of a pull request under review, and the
+//! client that posts review results back to it.
+
+use crate::agents::quality::QualityIssue;
+use crate::diff::lines_in_diff;
+use crate::project::to_repo_relative_path;
+use crate::redact::{default_patterns, redact};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A single file changed in the pull request, as reported by the GitHub compare API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedFile {
+    pub path: String,
+    pub status: String,
+    pub additions: usize,
+    pub deletions: usize,
+    /// Unified diff patch for this file, when GitHub provides one.
+    pub patch: Option<String>,
+}
+
+/// Everything the agents need to know about the pull request being analyzed.
+///
+/// This is assembled once per run by the GitHub client and then shared
+/// (read-only) across all agents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PRContext {
+    pub owner: String,
+    pub repo: String,
+    pub number: u64,
+    pub title: String,
+    /// Raw PR description (GitHub calls this the "body").
+    pub description: String,
+    pub base_sha: String,
+    pub head_sha: String,
+    pub changed_files: Vec<ChangedFile>,
+    pub is_draft: bool,
+}
+
+impl PRContext {
+    pub fn changed_paths(&self) -> impl Iterator<Item = &str> {
+        self.changed_files.iter().map(|f| f.path.as_str())
+    }
+}
+
+/// A single line-anchored review comment, in GitHub's review-comment shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineComment {
+    pub path: String,
+    pub line: u32,
+    pub body: String,
+}
+
+/// The payload for a single pending review batching every line comment
+/// together, so posting them doesn't fire one notification per finding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewPayload {
+    pub body: String,
+    pub event: String,
+    pub comments: Vec<LineComment>,
+    /// Findings for files GitHub didn't send a patch for (typically diffs
+    /// too large to render), which can't be line-anchored and are instead
+    /// surfaced as whole-file notes in the review body.
+    pub general_comments: Vec<String>,
+}
+
+/// The public GitHub API host, used when no Enterprise Server URL is
+/// configured.
+pub const DEFAULT_GITHUB_API_BASE_URL: &str = "https://api.github.com";
+
+/// Builds request targets against the GitHub REST API for a PR's review
+/// lifecycle. Defaults to public GitHub; call [`GitHubClient::with_base_url`]
+/// to target a GitHub Enterprise Server instance instead.
+#[derive(Debug, Clone)]
+pub struct GitHubClient {
+    token: String,
+    base_url: String,
+}
+
+impl GitHubClient {
+    /// Targets the public GitHub API.
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+            base_url: DEFAULT_GITHUB_API_BASE_URL.to_string(),
+        }
+    }
+
+    /// Targets a GitHub Enterprise Server instance at `base_url`, e.g.
+    /// `https://ghe.internal/api/v3`.
+    pub fn with_base_url(
+        token: impl Into<String>,
+        base_url: impl Into<String>,
+    ) -> Result<Self, String> {
+        let base_url = base_url.into();
+        if !base_url.starts_with("https://") && !base_url.starts_with("http://") {
+            return Err(format!(
+                "--github-url must be an absolute URL (http(s)://...), got: {base_url}"
+            ));
+        }
+        Ok(Self {
+            token: token.into(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn authorization_header(&self) -> String {
+        format!("Bearer {}", self.token)
+    }
+
+    /// The URL for posting a review on `pr`.
+    pub fn review_url(&self, pr: &PRContext) -> String {
+        format!(
+            "{}/repos/{}/{}/pulls/{}/reviews",
+            self.base_url, pr.owner, pr.repo, pr.number
+        )
+    }
+
+    /// The URL for probing a token's access to `owner/repo`, used by
+    /// [`GitHubClient::verify_access`].
+    pub fn repo_url(&self, owner: &str, repo: &str) -> String {
+        format!("{}/repos/{}/{}", self.base_url, owner, repo)
+    }
+
+    /// Probes the token's access to `owner/repo` via `transport` before any
+    /// analysis runs, turning a 403 discovered deep in a run into an upfront,
+    /// actionable error naming the missing permission.
+    pub fn verify_access(
+        &self,
+        transport: &dyn GitHubTransport,
+        owner: &str,
+        repo: &str,
+    ) -> Result<(), AccessError> {
+        let response = transport
+            .get_repo_access(&self.repo_url(owner, repo), &self.authorization_header())
+            .map_err(AccessError::Transport)?;
+
+        match response.status {
+            200 => Ok(()),
+            404 => Err(AccessError::RepoNotFound {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+            }),
+            403 => Err(AccessError::MissingScope {
+                scope: REQUIRED_SCOPE.to_string(),
+                oauth_scopes: response.oauth_scopes,
+            }),
+            other => Err(AccessError::Transport(format!(
+                "unexpected status {other} probing access to {owner}/{repo}"
+            ))),
+        }
+    }
+
+    /// Posts `payload` as a review on `pr` via `transport`.
+    ///
+    /// `GitHubClient` only builds request targets and auth headers; it holds
+    /// no HTTP client of its own (this crate has no HTTP dependency at all
+    /// yet). The actual send is injected via [`GitHubTransport`] so a real
+    /// caller can plug in whatever HTTP stack it likes — with shared
+    /// connection pooling, a corporate proxy, a custom CA — configured once
+    /// at that layer, and tests can supply a stub instead of a live network.
+    pub fn post_review(
+        &self,
+        transport: &dyn GitHubTransport,
+        pr: &PRContext,
+        payload: &ReviewPayload,
+    ) -> Result<(), String> {
+        transport.post(&self.review_url(pr), &self.authorization_header(), payload)
+    }
+
+    /// The URL for listing or creating issue/PR comments on `pr`. GitHub
+    /// treats a PR as an issue for comment purposes, so this is the same
+    /// endpoint whether `pr` is a PR or a plain issue.
+    pub fn comments_url(&self, pr: &PRContext) -> String {
+        format!(
+            "{}/repos/{}/{}/issues/{}/comments",
+            self.base_url, pr.owner, pr.repo, pr.number
+        )
+    }
+
+    /// The URL for updating a single existing comment.
+    pub fn comment_url(&self, pr: &PRContext, comment_id: u64) -> String {
+        format!(
+            "{}/repos/{}/{}/issues/comments/{}",
+            self.base_url, pr.owner, pr.repo, comment_id
+        )
+    }
+
+    /// Posts `body` as a new, plain PR comment via `transport`. Every call
+    /// leaves a new comment; use [`GitHubClient::update_or_replace_comment`]
+    /// for a report that should stay a single, updated comment across runs.
+    pub fn post_review_comment(
+        &self,
+        transport: &dyn GitHubTransport,
+        pr: &PRContext,
+        body: &str,
+    ) -> Result<(), String> {
+        transport.create_comment(&self.comments_url(pr), &self.authorization_header(), body)
+    }
+
+    /// The URL for fetching a single pull request's own metadata (title,
+    /// body, base/head SHA, draft status).
+    pub fn pull_request_url(&self, owner: &str, repo: &str, number: u64) -> String {
+        format!("{}/repos/{}/{}/pulls/{}", self.base_url, owner, repo, number)
+    }
+
+    /// The URL for listing a pull request's changed files (path, status,
+    /// additions/deletions, patch).
+    pub fn pull_request_files_url(&self, owner: &str, repo: &str, number: u64) -> String {
+        format!("{}/files", self.pull_request_url(owner, repo, number))
+    }
+
+    /// Fetches everything [`PRContext`] needs for `owner/repo#number` via two
+    /// GET calls through `transport`: the pull request itself and its
+    /// changed-files list. This is the one place this crate reaches out for
+    /// a live PR instead of reading local build artifacts.
+    pub fn fetch_pr_context(
+        &self,
+        transport: &dyn GitHubTransport,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<PRContext, String> {
+        let authorization = self.authorization_header();
+        let pr = transport.get_json(&self.pull_request_url(owner, repo, number), &authorization)?;
+        let files =
+            transport.get_json(&self.pull_request_files_url(owner, repo, number), &authorization)?;
+        Ok(parse_pr_context(owner, repo, number, &pr, &files))
+    }
+
+    /// Posts `body` as a sticky PR comment: on the first run, a new comment
+    /// is created; on every later run for the same PR, that same comment is
+    /// updated in place instead of leaving a fresh one behind.
+    ///
+    /// `marker_tag` identifies which sticky comment this is (a PR can have
+    /// more than one, e.g. one per report section) — `marker_for` prefixes
+    /// `body` with an HTML-comment marker so it's invisible when rendered,
+    /// and every existing comment is checked for that marker rather than
+    /// relying on comment ordering or matching the report text itself, which
+    /// changes from run to run.
+    pub fn update_or_replace_comment(
+        &self,
+        transport: &dyn GitHubTransport,
+        pr: &PRContext,
+        marker_tag: &str,
+        body: &str,
+    ) -> Result<(), String> {
+        let marker = marker_for(marker_tag);
+        let full_body = format!("{marker}\n{body}");
+        let authorization = self.authorization_header();
+        let existing = transport.list_comments(&self.comments_url(pr), &authorization)?;
+
+        match existing.iter().find(|c| c.body.contains(&marker)) {
+            Some(comment) => transport.update_comment(
+                &self.comment_url(pr, comment.id),
+                &authorization,
+                &full_body,
+            ),
+            None => transport.create_comment(&self.comments_url(pr), &authorization, &full_body),
+        }
+    }
+}
+
+/// A single issue/PR comment, as returned by GitHub's "list issue comments"
+/// API, trimmed to what [`GitHubClient::update_or_replace_comment`] needs.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct IssueComment {
+    pub id: u64,
+    pub body: String,
+}
+
+/// Parses a `GET /repos/{owner}/{repo}/issues/{number}/comments` response
+/// into [`IssueComment`]s, skipping any entry that doesn't match the
+/// expected shape rather than failing the whole page.
+pub fn parse_issue_comments(response: &Value) -> Vec<IssueComment> {
+    let Some(items) = response.as_array() else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            Some(IssueComment {
+                id: item.get("id")?.as_u64()?,
+                body: item.get("body")?.as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses a `GET /repos/{owner}/{repo}/pulls/{number}` response plus its
+/// paired `.../files` response (see [`GitHubClient::fetch_pr_context`]) into
+/// a [`PRContext`]. Missing/malformed fields default rather than failing the
+/// whole parse, the same tolerance [`parse_open_prs`]/[`parse_issue_comments`]
+/// have for a partial GitHub response.
+pub fn parse_pr_context(owner: &str, repo: &str, number: u64, pr: &Value, files: &Value) -> PRContext {
+    PRContext {
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+        number,
+        title: pr.get("title").and_then(Value::as_str).unwrap_or_default().to_string(),
+        description: pr.get("body").and_then(Value::as_str).unwrap_or_default().to_string(),
+        base_sha: pr
+            .get("base")
+            .and_then(|b| b.get("sha"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        head_sha: pr
+            .get("head")
+            .and_then(|h| h.get("sha"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        is_draft: pr.get("draft").and_then(Value::as_bool).unwrap_or(false),
+        changed_files: parse_changed_files(files),
+    }
+}
+
+/// Parses a `GET /repos/{owner}/{repo}/pulls/{number}/files` response into
+/// [`ChangedFile`]s, skipping any entry that doesn't match the expected shape
+/// rather than failing the whole page.
+fn parse_changed_files(files: &Value) -> Vec<ChangedFile> {
+    let Some(items) = files.as_array() else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            Some(ChangedFile {
+                path: item.get("filename")?.as_str()?.to_string(),
+                status: item.get("status")?.as_str()?.to_string(),
+                additions: item.get("additions").and_then(Value::as_u64).unwrap_or(0) as usize,
+                deletions: item.get("deletions").and_then(Value::as_u64).unwrap_or(0) as usize,
+                patch: item.get("patch").and_then(Value::as_str).map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// The invisible marker embedded at the top of a sticky comment's body so
+/// [`GitHubClient::update_or_replace_comment`] can find its own comment on a
+/// later run without depending on comment ordering or exact report text.
+fn marker_for(tag: &str) -> String {
+    format!("<!-- dbt-pr-agent:{tag} -->")
+}
+
+/// Sends an already-built review request, or probes repo access. Implemented
+/// by a real HTTP backend at the call site, and by a stub in tests.
+pub trait GitHubTransport {
+    fn post(&self, url: &str, authorization: &str, payload: &ReviewPayload) -> Result<(), String>;
+
+    /// Issues a `GET` against `url` (a repo endpoint) and reports back the
+    /// HTTP status code together with the `X-OAuth-Scopes` header GitHub
+    /// sends on every authenticated response, so [`GitHubClient::verify_access`]
+    /// can tell "token lacks scope" from "repo doesn't exist".
+    fn get_repo_access(&self, url: &str, authorization: &str) -> Result<RepoAccessResponse, String>;
+
+    /// Lists existing comments at `url`, for sticky-comment lookup.
+    fn list_comments(&self, url: &str, authorization: &str) -> Result<Vec<IssueComment>, String>;
+
+    /// Creates a new comment with `body` at `url`.
+    fn create_comment(&self, url: &str, authorization: &str, body: &str) -> Result<(), String>;
+
+    /// Overwrites an existing comment's body at `url`.
+    fn update_comment(&self, url: &str, authorization: &str, body: &str) -> Result<(), String>;
+
+    /// Issues a `GET` against `url` and returns the parsed JSON body, for
+    /// endpoints a caller parses itself (pull request metadata, a changed-files
+    /// page) rather than one this client has a dedicated typed response for.
+    fn get_json(&self, url: &str, authorization: &str) -> Result<Value, String>;
+}
+
+/// The scope [`GitHubClient::verify_access`] requires the token to carry.
+const REQUIRED_SCOPE: &str = "repo";
+
+/// The result of a `GET /repos/{owner}/{repo}` probe: enough to distinguish
+/// "not found" from "found but forbidden" without a full response body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoAccessResponse {
+    pub status: u16,
+    /// The token's granted OAuth scopes, parsed from the `X-OAuth-Scopes`
+    /// response header. Empty for fine-grained/App tokens, which don't send
+    /// that header; those are reported as [`AccessError::MissingScope`] with
+    /// an empty list rather than misclassified as "not found".
+    pub oauth_scopes: Vec<String>,
+}
+
+/// Why [`GitHubClient::verify_access`] couldn't confirm the token can act on
+/// the target repo, surfaced before analysis runs instead of as a mid-run
+/// 403.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AccessError {
+    #[error("repository {owner}/{repo} not found, or the token can't see it")]
+    RepoNotFound { owner: String, repo: String },
+    #[error("token is missing the '{scope}' scope (granted scopes: {})", granted_scopes(oauth_scopes))]
+    MissingScope {
+        scope: String,
+        oauth_scopes: Vec<String>,
+    },
+    #[error("could not verify repo access: {0}")]
+    Transport(String),
+}
+
+/// Renders a token's granted scopes for [`AccessError::MissingScope`]'s
+/// message, since fine-grained/App tokens send no `X-OAuth-Scopes` header at
+/// all and an empty list would otherwise render as a blank.
+fn granted_scopes(oauth_scopes: &[String]) -> String {
+    if oauth_scopes.is_empty() {
+        "none reported".to_string()
+    } else {
+        oauth_scopes.join(", ")
+    }
+}
+
+/// One entry from the GitHub "list pull requests" API, trimmed to what bulk
+/// analysis needs.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OpenPr {
+    pub number: u64,
+    pub title: String,
+    pub head_sha: String,
+}
+
+/// Parses a `GET /repos/{owner}/{repo}/pulls` response into [`OpenPr`]s,
+/// skipping any entry that doesn't match the expected shape rather than
+/// failing the whole page.
+pub fn parse_open_prs(response: &Value) -> Vec<OpenPr> {
+    let Some(items) = response.as_array() else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            Some(OpenPr {
+                number: item.get("number")?.as_u64()?,
+                title: item.get("title")?.as_str()?.to_string(),
+                head_sha: item.get("head")?.get("sha")?.as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Builds the review payload for `issues`.
+///
+/// A file with no `patch` (GitHub omits it for diffs over its size limit)
+/// can't be line-anchored, so its issues are surfaced as whole-file notes in
+/// `general_comments` instead of being silently dropped; `get_model_definition`
+/// is consulted so callers can tell those notes came from whole-file SQL
+/// analysis rather than the diff. An issue with no line number for a file
+/// that *does* have a patch, or a line not actually present in the diff
+/// (GitHub rejects those), is dropped as before.
+///
+/// `issue.file_path` may be manifest-relative (project-relative) or already
+/// repo-relative depending on which agent produced it; `project_subdir` (the
+/// dbt project's location within the repo, empty at the repo root) is used to
+/// normalize every path to repo-root-relative via
+/// [`crate::project::to_repo_relative_path`] before it's matched against
+/// `pr.changed_files`, so a monorepo layout doesn't cause GitHub to silently
+/// drop a comment on a path it doesn't recognize.
+///
+/// Comment bodies are redacted before being included: this is a remote
+/// output (posted to GitHub), so secret-shaped substrings picked up from SQL
+/// or diff content default to scrubbed.
+///
+/// When `get_model_definition` resolves a line comment's file, the comment
+/// body gets a [`crate::render::render_context_block`] appended, showing
+/// `context_lines` lines of surrounding SQL above and below the flagged
+/// line — the same rationale `render_context_block`'s own doc comment
+/// gives, applied to a line comment instead of the markdown report.
+pub fn build_review(
+    pr: &PRContext,
+    issues: &[QualityIssue],
+    project_subdir: &str,
+    context_lines: usize,
+    get_model_definition: &dyn Fn(&str) -> Option<String>,
+) -> ReviewPayload {
+    let diff_lines_by_path: HashMap<&str, std::collections::HashSet<u32>> = pr
+        .changed_files
+        .iter()
+        .filter_map(|f| {
+            f.patch
+                .as_deref()
+                .map(|patch| (f.path.as_str(), lines_in_diff(patch)))
+        })
+        .collect();
+
+    let patterns = default_patterns();
+    let mut noted_large_files = std::collections::HashSet::new();
+    let mut comments = Vec::new();
+    let mut general_comments = Vec::new();
+
+    for issue in issues {
+        let file_path = to_repo_relative_path(project_subdir, &issue.file_path);
+        let has_patch = pr
+            .changed_files
+            .iter()
+            .any(|f| f.path == file_path && f.patch.is_some());
+
+        if !has_patch {
+            if noted_large_files.insert(file_path.clone())
+                && get_model_definition(&issue.file_path).is_some()
+            {
+                log::info!(
+                    "patch unavailable for large file {file_path}; falling back to whole-file analysis"
+                );
+            }
+            general_comments.push(format!("{}: {}", file_path, redact(&issue.message, &patterns)));
+            continue;
+        }
+
+        let Some(line) = issue.line_number else {
+            continue;
+        };
+        let in_diff = diff_lines_by_path
+            .get(file_path.as_str())
+            .is_some_and(|lines| lines.contains(&line));
+        if in_diff {
+            let mut body = redact(&issue.message, &patterns);
+            if let Some(source) = get_model_definition(&issue.file_path) {
+                body.push_str("\n\n");
+                body.push_str(&redact(
+                    &crate::render::render_context_block(&source, line, context_lines),
+                    &patterns,
+                ));
+            }
+            comments.push(LineComment {
+                path: file_path,
+                line,
+                body,
+            });
+        }
+    }
+
+    ReviewPayload {
+        body: "dbt-pr-agent found issues on the changed lines below.".to_string(),
+        event: "COMMENT".to_string(),
+        comments,
+        general_comments,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::severity::Severity;
+
+    #[test]
+    fn builds_review_from_two_line_anchored_issues() {
+        let pr = PRContext {
+            owner: "acme".to_string(),
+            repo: "analytics".to_string(),
+            number: 1,
+            title: String::new(),
+            description: String::new(),
+            base_sha: "base".to_string(),
+            head_sha: "head".to_string(),
+            is_draft: false,
+            changed_files: vec![ChangedFile {
+                path: "models/marts/orders.sql".to_string(),
+                status: "modified".to_string(),
+                additions: 2,
+                deletions: 0,
+                patch: Some("@@ -1,1 +1,2 @@\n context\n+select * from orders\n".to_string()),
+            }],
+        };
+
+        let issues = vec![
+            QualityIssue {
+                file_path: "models/marts/orders.sql".to_string(),
+                line_number: Some(2),
+                message: "avoid SELECT *".to_string(),
+                severity: Severity::Medium,
+            },
+            QualityIssue {
+                file_path: "models/marts/orders.sql".to_string(),
+                line_number: Some(99),
+                message: "not present in the diff".to_string(),
+                severity: Severity::Low,
+            },
+        ];
+
+        let review = build_review(&pr, &issues, "", 3, &|_| None);
+
+        assert_eq!(review.comments.len(), 1);
+        assert_eq!(review.comments[0].line, 2);
+        assert_eq!(review.event, "COMMENT");
+    }
+
+    #[test]
+    fn a_line_comment_gets_a_context_block_appended_when_a_model_definition_resolves() {
+        let pr = PRContext {
+            owner: "acme".to_string(),
+            repo: "analytics".to_string(),
+            number: 1,
+            title: String::new(),
+            description: String::new(),
+            base_sha: "base".to_string(),
+            head_sha: "head".to_string(),
+            is_draft: false,
+            changed_files: vec![ChangedFile {
+                path: "models/marts/orders.sql".to_string(),
+                status: "modified".to_string(),
+                additions: 1,
+                deletions: 0,
+                patch: Some("@@ -1,1 +1,2 @@\n context\n+select * from orders\n".to_string()),
+            }],
+        };
+
+        let issues = vec![QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: Some(2),
+            message: "avoid SELECT *".to_string(),
+            severity: Severity::Medium,
+        }];
+
+        let review = build_review(&pr, &issues, "", 1, &|path| {
+            (path == "models/marts/orders.sql")
+                .then(|| "select id\nselect * from orders\nfrom raw.orders".to_string())
+        });
+
+        assert_eq!(review.comments.len(), 1);
+        assert!(review.comments[0].body.contains("avoid SELECT *"));
+        assert!(review.comments[0].body.contains("```sql"));
+        assert!(review.comments[0].body.contains("select id"));
+    }
+
+    #[test]
+    fn comment_bodies_are_redacted_by_default() {
+        let pr = PRContext {
+            owner: "acme".to_string(),
+            repo: "analytics".to_string(),
+            number: 1,
+            title: String::new(),
+            description: String::new(),
+            base_sha: "base".to_string(),
+            head_sha: "head".to_string(),
+            is_draft: false,
+            changed_files: vec![ChangedFile {
+                path: "models/marts/orders.sql".to_string(),
+                status: "modified".to_string(),
+                additions: 1,
+                deletions: 0,
+                patch: Some("@@ -1,1 +1,2 @@\n context\n+select 1\n".to_string()),
+            }],
+        };
+
+        let issues = vec![QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: Some(2),
+            message: "connection uses password=hunter2".to_string(),
+            severity: Severity::High,
+        }];
+
+        let review = build_review(&pr, &issues, "", 3, &|_| None);
+
+        assert!(!review.comments[0].body.contains("hunter2"));
+        assert!(review.comments[0].body.contains("password="));
+    }
+
+    #[test]
+    fn defaults_to_public_github_when_no_base_url_is_configured() {
+        let client = GitHubClient::new("token");
+        assert_eq!(client.base_url(), DEFAULT_GITHUB_API_BASE_URL);
+    }
+
+    #[test]
+    fn requests_are_built_against_the_configured_enterprise_base_url() {
+        let client = GitHubClient::with_base_url("token", "https://ghe.internal/api/v3")
+            .expect("valid enterprise URL");
+
+        let pr = PRContext {
+            owner: "acme".to_string(),
+            repo: "analytics".to_string(),
+            number: 42,
+            title: String::new(),
+            description: String::new(),
+            base_sha: String::new(),
+            head_sha: String::new(),
+            changed_files: vec![],
+            is_draft: false,
+        };
+
+        assert_eq!(
+            client.review_url(&pr),
+            "https://ghe.internal/api/v3/repos/acme/analytics/pulls/42/reviews"
+        );
+    }
+
+    #[test]
+    fn rejects_a_base_url_with_no_scheme() {
+        assert!(GitHubClient::with_base_url("token", "ghe.internal/api/v3").is_err());
+    }
+
+    /// A stub [`GitHubTransport`] that records the call it received instead
+    /// of touching the network, so tests can inject it in place of a real
+    /// HTTP client.
+    struct StubTransport {
+        received: std::cell::RefCell<Option<(String, String)>>,
+    }
+
+    impl StubTransport {
+        fn new() -> Self {
+            Self {
+                received: std::cell::RefCell::new(None),
+            }
+        }
+    }
+
+    impl GitHubTransport for StubTransport {
+        fn post(
+            &self,
+            url: &str,
+            authorization: &str,
+            _payload: &ReviewPayload,
+        ) -> Result<(), String> {
+            *self.received.borrow_mut() = Some((url.to_string(), authorization.to_string()));
+            Ok(())
+        }
+
+        fn get_repo_access(
+            &self,
+            _url: &str,
+            _authorization: &str,
+        ) -> Result<RepoAccessResponse, String> {
+            unimplemented!("not exercised by tests using StubTransport for post_review")
+        }
+
+        fn list_comments(&self, _url: &str, _authorization: &str) -> Result<Vec<IssueComment>, String> {
+            unimplemented!("not exercised by tests using StubTransport for post_review")
+        }
+
+        fn create_comment(&self, _url: &str, _authorization: &str, _body: &str) -> Result<(), String> {
+            unimplemented!("not exercised by tests using StubTransport for post_review")
+        }
+
+        fn update_comment(&self, _url: &str, _authorization: &str, _body: &str) -> Result<(), String> {
+            unimplemented!("not exercised by tests using StubTransport for post_review")
+        }
+
+        fn get_json(&self, _url: &str, _authorization: &str) -> Result<Value, String> {
+            unimplemented!("not exercised by tests using StubTransport for post_review")
+        }
+    }
+
+    /// A stub [`GitHubTransport`] whose `get_repo_access` returns a
+    /// pre-scripted response, for [`GitHubClient::verify_access`] tests.
+    struct StubAccessTransport {
+        response: Result<RepoAccessResponse, String>,
+    }
+
+    impl GitHubTransport for StubAccessTransport {
+        fn post(&self, _url: &str, _authorization: &str, _payload: &ReviewPayload) -> Result<(), String> {
+            unimplemented!("not exercised by verify_access tests")
+        }
+
+        fn get_repo_access(
+            &self,
+            _url: &str,
+            _authorization: &str,
+        ) -> Result<RepoAccessResponse, String> {
+            self.response.clone()
+        }
+
+        fn list_comments(&self, _url: &str, _authorization: &str) -> Result<Vec<IssueComment>, String> {
+            unimplemented!("not exercised by verify_access tests")
+        }
+
+        fn create_comment(&self, _url: &str, _authorization: &str, _body: &str) -> Result<(), String> {
+            unimplemented!("not exercised by verify_access tests")
+        }
+
+        fn update_comment(&self, _url: &str, _authorization: &str, _body: &str) -> Result<(), String> {
+            unimplemented!("not exercised by verify_access tests")
+        }
+
+        fn get_json(&self, _url: &str, _authorization: &str) -> Result<Value, String> {
+            unimplemented!("not exercised by verify_access tests")
+        }
+    }
+
+    /// A stub [`GitHubTransport`] that records comment create/update calls
+    /// and serves a scripted `list_comments` response, for
+    /// [`GitHubClient::update_or_replace_comment`] tests.
+    struct StubCommentTransport {
+        existing_comments: Vec<IssueComment>,
+        created: std::cell::RefCell<Vec<(String, String)>>,
+        updated: std::cell::RefCell<Vec<(String, String)>>,
+    }
+
+    impl StubCommentTransport {
+        fn with_existing(existing_comments: Vec<IssueComment>) -> Self {
+            Self {
+                existing_comments,
+                created: std::cell::RefCell::new(Vec::new()),
+                updated: std::cell::RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl GitHubTransport for StubCommentTransport {
+        fn post(&self, _url: &str, _authorization: &str, _payload: &ReviewPayload) -> Result<(), String> {
+            unimplemented!("not exercised by comment tests")
+        }
+
+        fn get_repo_access(
+            &self,
+            _url: &str,
+            _authorization: &str,
+        ) -> Result<RepoAccessResponse, String> {
+            unimplemented!("not exercised by comment tests")
+        }
+
+        fn list_comments(&self, _url: &str, _authorization: &str) -> Result<Vec<IssueComment>, String> {
+            Ok(self.existing_comments.clone())
+        }
+
+        fn create_comment(&self, url: &str, _authorization: &str, body: &str) -> Result<(), String> {
+            self.created
+                .borrow_mut()
+                .push((url.to_string(), body.to_string()));
+            Ok(())
+        }
+
+        fn update_comment(&self, url: &str, _authorization: &str, body: &str) -> Result<(), String> {
+            self.updated
+                .borrow_mut()
+                .push((url.to_string(), body.to_string()));
+            Ok(())
+        }
+
+        fn get_json(&self, _url: &str, _authorization: &str) -> Result<Value, String> {
+            unimplemented!("not exercised by comment tests")
+        }
+    }
+
+    /// A stub [`GitHubTransport`] that serves scripted responses for the two
+    /// GET calls [`GitHubClient::fetch_pr_context`] makes, keyed by whether
+    /// `url` ends in `/files`.
+    struct StubPrTransport {
+        pr: Value,
+        files: Value,
+    }
+
+    impl GitHubTransport for StubPrTransport {
+        fn post(&self, _url: &str, _authorization: &str, _payload: &ReviewPayload) -> Result<(), String> {
+            unimplemented!("not exercised by fetch_pr_context tests")
+        }
+
+        fn get_repo_access(
+            &self,
+            _url: &str,
+            _authorization: &str,
+        ) -> Result<RepoAccessResponse, String> {
+            unimplemented!("not exercised by fetch_pr_context tests")
+        }
+
+        fn list_comments(&self, _url: &str, _authorization: &str) -> Result<Vec<IssueComment>, String> {
+            unimplemented!("not exercised by fetch_pr_context tests")
+        }
+
+        fn create_comment(&self, _url: &str, _authorization: &str, _body: &str) -> Result<(), String> {
+            unimplemented!("not exercised by fetch_pr_context tests")
+        }
+
+        fn update_comment(&self, _url: &str, _authorization: &str, _body: &str) -> Result<(), String> {
+            unimplemented!("not exercised by fetch_pr_context tests")
+        }
+
+        fn get_json(&self, url: &str, _authorization: &str) -> Result<Value, String> {
+            if url.ends_with("/files") {
+                Ok(self.files.clone())
+            } else {
+                Ok(self.pr.clone())
+            }
+        }
+    }
+
+    #[test]
+    fn post_review_sends_the_built_url_and_auth_header_through_the_injected_transport() {
+        let client = GitHubClient::new("secret-token");
+        let pr = PRContext {
+            owner: "acme".to_string(),
+            repo: "analytics".to_string(),
+            number: 7,
+            title: String::new(),
+            description: String::new(),
+            base_sha: String::new(),
+            head_sha: String::new(),
+            changed_files: vec![],
+            is_draft: false,
+        };
+        let payload = ReviewPayload {
+            body: "looks good".to_string(),
+            event: "COMMENT".to_string(),
+            comments: vec![],
+            general_comments: vec![],
+        };
+        let transport = StubTransport::new();
+
+        client
+            .post_review(&transport, &pr, &payload)
+            .expect("stub transport never fails");
+
+        let (url, authorization) = transport.received.into_inner().expect("transport was called");
+        assert_eq!(
+            url,
+            "https://api.github.com/repos/acme/analytics/pulls/7/reviews"
+        );
+        assert_eq!(authorization, "Bearer secret-token");
+    }
+
+    #[test]
+    fn missing_patch_falls_back_to_a_whole_file_note_via_model_definition() {
+        let pr = PRContext {
+            owner: "acme".to_string(),
+            repo: "analytics".to_string(),
+            number: 1,
+            title: String::new(),
+            description: String::new(),
+            base_sha: "base".to_string(),
+            head_sha: "head".to_string(),
+            is_draft: false,
+            changed_files: vec![ChangedFile {
+                path: "models/marts/huge_refactor.sql".to_string(),
+                status: "modified".to_string(),
+                additions: 5000,
+                deletions: 4800,
+                patch: None,
+            }],
+        };
+
+        let issues = vec![QualityIssue {
+            file_path: "models/marts/huge_refactor.sql".to_string(),
+            line_number: None,
+            message: "avoid SELECT *".to_string(),
+            severity: Severity::Medium,
+        }];
+
+        let review = build_review(&pr, &issues, "", 3, &|path| {
+            (path == "models/marts/huge_refactor.sql").then(|| "select * from orders".to_string())
+        });
+
+        assert!(
+            review.comments.is_empty(),
+            "an unpatched file can't be line-anchored"
+        );
+        assert_eq!(review.general_comments.len(), 1);
+        assert!(review.general_comments[0].contains("avoid SELECT *"));
+    }
+
+    #[test]
+    fn a_project_relative_path_in_a_monorepo_subdir_is_normalized_before_matching_the_diff() {
+        let pr = PRContext {
+            owner: "acme".to_string(),
+            repo: "analytics".to_string(),
+            number: 1,
+            title: String::new(),
+            description: String::new(),
+            base_sha: "base".to_string(),
+            head_sha: "head".to_string(),
+            is_draft: false,
+            changed_files: vec![ChangedFile {
+                path: "analytics/models/marts/orders.sql".to_string(),
+                status: "modified".to_string(),
+                additions: 2,
+                deletions: 0,
+                patch: Some("@@ -1,1 +1,2 @@\n context\n+select * from orders\n".to_string()),
+            }],
+        };
+
+        let issues = vec![QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: Some(2),
+            message: "avoid SELECT *".to_string(),
+            severity: Severity::Medium,
+        }];
+
+        let review = build_review(&pr, &issues, "analytics", 3, &|_| None);
+
+        assert_eq!(review.comments.len(), 1);
+        assert_eq!(review.comments[0].path, "analytics/models/marts/orders.sql");
+        assert_eq!(review.comments[0].line, 2);
+    }
+
+    #[test]
+    fn a_403_with_no_matching_scope_is_reported_as_a_missing_scope_error() {
+        let client = GitHubClient::new("token");
+        let transport = StubAccessTransport {
+            response: Ok(RepoAccessResponse {
+                status: 403,
+                oauth_scopes: vec!["read:org".to_string()],
+            }),
+        };
+
+        let err = client
+            .verify_access(&transport, "acme", "analytics")
+            .expect_err("403 without repo scope should fail");
+
+        assert_eq!(
+            err,
+            AccessError::MissingScope {
+                scope: "repo".to_string(),
+                oauth_scopes: vec!["read:org".to_string()],
+            }
+        );
+        assert!(err.to_string().contains("repo"));
+    }
+
+    #[test]
+    fn a_404_is_distinguished_from_a_missing_scope() {
+        let client = GitHubClient::new("token");
+        let transport = StubAccessTransport {
+            response: Ok(RepoAccessResponse {
+                status: 404,
+                oauth_scopes: vec![],
+            }),
+        };
+
+        let err = client
+            .verify_access(&transport, "acme", "ghost-repo")
+            .expect_err("404 should fail");
+
+        assert_eq!(
+            err,
+            AccessError::RepoNotFound {
+                owner: "acme".to_string(),
+                repo: "ghost-repo".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn a_200_response_verifies_access() {
+        let client = GitHubClient::new("token");
+        let transport = StubAccessTransport {
+            response: Ok(RepoAccessResponse {
+                status: 200,
+                oauth_scopes: vec!["repo".to_string()],
+            }),
+        };
+
+        assert!(client.verify_access(&transport, "acme", "analytics").is_ok());
+    }
+
+    #[test]
+    fn parses_open_prs_from_a_list_pulls_response() {
+        let response = serde_json::json!([
+            { "number": 12, "title": "Add stg_payments", "head": { "sha": "aaa111" } },
+            { "number": 13, "title": "Fix orders_summary join", "head": { "sha": "bbb222" } },
+        ]);
+
+        let prs = parse_open_prs(&response);
+
+        assert_eq!(prs.len(), 2);
+        assert_eq!(prs[0].number, 12);
+        assert_eq!(prs[1].head_sha, "bbb222");
+    }
+
+    fn test_pr() -> PRContext {
+        PRContext {
+            owner: "acme".to_string(),
+            repo: "analytics".to_string(),
+            number: 7,
+            title: String::new(),
+            description: String::new(),
+            base_sha: String::new(),
+            head_sha: String::new(),
+            changed_files: vec![],
+            is_draft: false,
+        }
+    }
+
+    #[test]
+    fn parses_issue_comments_from_a_list_comments_response() {
+        let response = serde_json::json!([
+            { "id": 1, "body": "first comment" },
+            { "id": 2, "body": "<!-- dbt-pr-agent:review --> second comment" },
+        ]);
+
+        let comments = parse_issue_comments(&response);
+
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[1].id, 2);
+    }
+
+    #[test]
+    fn update_or_replace_comment_creates_a_new_comment_when_none_exists_yet() {
+        let client = GitHubClient::new("token");
+        let pr = test_pr();
+        let transport = StubCommentTransport::with_existing(vec![]);
+
+        client
+            .update_or_replace_comment(&transport, &pr, "review", "the report body")
+            .expect("create should succeed");
+
+        assert_eq!(transport.created.borrow().len(), 1);
+        assert!(transport.updated.borrow().is_empty());
+        let (url, body) = &transport.created.borrow()[0];
+        assert_eq!(url, "https://api.github.com/repos/acme/analytics/issues/7/comments");
+        assert!(body.contains("<!-- dbt-pr-agent:review -->"));
+        assert!(body.contains("the report body"));
+    }
+
+    #[test]
+    fn update_or_replace_comment_updates_the_existing_marked_comment_instead_of_creating_a_new_one() {
+        let client = GitHubClient::new("token");
+        let pr = test_pr();
+        let transport = StubCommentTransport::with_existing(vec![
+            IssueComment {
+                id: 1,
+                body: "unrelated human comment".to_string(),
+            },
+            IssueComment {
+                id: 42,
+                body: "<!-- dbt-pr-agent:review -->\nstale report".to_string(),
+            },
+        ]);
+
+        client
+            .update_or_replace_comment(&transport, &pr, "review", "fresh report body")
+            .expect("update should succeed");
+
+        assert!(transport.created.borrow().is_empty());
+        assert_eq!(transport.updated.borrow().len(), 1);
+        let (url, body) = &transport.updated.borrow()[0];
+        assert_eq!(
+            url,
+            "https://api.github.com/repos/acme/analytics/issues/comments/42"
+        );
+        assert!(body.contains("fresh report body"));
+    }
+
+    #[test]
+    fn different_marker_tags_are_treated_as_different_sticky_comments() {
+        let client = GitHubClient::new("token");
+        let pr = test_pr();
+        let transport = StubCommentTransport::with_existing(vec![IssueComment {
+            id: 1,
+            body: "<!-- dbt-pr-agent:quality -->\nquality report".to_string(),
+        }]);
+
+        client
+            .update_or_replace_comment(&transport, &pr, "performance", "performance report")
+            .expect("create should succeed");
+
+        assert_eq!(
+            transport.created.borrow().len(),
+            1,
+            "a differently-tagged marker shouldn't match the existing comment"
+        );
+        assert!(transport.updated.borrow().is_empty());
+    }
+
+    #[test]
+    fn parses_a_pr_context_from_a_pull_request_and_files_response() {
+        let pr = serde_json::json!({
+            "title": "Add stg_payments",
+            "body": "adds a new staging model",
+            "draft": true,
+            "base": { "sha": "base111" },
+            "head": { "sha": "head222" },
+        });
+        let files = serde_json::json!([
+            {
+                "filename": "models/staging/stg_payments.sql",
+                "status": "added",
+                "additions": 12,
+                "deletions": 0,
+                "patch": "@@ -0,0 +1,12 @@\n+select * from raw.payments\n",
+            }
+        ]);
+
+        let context = parse_pr_context("acme", "analytics", 7, &pr, &files);
+
+        assert_eq!(context.title, "Add stg_payments");
+        assert_eq!(context.base_sha, "base111");
+        assert_eq!(context.head_sha, "head222");
+        assert!(context.is_draft);
+        assert_eq!(context.changed_files.len(), 1);
+        assert_eq!(context.changed_files[0].path, "models/staging/stg_payments.sql");
+    }
+
+    #[test]
+    fn a_malformed_pull_request_response_parses_to_defaults_instead_of_panicking() {
+        let context = parse_pr_context("acme", "analytics", 7, &serde_json::json!({}), &serde_json::json!({}));
+
+        assert_eq!(context.title, "");
+        assert!(!context.is_draft);
+        assert!(context.changed_files.is_empty());
+    }
+
+    #[test]
+    fn fetch_pr_context_issues_both_gets_through_the_transport() {
+        let client = GitHubClient::new("token");
+        let transport = StubPrTransport {
+            pr: serde_json::json!({ "title": "Fix orders join", "draft": false }),
+            files: serde_json::json!([
+                { "filename": "models/marts/orders.sql", "status": "modified", "additions": 1, "deletions": 1 }
+            ]),
+        };
+
+        let context = client
+            .fetch_pr_context(&transport, "acme", "analytics", 7)
+            .expect("stub transport never fails");
+
+        assert_eq!(context.title, "Fix orders join");
+        assert_eq!(context.changed_files.len(), 1);
+        assert_eq!(context.owner, "acme");
+        assert_eq!(context.number, 7);
+    }
+}
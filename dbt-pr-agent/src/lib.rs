@@ -0,0 +1,433 @@
+pub mod agents;
+pub mod artifact;
+pub mod artifacts;
+pub mod benchmark;
+pub mod bulk;
+pub mod cancellation;
+pub mod config;
+pub mod dbt_runner;
+pub mod debounce;
+pub mod diff;
+pub mod filter;
+pub mod github;
+pub mod lineage;
+pub mod llm;
+pub mod manifest;
+pub mod model_source_cache;
+pub mod notify;
+pub mod orchestrator;
+pub mod project;
+pub mod redact;
+pub mod render;
+pub mod report;
+pub mod risk_rules;
+pub mod severity;
+pub mod state;
+pub mod warehouse;
+pub mod watch;
+
+use config::{AgentKind, PrOverrides, RuntimeOptions};
+use github::PRContext;
+use report::{Priority, Recommendation};
+use severity::Severity;
+
+/// A dbt project's manifest-derived state for one PR: the head commit's
+/// lineage graph (and its `ModelInfo`s, for [`manifest::discover_changed_models`]),
+/// plus the base commit's graph when available. Building this is what turns
+/// [`analyze_pr`] into [`analyze_pr_with_manifest`] — everything lineage-aware
+/// (impact, breaking changes, stale sources) needs a compiled manifest, which
+/// isn't fetched from a diff alone.
+pub struct ManifestContext {
+    head_nodes: Vec<artifacts::ManifestNode>,
+    head_models: Vec<manifest::ModelInfo>,
+    /// `Arc`-wrapped so [`orchestrator::run_detailed_or_summary_concurrent`]
+    /// can share it across the per-model analysis threads without cloning
+    /// the graph itself.
+    head_graph: std::sync::Arc<lineage::LineageGraph>,
+    base_graph: Option<lineage::LineageGraph>,
+}
+
+impl ManifestContext {
+    /// Builds the head-commit half of the context from a parsed
+    /// `manifest.json` document.
+    pub fn from_head_manifest(manifest: &serde_json::Value) -> Self {
+        let nodes = artifacts::parse_manifest_nodes(manifest);
+        let models = artifacts::manifest_nodes_to_model_infos(&nodes);
+        Self {
+            head_graph: std::sync::Arc::new(lineage::LineageGraph::from_models(models.clone())),
+            head_models: models,
+            head_nodes: nodes,
+            base_graph: None,
+        }
+    }
+
+    /// Attaches the base commit's graph, enabling the structural-diff checks
+    /// (currently: [`agents::breaking_changes::detect_orphaned_by_deletion`])
+    /// that need to know what a PR removed. Optional: a context built without
+    /// this call simply skips those checks, the same way
+    /// [`lineage::LineageGraph::with_exposures`] is optional.
+    pub fn with_base_manifest(mut self, manifest: &serde_json::Value) -> Self {
+        let nodes = artifacts::parse_manifest_nodes(manifest);
+        let models = artifacts::manifest_nodes_to_model_infos(&nodes);
+        self.base_graph = Some(lineage::LineageGraph::from_models(models));
+        self
+    }
+
+    /// The head commit's compiled SQL for the model at `original_file_path`,
+    /// for callers (e.g. [`github::build_review`]'s `get_model_definition`)
+    /// that want to show surrounding source around a finding. `None` when the
+    /// path doesn't match a node, or the manifest has no compiled SQL for it
+    /// (a `dbt parse`-only manifest — see [`artifacts::is_parse_only_manifest`]).
+    pub fn compiled_code_for(&self, original_file_path: &str) -> Option<String> {
+        self.head_nodes
+            .iter()
+            .find(|n| n.original_file_path == original_file_path)
+            .and_then(|n| n.compiled_code.clone())
+    }
+
+    /// How many downstream models `changed_paths` impacts, per
+    /// [`lineage::LineageGraph::analyze_impact_report`]. 0 when none of
+    /// `changed_paths` resolve to a model in this manifest. Exposed so
+    /// callers (e.g. [`risk_rules::RiskRuleContext::downstream_models`]) can
+    /// get this one number without reaching into the graph directly.
+    pub fn downstream_model_count(&self, changed_paths: &[String], include_upstream: bool) -> usize {
+        let changed_models = manifest::discover_changed_models(&self.head_models, changed_paths);
+        if changed_models.is_empty() {
+            return 0;
+        }
+        self.head_graph
+            .analyze_impact_report(&changed_models, include_upstream)
+            .impacts
+            .len()
+    }
+
+    /// One [`notify::OwnedFinding`] per changed model, its impact score as
+    /// the message and its `meta.owner`/`group` (see [`manifest::ModelInfo::owner`])
+    /// as the owner, for [`notify::route_by_owner`] to route to the right
+    /// team's channel. Empty when none of `changed_paths` resolve to a model.
+    pub fn owned_findings(&self, changed_paths: &[String]) -> Vec<notify::OwnedFinding> {
+        let changed_models = manifest::discover_changed_models(&self.head_models, changed_paths);
+        changed_models
+            .iter()
+            .map(|model| {
+                let score = self.head_graph.calculate_impact_score(std::slice::from_ref(model));
+                let owner = self
+                    .head_models
+                    .iter()
+                    .find(|m| &m.unique_id == model)
+                    .and_then(|m| m.owner.clone());
+                let mut message = format!(
+                    "impact score {:.2} ({} downstream model(s))",
+                    score.score, score.total_downstream
+                );
+                if let Some(note) = score.fan_out_note() {
+                    message.push_str(&format!("; {note}"));
+                }
+                notify::OwnedFinding {
+                    model: model.clone(),
+                    owner,
+                    message,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A single finding summarizing `report`'s downstream blast radius: the
+/// rebuild command, the deepest impact chain, and any affected
+/// exposures/metrics. `None` when the changeset has no downstream impact at
+/// all, so a clean PR doesn't get a "no impact" finding cluttering the report.
+fn recommendation_from_impact_report(report: &lineage::ImpactReport) -> Option<Recommendation> {
+    if report.impacts.is_empty() {
+        return None;
+    }
+    let mut message = format!(
+        "{} downstream model(s) affected; rebuild with `{}`",
+        report.impacts.len(),
+        report.rebuild_plan.command
+    );
+    if let Some(deepest) = &report.deepest_impact {
+        message.push_str(&format!("; {deepest}"));
+    }
+    if !report.affected_exposures.is_empty() {
+        message.push_str(&format!(
+            "; affects exposure(s): {}",
+            report.affected_exposures.join(", ")
+        ));
+    }
+    if !report.affected_metrics.is_empty() {
+        message.push_str(&format!(
+            "; affects metric(s): {}",
+            report.affected_metrics.join(", ")
+        ));
+    }
+    Some(Recommendation {
+        source: AgentKind::Impact,
+        message,
+        priority: Priority::Medium,
+        confidence: None,
+    })
+}
+
+fn recommendation_from_stale_source_dependency(
+    dep: &agents::impact::StaleSourceDependency,
+) -> Recommendation {
+    Recommendation {
+        source: AgentKind::Impact,
+        message: format!(
+            "{} depends on source {} which is stale ({})",
+            dep.model, dep.source, dep.status
+        ),
+        priority: report::Priority::from_severity(dep.severity),
+        confidence: None,
+    }
+}
+
+fn recommendation_from_orphaned_reference(
+    orphan: &agents::breaking_changes::OrphanedReference,
+) -> Recommendation {
+    Recommendation {
+        source: AgentKind::Impact,
+        message: format!(
+            "{} references {}, which this PR removes, and will break",
+            orphan.referencing_node, orphan.removed_node
+        ),
+        priority: report::Priority::from_severity(orphan.severity),
+        confidence: None,
+    }
+}
+
+/// A single changed model's own [`lineage::ImpactScore`], surfaced as a
+/// [`Recommendation`] for the per-model breakdown
+/// [`analyze_pr_with_manifest`] runs via
+/// [`orchestrator::run_detailed_or_summary_concurrent`].
+fn recommendation_from_impact_score(model: &str, score: &lineage::ImpactScore) -> Recommendation {
+    let mut message = format!(
+        "{model}: impact score {:.2} ({} downstream model(s))",
+        score.score, score.total_downstream
+    );
+    if let Some(note) = score.fan_out_note() {
+        message.push_str(&format!("; {note}"));
+    }
+    Recommendation {
+        source: AgentKind::Impact,
+        message,
+        priority: Priority::Low,
+        confidence: None,
+    }
+}
+
+/// [`analyze_pr`] plus every manifest-aware check a compiled `manifest.json`
+/// unlocks: impact analysis, stale-source-dependency detection, a per-model
+/// impact-score breakdown, and (when `manifest` carries a base graph)
+/// breaking changes from deleted nodes. `include_upstream` and `freshness`
+/// are passed straight through to
+/// [`lineage::LineageGraph::analyze_impact_report`] and
+/// [`agents::impact::detect_stale_source_dependencies`] respectively.
+/// `summary_mode_threshold` and `max_parallel_agents` gate and bound the
+/// per-model breakdown, via [`orchestrator::run_detailed_or_summary_concurrent`]:
+/// a PR touching more than `summary_mode_threshold` models gets the
+/// aggregate impact report above only, not a per-model line each.
+pub fn analyze_pr_with_manifest(
+    pr: &PRContext,
+    manifest: &ManifestContext,
+    include_upstream: bool,
+    freshness: &[artifacts::SourceFreshness],
+    summary_mode_threshold: usize,
+    max_parallel_agents: usize,
+) -> Vec<report::Recommendation> {
+    let mut recommendations = analyze_pr(pr);
+
+    let changed_paths: Vec<String> = pr.changed_paths().map(String::from).collect();
+    let changed_models = manifest::discover_changed_models(&manifest.head_models, &changed_paths);
+
+    if let Some(anomaly) = orchestrator::stale_manifest_anomaly(&changed_paths, &changed_models) {
+        recommendations.push(anomaly);
+    }
+
+    if !changed_models.is_empty() {
+        let impact_report = manifest
+            .head_graph
+            .analyze_impact_report(&changed_models, include_upstream);
+        recommendations.extend(recommendation_from_impact_report(&impact_report));
+
+        recommendations.extend(
+            agents::impact::detect_stale_source_dependencies(
+                &manifest.head_graph,
+                &changed_models,
+                freshness,
+            )
+            .iter()
+            .map(recommendation_from_stale_source_dependency),
+        );
+
+        let (mode, scores) = orchestrator::run_detailed_or_summary_concurrent(
+            &changed_models,
+            summary_mode_threshold,
+            max_parallel_agents,
+            manifest.head_graph.clone(),
+            |graph, model| graph.calculate_impact_score(&[model.to_string()]),
+        );
+        recommendations.extend(mode.note().map(|message| Recommendation {
+            source: AgentKind::Impact,
+            message,
+            priority: Priority::Low,
+            confidence: None,
+        }));
+        recommendations.extend(
+            changed_models
+                .iter()
+                .zip(&scores)
+                .map(|(model, score)| recommendation_from_impact_score(model, score)),
+        );
+    }
+
+    if let Some(base_graph) = &manifest.base_graph {
+        let diff = lineage::compare_graphs(base_graph, &manifest.head_graph);
+        recommendations.extend(
+            agents::breaking_changes::detect_orphaned_by_deletion(base_graph, &diff.removed_nodes)
+                .iter()
+                .map(recommendation_from_orphaned_reference),
+        );
+    }
+
+    report::dedupe_recommendations(recommendations)
+}
+
+/// Evaluates `risk_rules` against `context` via
+/// [`risk_rules::apply_risk_rules`], appending a [`Recommendation`] for each
+/// rule that escalates, and returns the (possibly escalated) severity for
+/// [`config::GateSummary::from_result`] to gate on. `computed` is the
+/// severity already observed from `recommendations` (typically
+/// [`report::max_severity`]) before any org-declared rule runs.
+pub fn escalate_with_risk_rules(
+    recommendations: &mut Vec<Recommendation>,
+    computed: Severity,
+    risk_rules: &[risk_rules::RiskRule],
+    context: &risk_rules::RiskRuleContext,
+) -> Severity {
+    let (escalated, reasons) = risk_rules::apply_risk_rules(risk_rules, context, computed);
+    for reason in reasons {
+        recommendations.push(Recommendation {
+            source: AgentKind::Impact,
+            message: format!("risk rule escalated this PR's risk to {escalated:?}: {reason}"),
+            priority: Priority::from_severity(escalated),
+            confidence: None,
+        });
+    }
+    escalated
+}
+
+/// Applies the resolved [`RuntimeOptions`]' file filter to `pr` in place,
+/// so every agent downstream sees the same filtered set of changed files.
+pub fn apply_file_filter(pr: &mut PRContext, options: &RuntimeOptions) {
+    let files = std::mem::take(&mut pr.changed_files);
+    let (kept, dropped) = filter::filter_changed_files(files, &options.file_filter);
+    if dropped > 0 {
+        log::info!(
+            "PR #{}: filtered out {dropped} changed file(s) via include/exclude config",
+            pr.number
+        );
+    }
+    pr.changed_files = kept;
+}
+
+/// Resolves the [`RuntimeOptions`] to use for analyzing `pr`, applying any
+/// inline override directive found in the PR description on top of `base`.
+pub fn resolve_runtime_options(base: RuntimeOptions, pr: &PRContext) -> RuntimeOptions {
+    match PrOverrides::parse_from_description(&pr.description) {
+        Ok(Some(overrides)) => {
+            log::info!(
+                "PR #{}: applying inline dbt-pr-agent override from description",
+                pr.number
+            );
+            overrides.apply(base)
+        }
+        Ok(None) => base,
+        Err(reason) => {
+            log::warn!(
+                "PR #{}: ignoring malformed dbt-pr-agent directive: {reason}",
+                pr.number
+            );
+            base
+        }
+    }
+}
+
+/// Runs the deterministic, diff-only quality checks
+/// ([`agents::quality::analyze_new_model_file`]) against every changed
+/// `.sql` file in `pr` that GitHub sent a patch for, and returns the
+/// resulting recommendations, deduplicated across files.
+///
+/// This is scoped to what's knowable from a PR's diff alone: a compiled
+/// manifest for `pr.head_sha` isn't fetched here, so the lineage-aware
+/// checks (impact, performance, breaking changes) that need one are out of
+/// scope until a manifest-fetch step exists alongside [`github::GitHubClient::fetch_pr_context`].
+pub fn analyze_pr(pr: &PRContext) -> Vec<report::Recommendation> {
+    let recommendations = pr
+        .changed_files
+        .iter()
+        .filter(|f| f.path.ends_with(".sql"))
+        .filter_map(|f| f.patch.as_deref().map(|patch| (f.path.as_str(), patch)))
+        .flat_map(|(path, patch)| {
+            let added_sql = diff::parse_unified_diff(patch).added_lines.join("\n");
+            agents::quality::analyze_new_model_file(path, &added_sql)
+        })
+        .collect();
+    report::dedupe_recommendations(recommendations)
+}
+
+/// Runs [`agents::quality::sql_rules::lint`] against every changed `.sql`
+/// file's added lines, with each [`agents::quality::QualityIssue`]'s line
+/// number remapped from its position in that added-lines text back onto the
+/// diff's real new-file line number (see [`diff::added_lines_with_numbers`]),
+/// so the result can be fed straight into [`github::build_review`] to post
+/// as line-anchored review comments.
+pub fn lint_pr(
+    pr: &PRContext,
+    config: &agents::quality::sql_rules::SqlLintConfig,
+) -> Vec<agents::quality::QualityIssue> {
+    pr.changed_files
+        .iter()
+        .filter(|f| f.path.ends_with(".sql"))
+        .filter_map(|f| f.patch.as_deref().map(|patch| (f.path.as_str(), patch)))
+        .flat_map(|(path, patch)| {
+            let added = diff::added_lines_with_numbers(patch);
+            let sql = added.iter().map(|(_, line)| line.as_str()).collect::<Vec<_>>().join("\n");
+            let mut issues = agents::quality::sql_rules::lint(path, &sql, config);
+            for issue in &mut issues {
+                issue.line_number = issue
+                    .line_number
+                    .and_then(|local| added.get(local as usize - 1))
+                    .map(|(real_line, _)| *real_line);
+            }
+            issues
+        })
+        .collect()
+}
+
+/// A changed `.sql` file's model name, guessed from its path's file stem —
+/// enough to match [`RuntimeOptions::protected_models`] glob patterns
+/// without a compiled manifest. Unlike
+/// [`crate::lineage::LineageGraph::protected_models_touched`], this only
+/// sees files the PR touches directly; a protected model touched solely by a
+/// downstream dependency needs that manifest-aware check instead.
+pub fn changed_model_names(pr: &PRContext) -> Vec<String> {
+    pr.changed_files
+        .iter()
+        .filter(|f| f.path.ends_with(".sql"))
+        .filter_map(|f| std::path::Path::new(&f.path).file_stem())
+        .filter_map(|stem| stem.to_str())
+        .map(str::to_string)
+        .collect()
+}
+
+/// True when any of `model_names` matches one of `patterns` (e.g.
+/// `revenue_mart`, `finance_*`), the same glob matching
+/// [`crate::lineage::LineageGraph::protected_models_touched`] uses.
+pub fn matches_protected_model(model_names: &[String], patterns: &[String]) -> bool {
+    let compiled: Vec<glob::Pattern> = patterns.iter().filter_map(|p| glob::Pattern::new(p).ok()).collect();
+    model_names
+        .iter()
+        .any(|name| compiled.iter().any(|p| p.matches(name)))
+}
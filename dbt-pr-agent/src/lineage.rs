@@ -0,0 +1,1569 @@
+//! The dependency graph between dbt models, and impact analysis over it.
+
+pub mod columns;
+
+use crate::artifacts::{ManifestExposure, ManifestMetric};
+use crate::manifest::{Access, Materialization, ModelInfo};
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+/// The dbt project's dependency graph, keyed by `unique_id`.
+pub struct LineageGraph {
+    nodes: HashMap<String, ModelInfo>,
+    /// Dashboards/notebooks/apps declared in the manifest's `exposures`, for
+    /// [`analyze_impact_report`](Self::analyze_impact_report)'s
+    /// `affected_exposures`. Empty unless populated via
+    /// [`with_exposures`](Self::with_exposures).
+    exposures: Vec<ManifestExposure>,
+    /// Metrics declared in the manifest's `metrics`, for `affected_metrics`
+    /// the same way. Empty unless populated via
+    /// [`with_metrics`](Self::with_metrics).
+    metrics: Vec<ManifestMetric>,
+}
+
+/// A model impacted, directly or transitively, by a changed model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Impact {
+    pub unique_id: String,
+    /// Set when this impact was attributed through an ephemeral model that
+    /// changed: ephemeral models don't materialize, so their change is
+    /// inlined into the consuming model's compiled SQL instead of producing
+    /// an intermediate table to rebuild.
+    pub inlined_from: Option<String>,
+    /// Number of edges from the changed model to this one (1 = direct child).
+    pub depth: usize,
+}
+
+/// Weight applied to a changeset's highest single-model fan-out, on top of
+/// its flat total-downstream count, in [`LineageGraph::calculate_impact_score`].
+const FAN_OUT_SCORE_WEIGHT: f64 = 0.5;
+
+/// A single number summarizing the downstream risk of a changeset, weighted
+/// by both breadth (total downstream models, see
+/// [`LineageGraph::analyze_impact`]) and depth of blast radius at a single
+/// point (the highest fan-out among the changed models themselves).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpactScore {
+    pub total_downstream: usize,
+    /// The changed model with the most direct dependents, and how many it
+    /// has. `None` when nothing in the changeset has any.
+    pub highest_fan_out: Option<(String, usize)>,
+    pub score: f64,
+}
+
+impl ImpactScore {
+    /// "highest fan-out: model X feeds 112 models", for the report. `None`
+    /// mirrors [`highest_fan_out`](Self::highest_fan_out).
+    pub fn fan_out_note(&self) -> Option<String> {
+        let (model, count) = self.highest_fan_out.as_ref()?;
+        Some(format!("highest fan-out: {model} feeds {count} models"))
+    }
+}
+
+impl LineageGraph {
+    pub fn from_models(models: Vec<ModelInfo>) -> Self {
+        let nodes = models
+            .into_iter()
+            .map(|m| (m.unique_id.clone(), m))
+            .collect();
+        Self {
+            nodes,
+            exposures: Vec::new(),
+            metrics: Vec::new(),
+        }
+    }
+
+    /// Attaches the manifest's exposures, so
+    /// [`analyze_impact_report`](Self::analyze_impact_report) can report
+    /// `affected_exposures`. Optional: a graph built without this call
+    /// simply reports no affected exposures, the same way one built without
+    /// [`with_metrics`](Self::with_metrics) reports no affected metrics.
+    pub fn with_exposures(mut self, exposures: Vec<ManifestExposure>) -> Self {
+        self.exposures = exposures;
+        self
+    }
+
+    /// Attaches the manifest's metrics, so
+    /// [`analyze_impact_report`](Self::analyze_impact_report) can report
+    /// `affected_metrics`.
+    pub fn with_metrics(mut self, metrics: Vec<ManifestMetric>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn node(&self, unique_id: &str) -> Option<&ModelInfo> {
+        self.nodes.get(unique_id)
+    }
+
+    fn node_ids(&self) -> HashSet<&str> {
+        self.nodes.keys().map(String::as_str).collect()
+    }
+
+    /// Every `(parent, child)` edge in the graph, i.e. `child` depends on `parent`.
+    fn edges(&self) -> HashSet<(&str, &str)> {
+        self.nodes
+            .values()
+            .flat_map(|n| {
+                n.depends_on
+                    .iter()
+                    .map(move |parent| (parent.as_str(), n.unique_id.as_str()))
+            })
+            .collect()
+    }
+
+    /// Nodes with no upstream dependencies, e.g. sources.
+    fn roots(&self) -> HashSet<&str> {
+        self.nodes
+            .values()
+            .filter(|n| n.depends_on.is_empty())
+            .map(|n| n.unique_id.as_str())
+            .collect()
+    }
+
+    /// Nodes nothing else depends on.
+    fn leaves(&self) -> HashSet<&str> {
+        let parents: HashSet<&str> = self.edges().into_iter().map(|(parent, _)| parent).collect();
+        self.node_ids()
+            .into_iter()
+            .filter(|id| !parents.contains(id))
+            .collect()
+    }
+
+    /// Direct children: nodes that list `unique_id` in their `depends_on`.
+    fn direct_children(&self, unique_id: &str) -> Vec<&str> {
+        self.nodes
+            .values()
+            .filter(|n| n.depends_on.iter().any(|d| d == unique_id))
+            .map(|n| n.unique_id.as_str())
+            .collect()
+    }
+
+    /// Direct parents of `unique_id`, i.e. what it lists in `depends_on`.
+    fn direct_parents(&self, unique_id: &str) -> Vec<&str> {
+        self.node(unique_id)
+            .map(|n| n.depends_on.iter().map(|s| s.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Every model `unique_id` transitively depends on, e.g. the sources and
+    /// staging models feeding a mart, so reviewers can judge whether a change
+    /// is safe given its inputs.
+    pub fn find_upstream_dependencies(&self, unique_id: &str) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<&str> = self.direct_parents(unique_id).into_iter().collect();
+        let mut out = Vec::new();
+        while let Some(id) = queue.pop_front() {
+            if seen.insert(id) {
+                out.push(id);
+                queue.extend(self.direct_parents(id));
+            }
+        }
+        out
+    }
+
+    /// Every node reachable downstream of `unique_id`, transitively.
+    pub fn all_downstream(&self, unique_id: &str) -> Vec<&str> {
+        self.all_downstream_with_depth(unique_id)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Every node reachable downstream of `unique_id`, paired with the number
+    /// of edges from `unique_id` to it (1 = direct child).
+    pub fn all_downstream_with_depth(&self, unique_id: &str) -> Vec<(&str, usize)> {
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<(&str, usize)> = self
+            .direct_children(unique_id)
+            .into_iter()
+            .map(|c| (c, 1))
+            .collect();
+        let mut out = Vec::new();
+        while let Some((id, depth)) = queue.pop_front() {
+            if seen.insert(id) {
+                out.push((id, depth));
+                queue.extend(self.direct_children(id).into_iter().map(|c| (c, depth + 1)));
+            }
+        }
+        out
+    }
+
+    /// Computes the set of models impacted by a change to `changed`.
+    ///
+    /// Ephemeral models are inlined into their consumers at compile time and
+    /// never materialize, so a change to one has no intermediate table of its
+    /// own to rebuild: its impact is attributed directly to its immediate
+    /// downstream consumers instead, and flagged as inlined so reports can
+    /// call that out.
+    pub fn analyze_impact(&self, changed: &[String]) -> Vec<Impact> {
+        let mut impacts: HashMap<String, Impact> = HashMap::new();
+
+        for changed_id in changed {
+            let is_ephemeral = self
+                .node(changed_id)
+                .map(|n| n.materialized == Materialization::Ephemeral)
+                .unwrap_or(false);
+
+            if is_ephemeral {
+                for consumer in self.direct_children(changed_id) {
+                    impacts
+                        .entry(consumer.to_string())
+                        .or_insert_with(|| Impact {
+                            unique_id: consumer.to_string(),
+                            inlined_from: Some(changed_id.clone()),
+                            depth: 1,
+                        });
+                    for (downstream, depth) in self.all_downstream_with_depth(consumer) {
+                        impacts
+                            .entry(downstream.to_string())
+                            .or_insert_with(|| Impact {
+                                unique_id: downstream.to_string(),
+                                inlined_from: None,
+                                depth: depth + 1,
+                            });
+                    }
+                }
+            } else {
+                for (downstream, depth) in self.all_downstream_with_depth(changed_id) {
+                    impacts
+                        .entry(downstream.to_string())
+                        .or_insert_with(|| Impact {
+                            unique_id: downstream.to_string(),
+                            inlined_from: None,
+                            depth,
+                        });
+                }
+            }
+        }
+
+        let mut result: Vec<Impact> = impacts.into_values().collect();
+        result.sort_by(|a, b| a.unique_id.cmp(&b.unique_id));
+        result
+    }
+
+    /// How many models directly depend on `unique_id` — its out-degree, aka
+    /// fan-out.
+    pub fn direct_dependent_count(&self, unique_id: &str) -> usize {
+        self.direct_children(unique_id).len()
+    }
+
+    /// Weights the flat downstream count by the worst single point of
+    /// failure among `changed`: a model feeding 100 downstream consumers is
+    /// riskier to change than the raw total-downstream count alone
+    /// suggests, since one mistake there breaks all 100 at once.
+    pub fn calculate_impact_score(&self, changed: &[String]) -> ImpactScore {
+        let total_downstream = self.analyze_impact(changed).len();
+        let highest_fan_out = changed
+            .iter()
+            .map(|id| (id.clone(), self.direct_dependent_count(id)))
+            .filter(|(_, count)| *count > 0)
+            .max_by_key(|(_, count)| *count);
+
+        let fan_out_component = highest_fan_out
+            .as_ref()
+            .map(|(_, count)| *count as f64)
+            .unwrap_or(0.0);
+        let score = total_downstream as f64 + fan_out_component * FAN_OUT_SCORE_WEIGHT;
+
+        ImpactScore {
+            total_downstream,
+            highest_fan_out,
+            score,
+        }
+    }
+
+    /// A ready-to-paste `dbt build` command covering `changed` and every
+    /// model downstream of it, plus the concrete downstream list and which
+    /// of those are incrementals that need `--full-refresh` to pick up an
+    /// upstream schema or logic change rather than just append/merge on top
+    /// of stale history.
+    pub fn rebuild_plan(&self, changed: &[String]) -> RebuildPlan {
+        let name_of = |id: &str| {
+            self.node(id)
+                .map(|n| n.name.clone())
+                .unwrap_or_else(|| id.to_string())
+        };
+
+        let impacts = self.analyze_impact(changed);
+        let downstream_models: Vec<String> =
+            impacts.iter().map(|i| name_of(&i.unique_id)).collect();
+        let incrementals_needing_full_refresh: Vec<String> = impacts
+            .iter()
+            .filter(|i| {
+                self.node(&i.unique_id)
+                    .is_some_and(|n| n.materialized == Materialization::Incremental)
+            })
+            .map(|i| name_of(&i.unique_id))
+            .collect();
+
+        let selector = changed
+            .iter()
+            .map(|id| format!("{}+", name_of(id)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let command = format!("dbt build --select {selector}");
+
+        RebuildPlan {
+            command,
+            downstream_models,
+            incrementals_needing_full_refresh,
+        }
+    }
+
+    /// Builds a `selectors.yml` fragment defining `pr_affected`: the union
+    /// of `changed` and everything downstream of each, using the same
+    /// `model+` syntax [`rebuild_plan`](Self::rebuild_plan)'s `--select`
+    /// string does, so `dbt build --selector pr_affected` targets exactly
+    /// what this PR's own rebuild plan does.
+    pub fn affected_selector(&self, changed: &[String]) -> SelectorsYml {
+        let name_of = |id: &str| {
+            self.node(id)
+                .map(|n| n.name.clone())
+                .unwrap_or_else(|| id.to_string())
+        };
+
+        let union = changed.iter().map(|id| format!("{}+", name_of(id))).collect();
+
+        SelectorsYml {
+            selectors: vec![Selector {
+                name: "pr_affected".to_string(),
+                description: "Models changed in this PR and their downstream dependents."
+                    .to_string(),
+                definition: SelectorDefinition { union },
+            }],
+        }
+    }
+
+    /// The single longest downstream dependency chain among `changed`'s
+    /// impacts: how many rebuild "waves" ripple out from the change, and
+    /// which model sits at the far end of the deepest one. Reuses
+    /// [`analyze_impact`](Self::analyze_impact)'s breadth-first depth rather
+    /// than a separate traversal, so this stays linear in the size of the
+    /// downstream graph even when it's dense.
+    pub fn deepest_impact(&self, changed: &[String]) -> Option<DeepestImpact> {
+        let name_of = |id: &str| {
+            self.node(id)
+                .map(|n| n.name.clone())
+                .unwrap_or_else(|| id.to_string())
+        };
+
+        self.analyze_impact(changed)
+            .into_iter()
+            .max_by_key(|i| i.depth)
+            .map(|i| DeepestImpact {
+                model: name_of(&i.unique_id),
+                depth: i.depth,
+            })
+    }
+
+    /// [`analyze_impact`](Self::analyze_impact) plus, when `include_upstream`
+    /// is set, each changed model's own upstream dependencies — the
+    /// provenance context reviewers need to judge whether a change is safe
+    /// given its inputs, not just what it breaks downstream.
+    pub fn analyze_impact_report(
+        &self,
+        changed: &[String],
+        include_upstream: bool,
+    ) -> ImpactReport {
+        let impacts = self.analyze_impact(changed);
+        let rebuild_plan = self.rebuild_plan(changed);
+        let deepest_impact = self.deepest_impact(changed);
+        let changed: Vec<ChangedModelContext> = changed
+            .iter()
+            .map(|id| {
+                let access = self.node(id).map(|n| n.access).unwrap_or_default();
+                ChangedModelContext {
+                    unique_id: id.clone(),
+                    upstream_dependencies: if include_upstream {
+                        self.find_upstream_dependencies(id)
+                            .into_iter()
+                            .map(String::from)
+                            .collect()
+                    } else {
+                        Vec::new()
+                    },
+                    package_dependencies: self.package_dependencies(id),
+                    access,
+                    risk_escalated: access == Access::Public,
+                }
+            })
+            .collect();
+        let mut affected: HashSet<&str> = changed.iter().map(|c| c.unique_id.as_str()).collect();
+        affected.extend(impacts.iter().map(|i| i.unique_id.as_str()));
+
+        ImpactReport {
+            affected_exposures: self.affected_exposures(&affected),
+            affected_metrics: self.affected_metrics(&affected),
+            impacts,
+            changed,
+            rebuild_plan,
+            deepest_impact,
+        }
+    }
+
+    /// The names of every exposure that depends, directly, on a model in
+    /// `affected` (the changeset plus everything downstream of it) — a
+    /// dashboard or notebook built on a model this changeset touches or
+    /// breaks. Deliberately direct-only, like
+    /// [`protected_models_touched`](Self::protected_models_touched): an
+    /// exposure declares its model dependencies explicitly rather than
+    /// through further transitive lineage, so there's no deeper edge to walk.
+    fn affected_exposures(&self, affected: &HashSet<&str>) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .exposures
+            .iter()
+            .filter(|e| e.depends_on.nodes.iter().any(|n| affected.contains(n.as_str())))
+            .map(|e| e.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// The names of every metric that depends, directly, on a model in
+    /// `affected`, the same way [`affected_exposures`](Self::affected_exposures)
+    /// does for exposures.
+    fn affected_metrics(&self, affected: &HashSet<&str>) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .metrics
+            .iter()
+            .filter(|m| m.depends_on.nodes.iter().any(|n| affected.contains(n.as_str())))
+            .map(|m| m.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Every `private` model in the graph that's referenced by a consumer
+    /// outside its own `group`.
+    pub fn cross_group_violations(&self) -> Vec<CrossGroupViolation> {
+        let mut violations = Vec::new();
+        for node in self.nodes.values() {
+            if node.access != Access::Private {
+                continue;
+            }
+            for consumer in self.direct_children(&node.unique_id) {
+                let same_group = self.node(consumer).is_some_and(|c| c.group == node.group);
+                if !same_group {
+                    violations.push(CrossGroupViolation {
+                        private_model: node.unique_id.clone(),
+                        consumer: consumer.to_string(),
+                    });
+                }
+            }
+        }
+        violations
+            .sort_by(|a, b| (&a.private_model, &a.consumer).cmp(&(&b.private_model, &b.consumer)));
+        violations
+    }
+
+    /// `unique_id`s of protected models (matched by name against
+    /// `protected_patterns`, e.g. `revenue_mart` or `finance_*`) touched
+    /// either directly by `changed` or as a direct (depth-1) downstream
+    /// consumer of one. A protected model further downstream is out of
+    /// scope: extra scrutiny is for models a change lands on immediately,
+    /// not everything transitively reachable.
+    pub fn protected_models_touched(
+        &self,
+        changed: &[String],
+        protected_patterns: &[String],
+    ) -> Vec<String> {
+        let patterns: Vec<Pattern> = protected_patterns
+            .iter()
+            .filter_map(|p| Pattern::new(p).ok())
+            .collect();
+        let is_protected = |unique_id: &str| {
+            self.node(unique_id).is_some_and(|n| {
+                patterns
+                    .iter()
+                    .any(|p| p.matches(&n.name) || p.matches(unique_id))
+            })
+        };
+
+        let impacts = self.analyze_impact(changed);
+        let mut touched: Vec<String> = changed
+            .iter()
+            .filter(|id| is_protected(id))
+            .cloned()
+            .chain(
+                impacts
+                    .iter()
+                    .filter(|i| i.depth <= 1 && is_protected(&i.unique_id))
+                    .map(|i| i.unique_id.clone()),
+            )
+            .collect();
+        touched.sort();
+        touched.dedup();
+        touched
+    }
+
+    /// Column-level impact of changing `changed_columns` on `changed_model`:
+    /// for every model downstream of it, which of its output columns derive
+    /// from one of the changed columns (via [`columns::column_level_impact`]).
+    ///
+    /// This is opt-in and separate from [`ImpactReport`], the same way
+    /// [`cross_group_violations`](Self::cross_group_violations) and
+    /// [`protected_models_touched`](Self::protected_models_touched) are:
+    /// unlike model-level impact, it needs each downstream model's compiled
+    /// SQL, which the graph itself doesn't hold, so `get_sql` is the caller's
+    /// way of supplying it (e.g. reading `target/compiled/...` from disk). A
+    /// downstream model `get_sql` returns `None` for is silently skipped
+    /// rather than failing the whole analysis.
+    pub fn analyze_column_impact(
+        &self,
+        changed_model: &str,
+        changed_columns: &[String],
+        get_sql: &dyn Fn(&str) -> Option<String>,
+    ) -> Vec<columns::ColumnImpact> {
+        let downstream: Vec<(String, String)> = self
+            .all_downstream(changed_model)
+            .into_iter()
+            .filter_map(|unique_id| get_sql(unique_id).map(|sql| (unique_id.to_string(), sql)))
+            .collect();
+        columns::column_level_impact(changed_columns, &downstream)
+    }
+
+    /// True when `unique_id` is referenced as a dependency somewhere in this
+    /// graph but has no first-party [`ModelInfo`] of its own here — almost
+    /// always a node from an installed package (`depends_on` naming a
+    /// `model.<other_package>.x` this project's manifest never resolved a
+    /// node for, since manifest.json only lists nodes dbt actually compiled).
+    pub fn is_external(&self, unique_id: &str) -> bool {
+        !self.nodes.contains_key(unique_id)
+            && self
+                .nodes
+                .values()
+                .any(|n| n.depends_on.iter().any(|d| d == unique_id))
+    }
+
+    /// `unique_id`'s direct dependencies that are [`external`](Self::is_external)
+    /// package models, so impact analysis can report "depends on package
+    /// model X" instead of silently dropping the edge because no first-party
+    /// node exists for it.
+    pub fn package_dependencies(&self, unique_id: &str) -> Vec<String> {
+        let mut deps: Vec<String> = self
+            .direct_parents(unique_id)
+            .into_iter()
+            .filter(|d| self.is_external(d))
+            .map(String::from)
+            .collect();
+        deps.sort();
+        deps
+    }
+
+    /// Loads a previously cached graph from `cache_path` if it was built
+    /// from a manifest with the same `manifest_hash` (see [`hash_manifest`]),
+    /// otherwise builds fresh from `models` and writes the cache for the
+    /// next invocation. A read/write failure just falls back to building
+    /// fresh — a stale or unwritable cache should never fail an analysis
+    /// run, only make it slower.
+    pub fn load_or_build(cache_path: &Path, manifest_hash: u64, models: Vec<ModelInfo>) -> Self {
+        if let Some(cache) = read_cache(cache_path) {
+            if cache.manifest_hash == manifest_hash {
+                return Self::from_models(cache.models);
+            }
+        }
+        let graph = Self::from_models(models);
+        write_cache(
+            cache_path,
+            manifest_hash,
+            graph.nodes.values().cloned().collect(),
+        );
+        graph
+    }
+}
+
+/// Hashes the raw `manifest.json` bytes so a cached graph is never mistaken
+/// for one built from a different manifest.
+pub fn hash_manifest(raw_manifest_json: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    raw_manifest_json.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// On-disk shape of a cached graph. Plain JSON rather than a binary format
+/// since this crate has no binary-serialization dependency and the models
+/// list is already the same order of magnitude as the manifest it came from.
+#[derive(Debug, Serialize, Deserialize)]
+struct LineageCache {
+    manifest_hash: u64,
+    models: Vec<ModelInfo>,
+}
+
+fn read_cache(cache_path: &Path) -> Option<LineageCache> {
+    let contents = std::fs::read_to_string(cache_path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn write_cache(cache_path: &Path, manifest_hash: u64, models: Vec<ModelInfo>) {
+    let cache = LineageCache {
+        manifest_hash,
+        models,
+    };
+    match serde_json::to_string(&cache) {
+        Ok(contents) => {
+            if let Err(e) = std::fs::write(cache_path, contents) {
+                log::warn!(
+                    "failed to write lineage cache to {}: {e}",
+                    cache_path.display()
+                );
+            }
+        }
+        Err(e) => log::warn!("failed to serialize lineage cache: {e}"),
+    }
+}
+
+/// Structural differences between a base and head [`LineageGraph`]: not what
+/// changed inside a model, but whether the DAG itself was rewired.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<String>,
+    pub removed_nodes: Vec<String>,
+    /// `(parent, child)` pairs.
+    pub added_edges: Vec<(String, String)>,
+    pub removed_edges: Vec<(String, String)>,
+    pub new_roots: Vec<String>,
+    pub removed_roots: Vec<String>,
+    pub new_leaves: Vec<String>,
+    pub removed_leaves: Vec<String>,
+}
+
+impl fmt::Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DAG changes: +{} edges, -{} edges, +{} new root(s), +{} new leaf(-ves)",
+            self.added_edges.len(),
+            self.removed_edges.len(),
+            self.new_roots.len(),
+            self.new_leaves.len()
+        )
+    }
+}
+
+fn sorted<'a>(ids: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut ids: Vec<String> = ids.into_iter().map(String::from).collect();
+    ids.sort();
+    ids
+}
+
+/// Diffs `base` against `head`: new/removed nodes, new/removed edges, and
+/// changes to the root/leaf sets. Catches structural rewiring (a model
+/// re-pointed at a different source, a new root added) that per-model diffs
+/// don't surface.
+pub fn compare_graphs(base: &LineageGraph, head: &LineageGraph) -> GraphDiff {
+    let base_nodes = base.node_ids();
+    let head_nodes = head.node_ids();
+    let base_edges = base.edges();
+    let head_edges = head.edges();
+    let base_roots = base.roots();
+    let head_roots = head.roots();
+    let base_leaves = base.leaves();
+    let head_leaves = head.leaves();
+
+    GraphDiff {
+        added_nodes: sorted(head_nodes.difference(&base_nodes).copied()),
+        removed_nodes: sorted(base_nodes.difference(&head_nodes).copied()),
+        added_edges: {
+            let mut edges: Vec<(String, String)> = head_edges
+                .difference(&base_edges)
+                .map(|(p, c)| (p.to_string(), c.to_string()))
+                .collect();
+            edges.sort();
+            edges
+        },
+        removed_edges: {
+            let mut edges: Vec<(String, String)> = base_edges
+                .difference(&head_edges)
+                .map(|(p, c)| (p.to_string(), c.to_string()))
+                .collect();
+            edges.sort();
+            edges
+        },
+        new_roots: sorted(head_roots.difference(&base_roots).copied()),
+        removed_roots: sorted(base_roots.difference(&head_roots).copied()),
+        new_leaves: sorted(head_leaves.difference(&base_leaves).copied()),
+        removed_leaves: sorted(base_leaves.difference(&head_leaves).copied()),
+    }
+}
+
+/// The full impact analysis for a set of changed models: what they break
+/// downstream, plus (optionally) what they themselves depend on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImpactReport {
+    pub impacts: Vec<Impact>,
+    pub changed: Vec<ChangedModelContext>,
+    /// What to run to rebuild everything this change affects (see
+    /// [`LineageGraph::rebuild_plan`]).
+    pub rebuild_plan: RebuildPlan,
+    /// The longest downstream dependency chain among `impacts`, `None` when
+    /// `impacts` is empty (see [`LineageGraph::deepest_impact`]).
+    pub deepest_impact: Option<DeepestImpact>,
+    /// Names of exposures (dashboards, notebooks, ...) that depend, directly
+    /// or transitively, on a changed or impacted model. Empty unless the
+    /// graph was built with [`LineageGraph::with_exposures`].
+    pub affected_exposures: Vec<String>,
+    /// Names of metrics that depend on a changed or impacted model, the same
+    /// way `affected_exposures` does for exposures. Empty unless the graph
+    /// was built with [`LineageGraph::with_metrics`].
+    pub affected_metrics: Vec<String>,
+}
+
+/// A ready-to-paste dbt command to rebuild a changeset and its downstream
+/// consumers, bridging review to deployment. See
+/// [`LineageGraph::rebuild_plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebuildPlan {
+    /// e.g. `"dbt build --select stg_orders+"`.
+    pub command: String,
+    pub downstream_models: Vec<String>,
+    /// The subset of `downstream_models` that are incremental: a plain
+    /// re-run only appends/merges on top of already-materialized rows, so
+    /// these need `--full-refresh` to reflect an upstream schema or logic
+    /// change rather than silently drift from it.
+    pub incrementals_needing_full_refresh: Vec<String>,
+}
+
+/// A dbt `selectors.yml`-compatible document. See
+/// [`LineageGraph::affected_selector`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SelectorsYml {
+    pub selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Selector {
+    pub name: String,
+    pub description: String,
+    pub definition: SelectorDefinition,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SelectorDefinition {
+    pub union: Vec<String>,
+}
+
+impl SelectorsYml {
+    /// Renders this document as YAML, ready to write to a `selectors.yml`
+    /// file (or fragment) dbt can load with `--selector`.
+    pub fn to_yaml(&self) -> String {
+        serde_yaml::to_string(self).expect("SelectorsYml always serializes")
+    }
+
+    /// Writes [`to_yaml`](Self::to_yaml)'s output to `path`.
+    pub fn write_to(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_yaml())
+    }
+}
+
+/// The model furthest from a change, and how many rebuild "waves" away it
+/// is. See [`LineageGraph::deepest_impact`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeepestImpact {
+    pub model: String,
+    pub depth: usize,
+}
+
+impl fmt::Display for DeepestImpact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = if self.depth == 1 { "level" } else { "levels" };
+        write!(f, "deepest impact: {} {level} to {}", self.depth, self.model)
+    }
+}
+
+/// One directly-changed model's upstream provenance context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedModelContext {
+    pub unique_id: String,
+    /// Populated only when `--include-upstream` (or the equivalent caller
+    /// flag) requests it; empty otherwise.
+    pub upstream_dependencies: Vec<String>,
+    /// This model's direct dependencies that are package models this
+    /// project's manifest never resolved a node for (see
+    /// [`LineageGraph::is_external`]). Always populated, since a missing
+    /// package model is invisible to every other check unless it's called
+    /// out explicitly here.
+    pub package_dependencies: Vec<String>,
+    /// This model's dbt `access` modifier.
+    pub access: Access,
+    /// Whether `access` alone should escalate this change to at least high
+    /// risk: a `public` model is relied on by other packages/teams, so a
+    /// change to one is inherently riskier than the same change to a
+    /// `protected`/`private` model.
+    pub risk_escalated: bool,
+}
+
+/// A `private` model referenced by a consumer outside its own dbt `group` —
+/// a contract violation, since `private` restricts a model to consumers
+/// within its own group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossGroupViolation {
+    pub private_model: String,
+    pub consumer: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(id: &str, materialized: Materialization, depends_on: &[&str]) -> ModelInfo {
+        ModelInfo {
+            unique_id: id.to_string(),
+            name: id.to_string(),
+            package_name: "trill_shop".to_string(),
+            materialized,
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            original_file_path: format!("models/{id}.sql"),
+            patch_path: None,
+            owner: None,
+            group: None,
+            access: Access::default(),
+            tags: Vec::new(),
+            meta: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn ephemeral_model_change_is_inlined_into_both_consumers() {
+        let graph = LineageGraph::from_models(vec![
+            model(
+                "model.trill_shop.stg_orders",
+                Materialization::Ephemeral,
+                &[],
+            ),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+            model(
+                "model.trill_shop.orders_by_region",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ]);
+
+        let impacts = graph.analyze_impact(&["model.trill_shop.stg_orders".to_string()]);
+
+        assert_eq!(impacts.len(), 2);
+        assert!(impacts
+            .iter()
+            .all(|i| i.inlined_from.as_deref() == Some("model.trill_shop.stg_orders")));
+        let ids: Vec<&str> = impacts.iter().map(|i| i.unique_id.as_str()).collect();
+        assert!(ids.contains(&"model.trill_shop.orders_summary"));
+        assert!(ids.contains(&"model.trill_shop.orders_by_region"));
+    }
+
+    #[test]
+    fn include_upstream_surfaces_the_changed_models_sources() {
+        let graph = LineageGraph::from_models(vec![
+            model("source.trill_shop.raw_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.stg_orders",
+                Materialization::View,
+                &["source.trill_shop.raw_orders"],
+            ),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ]);
+
+        let report =
+            graph.analyze_impact_report(&["model.trill_shop.orders_summary".to_string()], true);
+
+        assert_eq!(report.changed.len(), 1);
+        assert!(report.changed[0]
+            .upstream_dependencies
+            .contains(&"source.trill_shop.raw_orders".to_string()));
+        assert!(report.changed[0]
+            .upstream_dependencies
+            .contains(&"model.trill_shop.stg_orders".to_string()));
+    }
+
+    fn exposure(name: &str, depends_on: &[&str]) -> ManifestExposure {
+        ManifestExposure {
+            unique_id: format!("exposure.trill_shop.{name}"),
+            name: name.to_string(),
+            exposure_type: Some("dashboard".to_string()),
+            depends_on: crate::artifacts::DependsOn {
+                nodes: depends_on.iter().map(|s| s.to_string()).collect(),
+            },
+            tags: Vec::new(),
+        }
+    }
+
+    fn metric(name: &str, depends_on: &[&str]) -> ManifestMetric {
+        ManifestMetric {
+            unique_id: format!("metric.trill_shop.{name}"),
+            name: name.to_string(),
+            depends_on: crate::artifacts::DependsOn {
+                nodes: depends_on.iter().map(|s| s.to_string()).collect(),
+            },
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn a_dashboard_depending_on_a_downstream_impacted_model_is_reported_as_affected() {
+        let graph = LineageGraph::from_models(vec![
+            model(
+                "model.trill_shop.stg_orders",
+                Materialization::View,
+                &[],
+            ),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ])
+        .with_exposures(vec![
+            exposure("orders_dashboard", &["model.trill_shop.orders_summary"]),
+            exposure("unrelated_dashboard", &["model.trill_shop.stg_orders_unused"]),
+        ])
+        .with_metrics(vec![metric(
+            "weekly_active_users",
+            &["model.trill_shop.orders_summary"],
+        )]);
+
+        let report =
+            graph.analyze_impact_report(&["model.trill_shop.stg_orders".to_string()], false);
+
+        assert_eq!(report.affected_exposures, vec!["orders_dashboard".to_string()]);
+        assert_eq!(
+            report.affected_metrics,
+            vec!["weekly_active_users".to_string()]
+        );
+    }
+
+    #[test]
+    fn a_graph_built_without_exposures_or_metrics_reports_none_affected() {
+        let graph = LineageGraph::from_models(vec![model(
+            "model.trill_shop.stg_orders",
+            Materialization::View,
+            &[],
+        )]);
+
+        let report =
+            graph.analyze_impact_report(&["model.trill_shop.stg_orders".to_string()], false);
+
+        assert!(report.affected_exposures.is_empty());
+        assert!(report.affected_metrics.is_empty());
+    }
+
+    #[test]
+    fn changing_a_public_model_escalates_risk() {
+        let mut orders_summary = model(
+            "model.trill_shop.orders_summary",
+            Materialization::Table,
+            &[],
+        );
+        orders_summary.access = Access::Public;
+        let graph = LineageGraph::from_models(vec![orders_summary]);
+
+        let report =
+            graph.analyze_impact_report(&["model.trill_shop.orders_summary".to_string()], false);
+
+        assert_eq!(report.changed[0].access, Access::Public);
+        assert!(report.changed[0].risk_escalated);
+    }
+
+    #[test]
+    fn protected_model_change_does_not_escalate_risk() {
+        let graph = LineageGraph::from_models(vec![model(
+            "model.trill_shop.a",
+            Materialization::Table,
+            &[],
+        )]);
+
+        let report = graph.analyze_impact_report(&["model.trill_shop.a".to_string()], false);
+
+        assert!(!report.changed[0].risk_escalated);
+    }
+
+    #[test]
+    fn private_model_referenced_outside_its_group_is_a_violation() {
+        let mut private_model = model(
+            "model.trill_shop.internal_staging",
+            Materialization::View,
+            &[],
+        );
+        private_model.access = Access::Private;
+        private_model.group = Some("finance".to_string());
+
+        let mut cross_group_consumer = model(
+            "model.trill_shop.marketing_mart",
+            Materialization::Table,
+            &["model.trill_shop.internal_staging"],
+        );
+        cross_group_consumer.group = Some("marketing".to_string());
+
+        let same_group_consumer = model(
+            "model.trill_shop.finance_mart",
+            Materialization::Table,
+            &["model.trill_shop.internal_staging"],
+        );
+        let mut same_group_consumer = same_group_consumer;
+        same_group_consumer.group = Some("finance".to_string());
+
+        let graph = LineageGraph::from_models(vec![
+            private_model,
+            cross_group_consumer,
+            same_group_consumer,
+        ]);
+
+        let violations = graph.cross_group_violations();
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].consumer, "model.trill_shop.marketing_mart");
+    }
+
+    #[test]
+    fn a_direct_change_to_a_protected_model_is_reported() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.revenue_mart",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ]);
+
+        let touched = graph.protected_models_touched(
+            &["model.trill_shop.revenue_mart".to_string()],
+            &["*.revenue_mart".to_string()],
+        );
+
+        assert_eq!(touched, vec!["model.trill_shop.revenue_mart".to_string()]);
+    }
+
+    #[test]
+    fn a_close_downstream_change_to_a_protected_model_is_reported_but_a_distant_one_is_not() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.revenue_mart",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+            model(
+                "model.trill_shop.revenue_dashboard_export",
+                Materialization::Table,
+                &["model.trill_shop.revenue_mart"],
+            ),
+        ]);
+
+        let touched = graph.protected_models_touched(
+            &["model.trill_shop.stg_orders".to_string()],
+            &["*.revenue_mart".to_string()],
+        );
+
+        assert_eq!(touched, vec!["model.trill_shop.revenue_mart".to_string()]);
+    }
+
+    #[test]
+    fn no_protected_patterns_touches_nothing() {
+        let graph = LineageGraph::from_models(vec![model(
+            "model.trill_shop.stg_orders",
+            Materialization::View,
+            &[],
+        )]);
+
+        assert!(graph
+            .protected_models_touched(&["model.trill_shop.stg_orders".to_string()], &[])
+            .is_empty());
+    }
+
+    #[test]
+    fn a_second_build_with_an_unchanged_manifest_loads_from_cache() {
+        let cache_path = std::env::temp_dir().join(format!(
+            "dbt-pr-agent-lineage-cache-test-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let _ = std::fs::remove_file(&cache_path);
+
+        let models = vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ];
+        let manifest_hash = hash_manifest("{\"nodes\": {}}");
+
+        let fresh = LineageGraph::load_or_build(&cache_path, manifest_hash, models.clone());
+        assert!(
+            cache_path.exists(),
+            "a cache miss should write the cache for next time"
+        );
+
+        // Pass an empty model list on the second call: if this doesn't come
+        // back from cache, the graph would be empty instead of matching.
+        let cached = LineageGraph::load_or_build(&cache_path, manifest_hash, Vec::new());
+
+        assert_eq!(
+            cached
+                .analyze_impact_report(&["model.trill_shop.stg_orders".to_string()], false)
+                .impacts
+                .len(),
+            1
+        );
+        assert_eq!(
+            fresh
+                .analyze_impact_report(&["model.trill_shop.stg_orders".to_string()], false)
+                .impacts
+                .len(),
+            cached
+                .analyze_impact_report(&["model.trill_shop.stg_orders".to_string()], false)
+                .impacts
+                .len()
+        );
+
+        let _ = std::fs::remove_file(&cache_path);
+    }
+
+    #[test]
+    fn a_changed_manifest_hash_invalidates_the_cache() {
+        let cache_path = std::env::temp_dir().join(format!(
+            "dbt-pr-agent-lineage-cache-invalidation-test-{}-{:?}",
+            std::process::id(),
+            std::thread::current().id()
+        ));
+        let _ = std::fs::remove_file(&cache_path);
+
+        let models = vec![model(
+            "model.trill_shop.stg_orders",
+            Materialization::View,
+            &[],
+        )];
+        LineageGraph::load_or_build(&cache_path, hash_manifest("v1"), models);
+
+        let rebuilt = LineageGraph::load_or_build(&cache_path, hash_manifest("v2"), Vec::new());
+        assert!(
+            rebuilt.node("model.trill_shop.stg_orders").is_none(),
+            "a hash mismatch must not reuse the stale cache"
+        );
+
+        let _ = std::fs::remove_file(&cache_path);
+    }
+
+    #[test]
+    fn upstream_dependencies_are_empty_when_not_requested() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.a", Materialization::Table, &[]),
+            model(
+                "model.trill_shop.b",
+                Materialization::Table,
+                &["model.trill_shop.a"],
+            ),
+        ]);
+
+        let report = graph.analyze_impact_report(&["model.trill_shop.b".to_string()], false);
+        assert!(report.changed[0].upstream_dependencies.is_empty());
+    }
+
+    #[test]
+    fn a_dependency_on_an_unresolved_package_model_is_tagged_external_and_survives_as_an_edge() {
+        let graph = LineageGraph::from_models(vec![model(
+            "model.trill_shop.stg_orders",
+            Materialization::View,
+            &["model.dbt_utils.some_macro_generated_model"],
+        )]);
+
+        assert!(graph.is_external("model.dbt_utils.some_macro_generated_model"));
+        assert!(
+            !graph.is_external("model.trill_shop.stg_orders"),
+            "a first-party node is never external"
+        );
+        assert_eq!(
+            graph.package_dependencies("model.trill_shop.stg_orders"),
+            vec!["model.dbt_utils.some_macro_generated_model".to_string()]
+        );
+
+        // The edge survives even though no first-party node exists for the
+        // package model: it still shows up as upstream provenance.
+        let upstream = graph.find_upstream_dependencies("model.trill_shop.stg_orders");
+        assert!(upstream.contains(&"model.dbt_utils.some_macro_generated_model"));
+    }
+
+    #[test]
+    fn changed_model_context_reports_its_package_dependencies() {
+        let graph = LineageGraph::from_models(vec![model(
+            "model.trill_shop.stg_orders",
+            Materialization::View,
+            &["model.dbt_utils.some_macro_generated_model"],
+        )]);
+
+        let report =
+            graph.analyze_impact_report(&["model.trill_shop.stg_orders".to_string()], false);
+
+        assert_eq!(
+            report.changed[0].package_dependencies,
+            vec!["model.dbt_utils.some_macro_generated_model".to_string()]
+        );
+    }
+
+    #[test]
+    fn a_first_party_only_model_has_no_package_dependencies() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ]);
+
+        assert!(graph
+            .package_dependencies("model.trill_shop.orders_summary")
+            .is_empty());
+    }
+
+    #[test]
+    fn an_added_dependency_edge_is_reported() {
+        let base = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model("model.trill_shop.stg_customers", Materialization::View, &[]),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ]);
+        let head = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model("model.trill_shop.stg_customers", Materialization::View, &[]),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &[
+                    "model.trill_shop.stg_orders",
+                    "model.trill_shop.stg_customers",
+                ],
+            ),
+        ]);
+
+        let diff = compare_graphs(&base, &head);
+
+        assert_eq!(
+            diff.added_edges,
+            vec![(
+                "model.trill_shop.stg_customers".to_string(),
+                "model.trill_shop.orders_summary".to_string()
+            )]
+        );
+        assert!(diff.removed_edges.is_empty());
+        assert_eq!(
+            diff.to_string(),
+            "DAG changes: +1 edges, -0 edges, +0 new root(s), +0 new leaf(-ves)"
+        );
+    }
+
+    #[test]
+    fn a_newly_added_node_with_no_deps_is_a_new_root() {
+        let base = LineageGraph::from_models(vec![model(
+            "model.trill_shop.a",
+            Materialization::Table,
+            &[],
+        )]);
+        let head = LineageGraph::from_models(vec![
+            model("model.trill_shop.a", Materialization::Table, &[]),
+            model("model.trill_shop.b", Materialization::View, &[]),
+        ]);
+
+        let diff = compare_graphs(&base, &head);
+
+        assert_eq!(diff.added_nodes, vec!["model.trill_shop.b".to_string()]);
+        assert_eq!(diff.new_roots, vec!["model.trill_shop.b".to_string()]);
+    }
+
+    #[test]
+    fn regular_model_change_is_not_marked_inlined() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.a", Materialization::Table, &[]),
+            model(
+                "model.trill_shop.b",
+                Materialization::Table,
+                &["model.trill_shop.a"],
+            ),
+        ]);
+
+        let impacts = graph.analyze_impact(&["model.trill_shop.a".to_string()]);
+        assert_eq!(impacts.len(), 1);
+        assert_eq!(impacts[0].inlined_from, None);
+    }
+
+    #[test]
+    fn a_high_fan_out_changed_model_scores_higher_than_a_chained_one_with_equal_total_downstream() {
+        // x feeds a, b, c, d directly: fan-out 4, total downstream 4.
+        let high_fan_out = LineageGraph::from_models(vec![
+            model("model.trill_shop.x", Materialization::Table, &[]),
+            model(
+                "model.trill_shop.a",
+                Materialization::Table,
+                &["model.trill_shop.x"],
+            ),
+            model(
+                "model.trill_shop.b",
+                Materialization::Table,
+                &["model.trill_shop.x"],
+            ),
+            model(
+                "model.trill_shop.c",
+                Materialization::Table,
+                &["model.trill_shop.x"],
+            ),
+            model(
+                "model.trill_shop.d",
+                Materialization::Table,
+                &["model.trill_shop.x"],
+            ),
+        ]);
+        // y feeds p, which chains into q, r, s: fan-out 1, total downstream 4.
+        let chained = LineageGraph::from_models(vec![
+            model("model.trill_shop.y", Materialization::Table, &[]),
+            model(
+                "model.trill_shop.p",
+                Materialization::Table,
+                &["model.trill_shop.y"],
+            ),
+            model(
+                "model.trill_shop.q",
+                Materialization::Table,
+                &["model.trill_shop.p"],
+            ),
+            model(
+                "model.trill_shop.r",
+                Materialization::Table,
+                &["model.trill_shop.q"],
+            ),
+            model(
+                "model.trill_shop.s",
+                Materialization::Table,
+                &["model.trill_shop.r"],
+            ),
+        ]);
+
+        let high_fan_out_score =
+            high_fan_out.calculate_impact_score(&["model.trill_shop.x".to_string()]);
+        let chained_score = chained.calculate_impact_score(&["model.trill_shop.y".to_string()]);
+
+        assert_eq!(
+            high_fan_out_score.total_downstream,
+            chained_score.total_downstream
+        );
+        assert!(
+            high_fan_out_score.score > chained_score.score,
+            "equal total downstream count, but the high-fan-out change should score higher"
+        );
+        assert_eq!(
+            high_fan_out_score.highest_fan_out,
+            Some(("model.trill_shop.x".to_string(), 4))
+        );
+        assert!(high_fan_out_score
+            .fan_out_note()
+            .unwrap()
+            .contains("feeds 4 models"));
+    }
+
+    #[test]
+    fn a_changeset_with_no_downstream_consumers_has_no_fan_out_note() {
+        let graph = LineageGraph::from_models(vec![model(
+            "model.trill_shop.a",
+            Materialization::Table,
+            &[],
+        )]);
+
+        let score = graph.calculate_impact_score(&["model.trill_shop.a".to_string()]);
+
+        assert_eq!(score.total_downstream, 0);
+        assert!(score.highest_fan_out.is_none());
+        assert!(score.fan_out_note().is_none());
+    }
+
+    #[test]
+    fn the_rebuild_selector_covers_the_changed_model_and_its_downstreams() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Incremental,
+                &["model.trill_shop.stg_orders"],
+            ),
+            model(
+                "model.trill_shop.orders_by_region",
+                Materialization::Table,
+                &["model.trill_shop.orders_summary"],
+            ),
+        ]);
+
+        let plan = graph.rebuild_plan(&["model.trill_shop.stg_orders".to_string()]);
+
+        assert_eq!(
+            plan.command,
+            "dbt build --select model.trill_shop.stg_orders+"
+        );
+        assert_eq!(
+            plan.downstream_models,
+            vec![
+                "model.trill_shop.orders_by_region".to_string(),
+                "model.trill_shop.orders_summary".to_string()
+            ]
+        );
+        assert_eq!(
+            plan.incrementals_needing_full_refresh,
+            vec!["model.trill_shop.orders_summary".to_string()]
+        );
+    }
+
+    #[test]
+    fn affected_selector_unions_each_changed_model_and_its_downstreams() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Incremental,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ]);
+
+        let selectors = graph.affected_selector(&["model.trill_shop.stg_orders".to_string()]);
+
+        assert_eq!(selectors.selectors.len(), 1);
+        let selector = &selectors.selectors[0];
+        assert_eq!(selector.name, "pr_affected");
+        assert_eq!(
+            selector.definition.union,
+            vec!["model.trill_shop.stg_orders+".to_string()]
+        );
+
+        let yaml = selectors.to_yaml();
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(
+            parsed["selectors"][0]["name"].as_str(),
+            Some("pr_affected")
+        );
+        assert_eq!(
+            parsed["selectors"][0]["definition"]["union"][0].as_str(),
+            Some("model.trill_shop.stg_orders+")
+        );
+    }
+
+    #[test]
+    fn a_changeset_with_no_downstream_incrementals_needs_no_full_refresh_guidance() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ]);
+
+        let plan = graph.rebuild_plan(&["model.trill_shop.stg_orders".to_string()]);
+
+        assert!(plan.incrementals_needing_full_refresh.is_empty());
+    }
+
+    #[test]
+    fn deepest_impact_reports_the_far_end_of_the_longest_downstream_chain() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+            model(
+                "model.trill_shop.orders_by_region",
+                Materialization::Table,
+                &["model.trill_shop.orders_summary"],
+            ),
+            model(
+                "model.trill_shop.rpt_board_metrics",
+                Materialization::Table,
+                &["model.trill_shop.orders_by_region"],
+            ),
+        ]);
+
+        let deepest = graph
+            .deepest_impact(&["model.trill_shop.stg_orders".to_string()])
+            .expect("a changed model with downstream consumers has a deepest impact");
+
+        assert_eq!(deepest.depth, 3);
+        assert_eq!(deepest.model, "model.trill_shop.rpt_board_metrics");
+        assert_eq!(
+            deepest.to_string(),
+            "deepest impact: 3 levels to model.trill_shop.rpt_board_metrics"
+        );
+    }
+
+    #[test]
+    fn deepest_impact_is_none_when_the_changed_model_has_no_downstream_consumers() {
+        let graph = LineageGraph::from_models(vec![model(
+            "model.trill_shop.stg_orders",
+            Materialization::View,
+            &[],
+        )]);
+
+        assert!(graph
+            .deepest_impact(&["model.trill_shop.stg_orders".to_string()])
+            .is_none());
+    }
+
+    #[test]
+    fn analyze_column_impact_flags_a_downstream_column_derived_from_a_changed_column() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ]);
+
+        let get_sql = |unique_id: &str| match unique_id {
+            "model.trill_shop.orders_summary" => Some(
+                "select order_id, status as order_status from stg_orders".to_string(),
+            ),
+            _ => None,
+        };
+
+        let impacts = graph.analyze_column_impact(
+            "model.trill_shop.stg_orders",
+            &["status".to_string()],
+            &get_sql,
+        );
+
+        assert_eq!(impacts.len(), 1);
+        assert_eq!(impacts[0].model, "model.trill_shop.orders_summary");
+        assert_eq!(impacts[0].column, "order_status");
+    }
+
+    #[test]
+    fn analyze_column_impact_skips_downstream_models_with_no_available_sql() {
+        let graph = LineageGraph::from_models(vec![
+            model("model.trill_shop.stg_orders", Materialization::View, &[]),
+            model(
+                "model.trill_shop.orders_summary",
+                Materialization::Table,
+                &["model.trill_shop.stg_orders"],
+            ),
+        ]);
+
+        let impacts = graph.analyze_column_impact(
+            "model.trill_shop.stg_orders",
+            &["status".to_string()],
+            &|_| None,
+        );
+
+        assert!(impacts.is_empty());
+    }
+}
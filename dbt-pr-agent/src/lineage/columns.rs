@@ -0,0 +1,266 @@
+//! Heuristic column-level lineage: which downstream columns derive from a
+//! changed model's columns, so a review can flag "this touches
+//! `orders.status`, which feeds `orders_summary.is_cancelled`" instead of
+//! just "this touches `orders_summary`".
+//!
+//! This crate has no SQL parser dependency (see
+//! [`crate::agents::quality::sql_rules`] for the same constraint applied to
+//! lint rules), so column extraction is a line-oriented heuristic over the
+//! last top-level `SELECT` list — good enough to flag a likely derivation,
+//! not a substitute for a real AST-based lineage tool. It undercounts (a
+//! column referenced only inside a CTE the final `SELECT` doesn't surface is
+//! missed) rather than overcounts, so a caller can trust a hit but shouldn't
+//! treat a miss as proof of no dependency.
+
+/// One column in a model's final output list: its output name, and the raw
+/// expression it was computed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputColumn {
+    pub name: String,
+    pub expression: String,
+}
+
+/// A downstream column found to derive from a changed upstream column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnImpact {
+    pub model: String,
+    pub column: String,
+    pub derives_from_column: String,
+}
+
+/// Extracts the output columns of `sql`'s last top-level `SELECT` (the
+/// model's final result, after any CTEs), splitting its column list on
+/// top-level commas so a function call's arguments aren't mistaken for
+/// separate columns.
+pub fn extract_output_columns(sql: &str) -> Vec<OutputColumn> {
+    let stripped = strip_line_comments(sql);
+    let Some(select_list) = extract_select_list(&stripped) else {
+        return Vec::new();
+    };
+    split_top_level_commas(&select_list)
+        .into_iter()
+        .map(|part| {
+            let expression = part.trim().to_string();
+            OutputColumn {
+                name: output_name(&expression),
+                expression,
+            }
+        })
+        .filter(|c| !c.expression.is_empty())
+        .collect()
+}
+
+/// For each `(model, sql)` pair in `downstream`, flags every output column
+/// whose source expression references one of `changed_columns` by name.
+pub fn column_level_impact(
+    changed_columns: &[String],
+    downstream: &[(String, String)],
+) -> Vec<ColumnImpact> {
+    let mut impacts = Vec::new();
+    for (model, sql) in downstream {
+        for output in extract_output_columns(sql) {
+            for changed in changed_columns {
+                if references_column(&output.expression, changed) {
+                    impacts.push(ColumnImpact {
+                        model: model.clone(),
+                        column: output.name.clone(),
+                        derives_from_column: changed.clone(),
+                    });
+                }
+            }
+        }
+    }
+    impacts
+}
+
+fn strip_line_comments(sql: &str) -> String {
+    sql.lines()
+        .map(|line| line.split("--").next().unwrap_or(""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The text between the last top-level `select` and the `from` that follows
+/// it, i.e. a query's final output column list.
+fn extract_select_list(sql: &str) -> Option<String> {
+    let lower = sql.to_ascii_lowercase();
+    let select_pos = rfind_word(&lower, "select")?;
+    let after_select = select_pos + "select".len();
+    let from_offset = find_word(&lower[after_select..], "from")?;
+    Some(sql[after_select..after_select + from_offset].to_string())
+}
+
+fn split_top_level_commas(list: &str) -> Vec<String> {
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut parts = Vec::new();
+    let mut current = String::new();
+
+    for c in list.chars() {
+        match c {
+            '\'' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.trim().is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// The output name of one SELECT-list expression: the text after a trailing
+/// `AS alias`, or the last `.`-segment of a bare `table.column`/`column`
+/// reference when there's no alias.
+fn output_name(expr: &str) -> String {
+    let trimmed = expr.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    if let Some(pos) = rfind_word(&lower, "as") {
+        return trimmed[pos + 2..]
+            .trim()
+            .trim_matches(|c: char| c == '`' || c == '"')
+            .to_string();
+    }
+    trimmed
+        .rsplit('.')
+        .next()
+        .unwrap_or(trimmed)
+        .trim()
+        .to_string()
+}
+
+fn references_column(expression: &str, column: &str) -> bool {
+    let lower_expr = expression.to_ascii_lowercase();
+    let lower_col = column.to_ascii_lowercase();
+    find_word(&lower_expr, &lower_col).is_some()
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// The byte offset of the first word-boundary-delimited occurrence of
+/// `word` in `haystack` (both assumed already lowercased).
+fn find_word(haystack: &str, word: &str) -> Option<usize> {
+    let bytes = haystack.as_bytes();
+    let wlen = word.len();
+    let mut start = 0;
+    while let Some(rel) = haystack[start..].find(word) {
+        let pos = start + rel;
+        if is_word_boundary_match(bytes, pos, wlen) {
+            return Some(pos);
+        }
+        start = pos + 1;
+    }
+    None
+}
+
+/// Like [`find_word`] but returns the last match instead of the first.
+fn rfind_word(haystack: &str, word: &str) -> Option<usize> {
+    let bytes = haystack.as_bytes();
+    let wlen = word.len();
+    let mut end = haystack.len();
+    while let Some(pos) = haystack[..end].rfind(word) {
+        if is_word_boundary_match(bytes, pos, wlen) {
+            return Some(pos);
+        }
+        if pos == 0 {
+            break;
+        }
+        end = pos;
+    }
+    None
+}
+
+fn is_word_boundary_match(bytes: &[u8], pos: usize, wlen: usize) -> bool {
+    let before_ok = pos == 0 || !is_ident_byte(bytes[pos - 1]);
+    let after_ok = pos + wlen >= bytes.len() || !is_ident_byte(bytes[pos + wlen]);
+    before_ok && after_ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_aliased_and_bare_columns_from_a_simple_select() {
+        let sql = "select order_id, status as order_status from stg_orders";
+
+        let columns = extract_output_columns(sql);
+
+        assert_eq!(
+            columns,
+            vec![
+                OutputColumn {
+                    name: "order_id".to_string(),
+                    expression: "order_id".to_string(),
+                },
+                OutputColumn {
+                    name: "order_status".to_string(),
+                    expression: "status as order_status".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_function_calls_commas_are_not_treated_as_column_separators() {
+        let sql = "select coalesce(discount, 0) as discount from stg_orders";
+
+        let columns = extract_output_columns(sql);
+
+        assert_eq!(columns.len(), 1);
+        assert_eq!(columns[0].name, "discount");
+    }
+
+    #[test]
+    fn uses_the_final_select_after_any_ctes() {
+        let sql = "with base as (select * from stg_orders) \
+                    select order_id, status from base";
+
+        let columns = extract_output_columns(sql);
+
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["order_id", "status"]);
+    }
+
+    #[test]
+    fn line_comments_are_ignored() {
+        let sql = "select order_id, -- an internal id\n status from stg_orders";
+
+        let columns = extract_output_columns(sql);
+
+        assert_eq!(columns.len(), 2);
+    }
+
+    #[test]
+    fn column_level_impact_flags_a_downstream_column_that_references_a_changed_column() {
+        let downstream = vec![(
+            "model.trill_shop.orders_summary".to_string(),
+            "select order_id, case when status = 'cancelled' then true else false end as is_cancelled from stg_orders".to_string(),
+        )];
+
+        let impacts = column_level_impact(&["status".to_string()], &downstream);
+
+        assert_eq!(impacts.len(), 1);
+        assert_eq!(impacts[0].model, "model.trill_shop.orders_summary");
+        assert_eq!(impacts[0].column, "is_cancelled");
+        assert_eq!(impacts[0].derives_from_column, "status");
+    }
+
+    #[test]
+    fn column_level_impact_finds_nothing_when_no_output_column_references_the_changed_column() {
+        let downstream = vec![(
+            "model.trill_shop.orders_summary".to_string(),
+            "select order_id from stg_orders".to_string(),
+        )];
+
+        assert!(column_level_impact(&["status".to_string()], &downstream).is_empty());
+    }
+}
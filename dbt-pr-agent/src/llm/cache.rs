@@ -0,0 +1,303 @@
+//! A content-addressed cache for [`LlmProvider`] responses, so re-running
+//! analysis on a PR whose diff hasn't changed doesn't re-bill tokens for the
+//! same prompts.
+//!
+//! Like [`crate::model_source_cache::ModelSourceCache`], there's no async
+//! runtime here (see [`crate::cancellation`]), so both backends are plain
+//! synchronous stores. Unlike that cache, entries do need a TTL: a model
+//! source file is immutable for the life of one analysis run, but a cached
+//! LLM response can go stale as prompts and provider behavior evolve, so a
+//! long-lived disk cache needs an expiry rather than living forever.
+
+use crate::llm::{LlmError, LlmProvider, LlmRequest, LlmResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Identifies a cached response by the exact request that would produce it:
+/// same model, same messages/tools/sampling settings hashes to the same key,
+/// so a one-character prompt change is a cache miss rather than a stale hit.
+pub fn cache_key(model: &str, request: &LlmRequest) -> String {
+    let mut hasher = DefaultHasher::new();
+    model.hash(&mut hasher);
+    // `LlmRequest` has no `Hash` impl (its `f32` field can't derive one), so
+    // its canonical JSON form is hashed instead; two requests that serialize
+    // the same way are the same request for caching purposes.
+    serde_json::to_string(request)
+        .expect("LlmRequest always serializes")
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// A stored response paired with the instant it stops being valid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    response: LlmResponse,
+    expires_at: SystemTime,
+}
+
+/// Where cached responses live. Implemented by [`InMemoryCache`] (scoped to
+/// one process) and [`DiskCache`] (shared across runs and processes).
+pub trait CacheBackend {
+    /// Returns the cached response for `key` if one exists and hasn't
+    /// expired as of `now`.
+    fn get(&self, key: &str, now: SystemTime) -> Option<LlmResponse>;
+
+    /// Stores `response` under `key`, valid until `now + ttl`.
+    fn put(&self, key: &str, response: LlmResponse, now: SystemTime, ttl: Duration);
+}
+
+/// A process-local cache, cheap to construct and share via `Clone`.
+#[derive(Clone, Default)]
+pub struct InMemoryCache {
+    entries: std::sync::Arc<Mutex<HashMap<String, CacheEntry>>>,
+}
+
+impl InMemoryCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CacheBackend for InMemoryCache {
+    fn get(&self, key: &str, now: SystemTime) -> Option<LlmResponse> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(key)?;
+        if entry.expires_at <= now {
+            return None;
+        }
+        Some(entry.response.clone())
+    }
+
+    fn put(&self, key: &str, response: LlmResponse, now: SystemTime, ttl: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(
+            key.to_string(),
+            CacheEntry {
+                response,
+                expires_at: now + ttl,
+            },
+        );
+    }
+}
+
+/// A cache backed by one JSON file per key under `dir`, so cached responses
+/// survive between CLI invocations. `dir` is created lazily on the first
+/// `put`; a fresh checkout with no cache directory yet is just an
+/// all-misses [`DiskCache`], not an error.
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+impl CacheBackend for DiskCache {
+    fn get(&self, key: &str, now: SystemTime) -> Option<LlmResponse> {
+        let contents = std::fs::read_to_string(self.entry_path(key)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&contents).ok()?;
+        if entry.expires_at <= now {
+            return None;
+        }
+        Some(entry.response)
+    }
+
+    fn put(&self, key: &str, response: LlmResponse, now: SystemTime, ttl: Duration) {
+        let entry = CacheEntry {
+            response,
+            expires_at: now + ttl,
+        };
+        let Ok(contents) = serde_json::to_string(&entry) else {
+            return;
+        };
+        if std::fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+        let _ = std::fs::write(self.entry_path(key), contents);
+    }
+}
+
+/// Wraps an [`LlmProvider`] with a [`CacheBackend`], so identical requests
+/// (same model, same messages/tools/sampling settings) within `ttl` of each
+/// other are served from the cache instead of re-invoking the wrapped
+/// provider.
+///
+/// `enabled` is the `--no-cache` escape hatch: constructing with
+/// `enabled: false` makes every call pass straight through to `inner`
+/// without consulting or populating the backend, for a caller who wants to
+/// force a fresh response (e.g. after changing a prompt template) without
+/// restructuring their provider chain.
+pub struct CachingProvider {
+    inner: Box<dyn LlmProvider>,
+    backend: Box<dyn CacheBackend>,
+    model: String,
+    ttl: Duration,
+    enabled: bool,
+}
+
+impl CachingProvider {
+    pub fn new(
+        inner: Box<dyn LlmProvider>,
+        backend: Box<dyn CacheBackend>,
+        model: impl Into<String>,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            inner,
+            backend,
+            model: model.into(),
+            ttl,
+            enabled: true,
+        }
+    }
+
+    /// Applies the `--no-cache` override: `false` disables both reading and
+    /// writing the cache for the life of this provider.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+}
+
+impl LlmProvider for CachingProvider {
+    fn complete(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+        if !self.enabled {
+            return self.inner.complete(request);
+        }
+
+        let key = cache_key(&self.model, request);
+        let now = SystemTime::now();
+        if let Some(cached) = self.backend.get(&key, now) {
+            log::debug!("LLM cache hit for key {key}");
+            return Ok(cached);
+        }
+
+        let response = self.inner.complete(request)?;
+        self.backend.put(&key, response.clone(), now, self.ttl);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{Message, MockProvider};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn request() -> LlmRequest {
+        LlmRequest {
+            messages: vec![Message::user("is stg_orders missing a unique_key?")],
+            tools: Vec::new(),
+            temperature: 0.0,
+            max_tokens: 100,
+        }
+    }
+
+    fn response(content: &str) -> LlmResponse {
+        LlmResponse {
+            content: Some(content.to_string()),
+            tool_calls: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn identical_requests_for_the_same_model_hash_to_the_same_key() {
+        assert_eq!(cache_key("gpt-4o", &request()), cache_key("gpt-4o", &request()));
+    }
+
+    #[test]
+    fn the_same_request_against_a_different_model_hashes_differently() {
+        assert_ne!(cache_key("gpt-4o", &request()), cache_key("claude", &request()));
+    }
+
+    #[test]
+    fn an_in_memory_cache_miss_falls_through_to_the_provider_and_then_hits() {
+        let provider = MockProvider::new(vec![response("first answer")]);
+        let caching = CachingProvider::new(
+            Box::new(provider),
+            Box::new(InMemoryCache::new()),
+            "gpt-4o",
+            Duration::from_secs(3600),
+        );
+
+        let first = caching.complete(&request()).unwrap();
+        let second = caching.complete(&request()).unwrap();
+
+        assert_eq!(first.content, second.content.clone());
+        assert_eq!(second.content.as_deref(), Some("first answer"));
+    }
+
+    #[test]
+    fn an_expired_in_memory_entry_is_a_miss() {
+        let cache = InMemoryCache::new();
+        let t0 = SystemTime::now();
+        cache.put("k", response("stale"), t0, Duration::from_secs(60));
+
+        assert!(cache.get("k", t0 + Duration::from_secs(30)).is_some());
+        assert!(cache.get("k", t0 + Duration::from_secs(61)).is_none());
+    }
+
+    #[test]
+    fn disabling_the_cache_calls_the_provider_every_time() {
+        let call_count = std::sync::Arc::new(AtomicUsize::new(0));
+        struct CountingProvider {
+            count: std::sync::Arc<AtomicUsize>,
+        }
+        impl LlmProvider for CountingProvider {
+            fn complete(&self, _request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+                self.count.fetch_add(1, Ordering::SeqCst);
+                Ok(response("fresh"))
+            }
+        }
+        let caching = CachingProvider::new(
+            Box::new(CountingProvider {
+                count: call_count.clone(),
+            }),
+            Box::new(InMemoryCache::new()),
+            "gpt-4o",
+            Duration::from_secs(3600),
+        )
+        .with_enabled(false);
+
+        caching.complete(&request()).unwrap();
+        caching.complete(&request()).unwrap();
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn a_disk_cache_round_trips_a_response_through_a_temp_directory() {
+        let dir = std::env::temp_dir().join(format!(
+            "dbt-pr-agent-cache-test-{:016x}",
+            {
+                let mut hasher = DefaultHasher::new();
+                std::process::id().hash(&mut hasher);
+                std::time::SystemTime::now().hash(&mut hasher);
+                hasher.finish()
+            }
+        ));
+        let cache = DiskCache::new(&dir);
+        let now = SystemTime::now();
+
+        assert!(cache.get("missing", now).is_none());
+
+        cache.put("k", response("persisted"), now, Duration::from_secs(60));
+        let hit = cache.get("k", now + Duration::from_secs(1)).unwrap();
+
+        assert_eq!(hit.content.as_deref(), Some("persisted"));
+        assert!(cache.get("k", now + Duration::from_secs(61)).is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,141 @@
+//! Embedding providers for semantic features (e.g. a future similar-model
+//! search), batched to minimize round-trips.
+//!
+//! OpenAI's embeddings endpoint accepts an array of inputs per request, so
+//! [`embed_all`] chunks the input list into `batch_size`-sized requests.
+//! Ollama has no batch endpoint — each input needs its own request — so its
+//! provider instead widens a batch across bounded-concurrency threads via
+//! [`BoundedConcurrencyProvider`], giving both providers the same call shape.
+
+use crate::llm::LlmError;
+
+/// Requests this many inputs per provider call unless the caller overrides
+/// it. Large enough to meaningfully cut round-trips, small enough to stay
+/// well under providers' per-request payload/token limits.
+pub const DEFAULT_EMBEDDING_BATCH_SIZE: usize = 50;
+
+/// A provider that can embed a batch of inputs in one call, returning one
+/// vector per input in the same order.
+pub trait EmbeddingProvider {
+    fn embed_batch(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, LlmError>;
+}
+
+/// Splits `inputs` into chunks of at most `batch_size` and calls
+/// `provider.embed_batch` once per chunk, concatenating the results back
+/// into the original input order. This is the entry point regardless of
+/// whether `provider` natively batches (OpenAI) or fans a batch out across
+/// threads (Ollama, via [`BoundedConcurrencyProvider`]).
+pub fn embed_all(
+    provider: &dyn EmbeddingProvider,
+    inputs: &[String],
+    batch_size: usize,
+) -> Result<Vec<Vec<f32>>, LlmError> {
+    let batch_size = batch_size.max(1);
+    let mut out = Vec::with_capacity(inputs.len());
+    for chunk in inputs.chunks(batch_size) {
+        out.extend(provider.embed_batch(chunk)?);
+    }
+    Ok(out)
+}
+
+/// Adapts a provider that only embeds one input per call into an
+/// [`EmbeddingProvider`], fanning a batch out across real OS threads capped
+/// at `max_concurrent` — the same bounded-concurrency approach
+/// [`crate::bulk::analyze_repo`] uses for PRs.
+pub struct BoundedConcurrencyProvider<F: Fn(&str) -> Result<Vec<f32>, LlmError> + Sync> {
+    embed_one: F,
+    max_concurrent: usize,
+}
+
+impl<F: Fn(&str) -> Result<Vec<f32>, LlmError> + Sync> BoundedConcurrencyProvider<F> {
+    pub fn new(embed_one: F, max_concurrent: usize) -> Self {
+        Self {
+            embed_one,
+            max_concurrent: max_concurrent.max(1),
+        }
+    }
+}
+
+impl<F: Fn(&str) -> Result<Vec<f32>, LlmError> + Sync> EmbeddingProvider
+    for BoundedConcurrencyProvider<F>
+{
+    fn embed_batch(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let mut out = Vec::with_capacity(inputs.len());
+        for chunk in inputs.chunks(self.max_concurrent) {
+            let chunk_results: Vec<Result<Vec<f32>, LlmError>> = std::thread::scope(|scope| {
+                let handles: Vec<_> = chunk
+                    .iter()
+                    .map(|input| scope.spawn(|| (self.embed_one)(input)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|h| h.join().expect("embedding thread panicked"))
+                    .collect()
+            });
+            for result in chunk_results {
+                out.push(result?);
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Scripted OpenAI-style provider: embeds a whole batch in one call and
+    /// counts how many calls it received.
+    struct MockBatchProvider {
+        call_count: AtomicUsize,
+    }
+
+    impl EmbeddingProvider for MockBatchProvider {
+        fn embed_batch(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+            self.call_count.fetch_add(1, Ordering::SeqCst);
+            Ok(inputs
+                .iter()
+                .map(|s| vec![s.parse::<f32>().expect("test inputs are numeric strings")])
+                .collect())
+        }
+    }
+
+    #[test]
+    fn a_hundred_and_twenty_inputs_stay_in_order_across_three_openai_batches() {
+        let provider = MockBatchProvider {
+            call_count: AtomicUsize::new(0),
+        };
+        let inputs: Vec<String> = (0..120).map(|i| i.to_string()).collect();
+
+        let vectors = embed_all(&provider, &inputs, DEFAULT_EMBEDDING_BATCH_SIZE)
+            .expect("embedding should succeed");
+
+        assert_eq!(
+            provider.call_count.load(Ordering::SeqCst),
+            3,
+            "120 inputs at batch size 50 should take 3 requests"
+        );
+        assert_eq!(vectors.len(), 120);
+        for (i, vector) in vectors.iter().enumerate() {
+            assert_eq!(vector, &vec![i as f32], "vector {i} out of order");
+        }
+    }
+
+    #[test]
+    fn bounded_concurrency_provider_preserves_order_for_a_single_item_endpoint() {
+        let provider = BoundedConcurrencyProvider::new(
+            |input: &str| Ok(vec![input.parse::<f32>().unwrap()]),
+            4,
+        );
+        let inputs: Vec<String> = (0..10).map(|i| i.to_string()).collect();
+
+        let vectors = embed_all(&provider, &inputs, DEFAULT_EMBEDDING_BATCH_SIZE)
+            .expect("embedding should succeed");
+
+        assert_eq!(vectors.len(), 10);
+        for (i, vector) in vectors.iter().enumerate() {
+            assert_eq!(vector, &vec![i as f32]);
+        }
+    }
+}
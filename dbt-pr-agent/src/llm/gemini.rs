@@ -0,0 +1,407 @@
+//! A Google Gemini / Vertex AI [`LlmProvider`], for GCP-only shops that can't
+//! or don't want to depend on OpenAI.
+//!
+//! Like [`crate::github::GitHubClient`], this provider only builds request
+//! URLs, headers and JSON bodies and parses JSON responses; it performs no
+//! network I/O of its own (this crate has no HTTP client dependency at all).
+//! Actual requests are issued through the injected [`GeminiTransport`], which
+//! a real binary backs with an HTTP client and tests back with a stub.
+//!
+//! This also means credential minting is out of scope: [`GeminiCredentials::VertexServiceAccount`]
+//! takes an already-issued OAuth access token (e.g. from `gcloud auth
+//! print-access-token` or a service account's own token exchange), the same
+//! way [`crate::github::GitHubClient`] takes an already-issued GitHub token
+//! rather than performing the OAuth dance itself.
+
+use crate::llm::embeddings::EmbeddingProvider;
+use crate::llm::{LlmError, LlmProvider, LlmRequest, LlmResponse, Role};
+use serde_json::{json, Value};
+
+/// How the provider authenticates: the public Generative Language API with a
+/// plain API key, or Vertex AI with a bearer token obtained from a service
+/// account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeminiCredentials {
+    ApiKey(String),
+    VertexServiceAccount {
+        /// An OAuth 2.0 access token already issued for the service account;
+        /// this provider does not mint or refresh tokens itself.
+        access_token: String,
+        project_id: String,
+        /// GCP region hosting the Vertex endpoint, e.g. "us-central1".
+        location: String,
+    },
+}
+
+/// Issues the raw HTTP calls a [`GeminiProvider`] needs. Implemented by a
+/// real HTTP-backed transport in the binary crate that embeds this one, and
+/// by stubs in tests.
+pub trait GeminiTransport {
+    /// `POST`s `request_body` to `url` with `headers` and returns the parsed
+    /// JSON response body, for a `generateContent` call.
+    fn generate_content(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        request_body: &Value,
+    ) -> Result<Value, String>;
+
+    /// `POST`s `request_body` to `url` with `headers` and returns the parsed
+    /// JSON response body, for an `embedContent`/`batchEmbedContents` call.
+    fn embed_content(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        request_body: &Value,
+    ) -> Result<Value, String>;
+}
+
+/// A Gemini/Vertex-backed [`LlmProvider`] and [`EmbeddingProvider`].
+pub struct GeminiProvider {
+    transport: Box<dyn GeminiTransport>,
+    credentials: GeminiCredentials,
+    model: String,
+}
+
+impl GeminiProvider {
+    pub fn new(
+        transport: Box<dyn GeminiTransport>,
+        credentials: GeminiCredentials,
+        model: impl Into<String>,
+    ) -> Self {
+        Self {
+            transport,
+            credentials,
+            model: model.into(),
+        }
+    }
+
+    /// The endpoint URL for `method` ("generateContent" or
+    /// "batchEmbedContents"), which differs between the public API key
+    /// surface and a Vertex-hosted deployment.
+    fn endpoint_url(&self, method: &str) -> String {
+        match &self.credentials {
+            GeminiCredentials::ApiKey(key) => format!(
+                "https://generativelanguage.googleapis.com/v1beta/models/{}:{method}?key={key}",
+                self.model
+            ),
+            GeminiCredentials::VertexServiceAccount {
+                project_id,
+                location,
+                ..
+            } => format!(
+                "https://{location}-aiplatform.googleapis.com/v1/projects/{project_id}/locations/{location}/publishers/google/models/{}:{method}",
+                self.model
+            ),
+        }
+    }
+
+    /// The headers `endpoint_url`'s call needs beyond the transport's own
+    /// `Content-Type`: Vertex authenticates with a bearer token, while the
+    /// API-key surface carries its credential in the URL and needs nothing
+    /// extra here.
+    fn headers(&self) -> Vec<(String, String)> {
+        match &self.credentials {
+            GeminiCredentials::ApiKey(_) => Vec::new(),
+            GeminiCredentials::VertexServiceAccount { access_token, .. } => {
+                vec![("Authorization".to_string(), format!("Bearer {access_token}"))]
+            }
+        }
+    }
+
+    fn to_gemini_role(role: &Role) -> &'static str {
+        match role {
+            // Gemini has no dedicated tool-result role; a tool result is
+            // just further context from the caller's side of the exchange.
+            Role::User | Role::Tool => "user",
+            Role::Assistant => "model",
+            // Folded into `systemInstruction` in `generate_content_body`
+            // instead, since Gemini has no "system" turn in `contents`.
+            Role::System => "system",
+        }
+    }
+}
+
+fn generate_content_body(request: &LlmRequest) -> Value {
+    let system_instruction: Vec<&str> = request
+        .messages
+        .iter()
+        .filter(|m| m.role == Role::System)
+        .map(|m| m.content.as_str())
+        .collect();
+
+    let contents: Vec<Value> = request
+        .messages
+        .iter()
+        .filter(|m| m.role != Role::System)
+        .map(|m| {
+            json!({
+                "role": GeminiProvider::to_gemini_role(&m.role),
+                "parts": [{"text": m.content}],
+            })
+        })
+        .collect();
+
+    let mut body = json!({
+        "contents": contents,
+        "generationConfig": {
+            "temperature": request.temperature,
+            "maxOutputTokens": request.max_tokens,
+        },
+    });
+
+    if !system_instruction.is_empty() {
+        body["systemInstruction"] = json!({
+            "parts": [{"text": system_instruction.join("\n\n")}],
+        });
+    }
+
+    body
+}
+
+/// Extracts the first candidate's concatenated text, mirroring
+/// [`crate::llm::extract_json`]'s tolerance for absent fields: a malformed or
+/// empty response yields an empty [`LlmResponse`] rather than an error, since
+/// the HTTP call itself already succeeded.
+fn parse_generate_content_response(response: &Value) -> LlmResponse {
+    let text = response
+        .get("candidates")
+        .and_then(Value::as_array)
+        .and_then(|candidates| candidates.first())
+        .and_then(|candidate| candidate.get("content"))
+        .and_then(|content| content.get("parts"))
+        .and_then(Value::as_array)
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("")
+        })
+        .filter(|text| !text.is_empty());
+
+    LlmResponse {
+        content: text,
+        tool_calls: Vec::new(),
+    }
+}
+
+impl LlmProvider for GeminiProvider {
+    fn complete(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+        let url = self.endpoint_url("generateContent");
+        let body = generate_content_body(request);
+        let response = self
+            .transport
+            .generate_content(&url, &self.headers(), &body)
+            .map_err(LlmError::Request)?;
+        Ok(parse_generate_content_response(&response))
+    }
+}
+
+impl EmbeddingProvider for GeminiProvider {
+    fn embed_batch(&self, inputs: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let url = self.endpoint_url("batchEmbedContents");
+        let requests: Vec<Value> = inputs
+            .iter()
+            .map(|input| {
+                json!({
+                    "model": format!("models/{}", self.model),
+                    "content": {"parts": [{"text": input}]},
+                })
+            })
+            .collect();
+        let body = json!({ "requests": requests });
+
+        let response = self
+            .transport
+            .embed_content(&url, &self.headers(), &body)
+            .map_err(LlmError::Request)?;
+
+        let embeddings = response
+            .get("embeddings")
+            .and_then(Value::as_array)
+            .ok_or_else(|| {
+                LlmError::Request("Gemini embed response had no 'embeddings' array".to_string())
+            })?;
+
+        embeddings
+            .iter()
+            .map(|embedding| {
+                let values = embedding
+                    .get("values")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| {
+                        LlmError::Request("Gemini embedding had no 'values' array".to_string())
+                    })?;
+                Ok(values.iter().filter_map(Value::as_f64).map(|v| v as f32).collect())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::Message;
+    use std::cell::RefCell;
+
+    struct StubTransport {
+        generate_content_response: Value,
+        embed_content_response: Value,
+        last_generate_content_url: RefCell<Option<String>>,
+        last_generate_content_headers: RefCell<Vec<(String, String)>>,
+    }
+
+    impl StubTransport {
+        fn new(generate_content_response: Value, embed_content_response: Value) -> Self {
+            Self {
+                generate_content_response,
+                embed_content_response,
+                last_generate_content_url: RefCell::new(None),
+                last_generate_content_headers: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl GeminiTransport for StubTransport {
+        fn generate_content(
+            &self,
+            url: &str,
+            headers: &[(String, String)],
+            _request_body: &Value,
+        ) -> Result<Value, String> {
+            *self.last_generate_content_url.borrow_mut() = Some(url.to_string());
+            *self.last_generate_content_headers.borrow_mut() = headers.to_vec();
+            Ok(self.generate_content_response.clone())
+        }
+
+        fn embed_content(
+            &self,
+            _url: &str,
+            _headers: &[(String, String)],
+            _request_body: &Value,
+        ) -> Result<Value, String> {
+            Ok(self.embed_content_response.clone())
+        }
+    }
+
+    fn request(messages: Vec<Message>) -> LlmRequest {
+        LlmRequest {
+            messages,
+            tools: Vec::new(),
+            temperature: 0.2,
+            max_tokens: 512,
+        }
+    }
+
+    #[test]
+    fn api_key_credentials_put_the_key_in_the_url_with_no_authorization_header() {
+        let transport = StubTransport::new(json!({"candidates": []}), json!({}));
+        let provider = GeminiProvider::new(
+            Box::new(transport),
+            GeminiCredentials::ApiKey("test-key".to_string()),
+            "gemini-1.5-pro",
+        );
+
+        provider.complete(&request(vec![Message::user("hi")])).unwrap();
+
+        assert!(provider
+            .endpoint_url("generateContent")
+            .contains("key=test-key"));
+        assert!(provider.headers().is_empty());
+    }
+
+    #[test]
+    fn vertex_credentials_send_a_bearer_token_and_a_project_scoped_url() {
+        let provider = GeminiProvider::new(
+            Box::new(StubTransport::new(json!({"candidates": []}), json!({}))),
+            GeminiCredentials::VertexServiceAccount {
+                access_token: "ya29.abc".to_string(),
+                project_id: "trill-shop".to_string(),
+                location: "us-central1".to_string(),
+            },
+            "gemini-1.5-pro",
+        );
+
+        let url = provider.endpoint_url("generateContent");
+
+        assert!(url.contains("us-central1-aiplatform.googleapis.com"));
+        assert!(url.contains("projects/trill-shop"));
+        assert_eq!(
+            provider.headers(),
+            vec![("Authorization".to_string(), "Bearer ya29.abc".to_string())]
+        );
+    }
+
+    #[test]
+    fn system_messages_are_folded_into_a_system_instruction_not_sent_as_contents() {
+        let body = generate_content_body(&request(vec![
+            Message::system("be terse"),
+            Message::user("hello"),
+        ]));
+
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "be terse");
+        let contents = body["contents"].as_array().unwrap();
+        assert_eq!(contents.len(), 1);
+        assert_eq!(contents[0]["role"], "user");
+    }
+
+    #[test]
+    fn complete_joins_multiple_text_parts_from_the_first_candidate() {
+        let transport = StubTransport::new(
+            json!({
+                "candidates": [{
+                    "content": {"parts": [{"text": "missing "}, {"text": "unique_key"}]}
+                }]
+            }),
+            json!({}),
+        );
+        let provider = GeminiProvider::new(
+            Box::new(transport),
+            GeminiCredentials::ApiKey("k".to_string()),
+            "gemini-1.5-pro",
+        );
+
+        let response = provider.complete(&request(vec![Message::user("hi")])).unwrap();
+
+        assert_eq!(response.content.as_deref(), Some("missing unique_key"));
+    }
+
+    #[test]
+    fn complete_returns_no_content_for_a_response_with_no_candidates() {
+        let transport = StubTransport::new(json!({"candidates": []}), json!({}));
+        let provider = GeminiProvider::new(
+            Box::new(transport),
+            GeminiCredentials::ApiKey("k".to_string()),
+            "gemini-1.5-pro",
+        );
+
+        let response = provider.complete(&request(vec![Message::user("hi")])).unwrap();
+
+        assert_eq!(response.content, None);
+    }
+
+    #[test]
+    fn embed_batch_parses_one_vector_per_input_in_order() {
+        let transport = StubTransport::new(
+            json!({}),
+            json!({
+                "embeddings": [
+                    {"values": [0.1, 0.2]},
+                    {"values": [0.3, 0.4]},
+                ]
+            }),
+        );
+        let provider = GeminiProvider::new(
+            Box::new(transport),
+            GeminiCredentials::ApiKey("k".to_string()),
+            "text-embedding-004",
+        );
+
+        let vectors = provider
+            .embed_batch(&["orders".to_string(), "customers".to_string()])
+            .unwrap();
+
+        assert_eq!(vectors, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+    }
+}
@@ -0,0 +1,524 @@
+//! Provider-agnostic LLM types shared by every agent.
+
+pub mod cache;
+pub mod embeddings;
+pub mod gemini;
+pub mod tokens;
+
+use crate::config::AgentKind;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    Tool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+    /// Set on a `Role::Tool` message: which tool call this is the result of.
+    #[serde(default)]
+    pub tool_call_id: Option<String>,
+}
+
+impl Message {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::System,
+            content: content.into(),
+            tool_call_id: None,
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: content.into(),
+            tool_call_id: None,
+        }
+    }
+
+    pub fn tool_result(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Tool,
+            content: content.into(),
+            tool_call_id: Some(tool_call_id.into()),
+        }
+    }
+}
+
+/// A function an agent exposes for the model to call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    /// JSON schema for the tool's arguments.
+    pub parameters: serde_json::Value,
+}
+
+/// A single invocation of a tool the model requested.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// One turn of a provider's response: either a final answer, or one or more
+/// tool calls the caller must satisfy before the model can continue.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LlmResponse {
+    pub content: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+}
+
+#[derive(Debug, Error)]
+pub enum LlmError {
+    #[error("provider request failed: {0}")]
+    Request(String),
+    #[error("analysis was cancelled")]
+    Cancelled,
+}
+
+/// Everything needed to make one completion call, bundled so per-agent
+/// sampling settings travel with the messages instead of being hard-coded by
+/// each provider implementation.
+#[derive(Debug, Clone, Serialize)]
+pub struct LlmRequest {
+    pub messages: Vec<Message>,
+    pub tools: Vec<ToolSpec>,
+    pub temperature: f32,
+    pub max_tokens: u32,
+}
+
+/// A chat-completion backend. Implemented per provider (OpenAI, Anthropic,
+/// ...) and by [`MockProvider`] in tests.
+pub trait LlmProvider {
+    fn complete(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError>;
+
+    /// Runs [`complete`](Self::complete) and delivers its content to `on_chunk`
+    /// word by word, so a caller can print partial output as it "arrives"
+    /// instead of waiting for the whole response.
+    ///
+    /// This crate has no async runtime and no HTTP client (see
+    /// [`crate::cancellation`] and [`crate::github`]), so there is no
+    /// connection to hold open and read incremental bytes from — a real
+    /// provider would replace this default with actual SSE/chunked decoding
+    /// against its API. Until such a provider exists, this default lets
+    /// every caller be written against streaming output now, with no
+    /// behavior change once a real streaming provider is dropped in.
+    fn stream_complete(
+        &self,
+        request: &LlmRequest,
+        on_chunk: &mut dyn FnMut(&str),
+    ) -> Result<LlmResponse, LlmError> {
+        let response = self.complete(request)?;
+        if let Some(content) = &response.content {
+            for word in content.split_inclusive(' ') {
+                on_chunk(word);
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// A scripted provider for tests: returns each queued response in order,
+/// ignoring the messages/tools it's called with, but records the last
+/// request it received so tests can assert on it.
+pub struct MockProvider {
+    responses: std::cell::RefCell<Vec<LlmResponse>>,
+    last_request: std::cell::RefCell<Option<LlmRequest>>,
+}
+
+impl MockProvider {
+    pub fn new(responses: Vec<LlmResponse>) -> Self {
+        Self {
+            responses: std::cell::RefCell::new(responses),
+            last_request: std::cell::RefCell::new(None),
+        }
+    }
+
+    pub fn last_request(&self) -> Option<LlmRequest> {
+        self.last_request.borrow().clone()
+    }
+}
+
+impl LlmProvider for MockProvider {
+    fn complete(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+        *self.last_request.borrow_mut() = Some(request.clone());
+        let mut responses = self.responses.borrow_mut();
+        if responses.is_empty() {
+            return Err(LlmError::Request(
+                "mock provider ran out of scripted responses".to_string(),
+            ));
+        }
+        Ok(responses.remove(0))
+    }
+}
+
+/// Wraps an ordered list of providers, trying each in turn until one
+/// succeeds. A reliability seam for provider outages or rate limits:
+/// configure a primary and one or more fallbacks, and a failure on the
+/// primary (after its own retries/circuit-breaking, if any) transparently
+/// moves on to the next rather than failing the whole analysis.
+pub struct FallbackProvider {
+    providers: Vec<(String, Box<dyn LlmProvider>)>,
+    last_served_by: std::cell::RefCell<Option<String>>,
+}
+
+impl FallbackProvider {
+    /// `providers` is tried in the order given; the first to return `Ok`
+    /// wins. Each is paired with a name used only for logging and
+    /// [`last_served_by`](Self::last_served_by).
+    pub fn new(providers: Vec<(String, Box<dyn LlmProvider>)>) -> Self {
+        Self {
+            providers,
+            last_served_by: std::cell::RefCell::new(None),
+        }
+    }
+
+    /// The name of the provider that served the last successful [`complete`](LlmProvider::complete)
+    /// call, so a caller can note in the final response which provider was
+    /// actually used. `None` before any call has succeeded.
+    pub fn last_served_by(&self) -> Option<String> {
+        self.last_served_by.borrow().clone()
+    }
+}
+
+impl LlmProvider for FallbackProvider {
+    fn complete(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+        let mut last_error = None;
+        for (name, provider) in &self.providers {
+            match provider.complete(request) {
+                Ok(response) => {
+                    log::info!("LLM request served by provider '{name}'");
+                    *self.last_served_by.borrow_mut() = Some(name.clone());
+                    return Ok(response);
+                }
+                Err(e) => {
+                    log::warn!("provider '{name}' failed, trying next: {e}");
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            LlmError::Request("no providers configured in fallback chain".to_string())
+        }))
+    }
+}
+
+/// Sampling knobs for a single agent's completion calls.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AgentLlmSettings {
+    pub temperature: f32,
+    pub max_tokens: u32,
+}
+
+impl Default for AgentLlmSettings {
+    fn default() -> Self {
+        Self {
+            temperature: 0.3,
+            max_tokens: 4096,
+        }
+    }
+}
+
+/// Extracts and parses the first top-level JSON object from `content`.
+///
+/// Models without a strict JSON mode — local ones via Ollama especially —
+/// routinely wrap their JSON in a ```json fence or prepend a sentence of
+/// explanation, so a plain `serde_json::from_str(&response.content)` fails
+/// and an agent that only tries that silently gets nothing back. This
+/// strips a surrounding fence if present, then scans for the first `{` and
+/// walks forward tracking brace depth (ignoring braces inside string
+/// literals) to find its matching `}`, and parses just that slice.
+///
+/// Every agent that expects a structured response from the model should run
+/// it through this before deserializing, rather than parsing `content`
+/// directly.
+pub fn extract_json<T: serde::de::DeserializeOwned>(content: &str) -> Result<T, LlmError> {
+    let unfenced = strip_code_fence(content);
+    let object = find_first_json_object(unfenced)
+        .ok_or_else(|| LlmError::Request("no JSON object found in LLM response".to_string()))?;
+    serde_json::from_str(object)
+        .map_err(|e| LlmError::Request(format!("failed to parse extracted JSON: {e}")))
+}
+
+/// Strips a leading ` ```json ` / ` ``` ` fence and its closing ` ``` `, if
+/// present. Leaves `content` untouched otherwise, since prose-then-JSON with
+/// no fence is handled by [`find_first_json_object`]'s brace scan instead.
+fn strip_code_fence(content: &str) -> &str {
+    let trimmed = content.trim();
+    let Some(after_open) = trimmed.strip_prefix("```") else {
+        return content;
+    };
+    let after_open = after_open
+        .strip_prefix("json")
+        .unwrap_or(after_open)
+        .trim_start_matches(['\n', '\r']);
+    after_open.rsplit_once("```").map_or(content, |(body, _)| body)
+}
+
+/// Scans `content` for the first `{` and returns the slice up to its
+/// matching `}`, tracking brace depth and skipping over braces that appear
+/// inside string literals (respecting `\"` escapes) so nested objects and
+/// stray braces in string values don't throw off the match.
+fn find_first_json_object(content: &str) -> Option<&str> {
+    let bytes = content.as_bytes();
+    let start = content.find('{')?;
+
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, &b) in bytes.iter().enumerate().skip(start) {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match b {
+            b'"' => in_string = true,
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&content[start..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// The context window of the model these settings are validated against.
+/// Conservative enough to warn well before a provider would reject the
+/// request outright.
+pub const MODEL_CONTEXT_LIMIT_TOKENS: u32 = 128_000;
+
+/// Per-[`AgentKind`] LLM settings, with agent-appropriate defaults: recommendation
+/// phrasing (impact, quality) tolerates the default temperature, while
+/// performance/cost estimation wants near-zero temperature for consistency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmSettings {
+    pub impact: AgentLlmSettings,
+    pub performance: AgentLlmSettings,
+    pub quality: AgentLlmSettings,
+}
+
+impl Default for LlmSettings {
+    fn default() -> Self {
+        Self {
+            impact: AgentLlmSettings::default(),
+            performance: AgentLlmSettings {
+                temperature: 0.0,
+                max_tokens: 2048,
+            },
+            quality: AgentLlmSettings::default(),
+        }
+    }
+}
+
+impl LlmSettings {
+    pub fn for_agent(&self, agent: AgentKind) -> AgentLlmSettings {
+        match agent {
+            AgentKind::Impact => self.impact,
+            AgentKind::Performance => self.performance,
+            AgentKind::Quality => self.quality,
+        }
+    }
+
+    /// Logs a warning for any agent whose configured `max_tokens` exceeds the
+    /// model's context window, so a misconfigured cap fails loudly instead of
+    /// silently truncating every response.
+    pub fn validate(&self) {
+        for (kind, settings) in [
+            (AgentKind::Impact, self.impact),
+            (AgentKind::Performance, self.performance),
+            (AgentKind::Quality, self.quality),
+        ] {
+            if settings.max_tokens > MODEL_CONTEXT_LIMIT_TOKENS {
+                log::warn!(
+                    "{kind:?} agent: max_tokens ({}) exceeds the model's context limit ({MODEL_CONTEXT_LIMIT_TOKENS}); requests will likely be rejected",
+                    settings.max_tokens
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Verdict {
+        finding: String,
+        confidence: f64,
+    }
+
+    #[test]
+    fn clean_json_parses_directly() {
+        let content = r#"{"finding": "missing unique_key", "confidence": 0.9}"#;
+
+        let verdict: Verdict = extract_json(content).unwrap();
+
+        assert_eq!(
+            verdict,
+            Verdict {
+                finding: "missing unique_key".to_string(),
+                confidence: 0.9,
+            }
+        );
+    }
+
+    #[test]
+    fn json_wrapped_in_a_markdown_fence_is_extracted() {
+        let content = "```json\n{\"finding\": \"missing unique_key\", \"confidence\": 0.9}\n```";
+
+        let verdict: Verdict = extract_json(content).unwrap();
+
+        assert_eq!(verdict.finding, "missing unique_key");
+    }
+
+    #[test]
+    fn json_preceded_by_prose_is_extracted() {
+        let content = "Sure, here's my analysis:\n\n{\"finding\": \"missing unique_key\", \"confidence\": 0.9}\n\nLet me know if you need more detail.";
+
+        let verdict: Verdict = extract_json(content).unwrap();
+
+        assert_eq!(verdict.finding, "missing unique_key");
+    }
+
+    #[test]
+    fn a_brace_inside_a_string_value_does_not_confuse_the_object_boundary() {
+        let content = r#"{"finding": "uses { in a comment", "confidence": 0.5}"#;
+
+        let verdict: Verdict = extract_json(content).unwrap();
+
+        assert_eq!(verdict.finding, "uses { in a comment");
+    }
+
+    #[test]
+    fn no_json_object_present_is_an_error() {
+        assert!(extract_json::<Verdict>("no JSON here at all").is_err());
+    }
+
+    #[test]
+    fn falls_back_to_the_second_provider_when_the_first_always_errors() {
+        let primary = MockProvider::new(vec![]);
+        let fallback = MockProvider::new(vec![LlmResponse {
+            content: Some("served by fallback".to_string()),
+            tool_calls: Vec::new(),
+        }]);
+        let provider = FallbackProvider::new(vec![
+            ("primary".to_string(), Box::new(primary)),
+            ("fallback".to_string(), Box::new(fallback)),
+        ]);
+        let request = LlmRequest {
+            messages: vec![Message::user("hello")],
+            tools: Vec::new(),
+            temperature: 0.0,
+            max_tokens: 100,
+        };
+
+        let response = provider.complete(&request).unwrap();
+
+        assert_eq!(response.content.as_deref(), Some("served by fallback"));
+        assert_eq!(provider.last_served_by().as_deref(), Some("fallback"));
+    }
+
+    #[test]
+    fn stream_complete_default_delivers_the_response_word_by_word() {
+        let provider = MockProvider::new(vec![LlmResponse {
+            content: Some("missing unique_key on this model".to_string()),
+            tool_calls: Vec::new(),
+        }]);
+        let request = LlmRequest {
+            messages: vec![Message::user("hello")],
+            tools: Vec::new(),
+            temperature: 0.0,
+            max_tokens: 100,
+        };
+
+        let mut chunks = Vec::new();
+        let response = provider
+            .stream_complete(&request, &mut |chunk| chunks.push(chunk.to_string()))
+            .unwrap();
+
+        assert_eq!(chunks, vec!["missing ", "unique_key ", "on ", "this ", "model"]);
+        assert_eq!(chunks.concat(), response.content.unwrap());
+    }
+
+    #[test]
+    fn stream_complete_default_delivers_no_chunks_for_a_tool_call_only_response() {
+        let provider = MockProvider::new(vec![LlmResponse {
+            content: None,
+            tool_calls: Vec::new(),
+        }]);
+        let request = LlmRequest {
+            messages: vec![Message::user("hello")],
+            tools: Vec::new(),
+            temperature: 0.0,
+            max_tokens: 100,
+        };
+
+        let mut chunks = Vec::new();
+        provider
+            .stream_complete(&request, &mut |chunk| chunks.push(chunk.to_string()))
+            .unwrap();
+
+        assert!(chunks.is_empty());
+    }
+
+    #[test]
+    fn stream_complete_default_propagates_errors_from_complete() {
+        let provider = MockProvider::new(vec![]);
+        let request = LlmRequest {
+            messages: vec![Message::user("hello")],
+            tools: Vec::new(),
+            temperature: 0.0,
+            max_tokens: 100,
+        };
+
+        let mut chunks = Vec::new();
+        assert!(provider
+            .stream_complete(&request, &mut |chunk| chunks.push(chunk.to_string()))
+            .is_err());
+        assert!(chunks.is_empty());
+    }
+
+    #[test]
+    fn returns_the_last_error_when_every_provider_fails() {
+        let primary = MockProvider::new(vec![]);
+        let fallback = MockProvider::new(vec![]);
+        let provider = FallbackProvider::new(vec![
+            ("primary".to_string(), Box::new(primary)),
+            ("fallback".to_string(), Box::new(fallback)),
+        ]);
+        let request = LlmRequest {
+            messages: vec![Message::user("hello")],
+            tools: Vec::new(),
+            temperature: 0.0,
+            max_tokens: 100,
+        };
+
+        assert!(provider.complete(&request).is_err());
+        assert!(provider.last_served_by().is_none());
+    }
+}
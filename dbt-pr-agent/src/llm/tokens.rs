@@ -0,0 +1,187 @@
+//! Token estimation and prompt-budget packing, so a large PR's SQL, diff and
+//! lineage context gets trimmed to fit a model's context window instead of
+//! the provider rejecting the whole request.
+//!
+//! This crate has no tokenizer dependency (no tiktoken, no BPE tables), so
+//! [`estimate_tokens`] uses the same chars-per-token rule of thumb
+//! tokenizer-less tooling commonly relies on: close enough to budget a
+//! prompt, not a substitute for the real count a provider bills on.
+
+/// Rough English/code average; see [`estimate_tokens`].
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Estimates how many tokens `text` will cost, rounding up so a budget check
+/// errs on the side of trimming too much rather than too little.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Truncates `text` to approximately `max_tokens`, cutting on a line
+/// boundary where possible so a truncated SQL file or DOT graph doesn't end
+/// mid-statement, and appending a marker noting how much was cut.
+pub fn truncate_to_token_budget(text: &str, max_tokens: usize) -> String {
+    if estimate_tokens(text) <= max_tokens {
+        return text.to_string();
+    }
+
+    let max_chars = max_tokens * CHARS_PER_TOKEN;
+    let cut_at = text
+        .char_indices()
+        .take_while(|(i, _)| *i < max_chars)
+        .last()
+        .map_or(0, |(i, c)| i + c.len_utf8());
+    let truncated = &text[..cut_at];
+    let boundary = truncated.rfind('\n').unwrap_or(cut_at);
+    let kept = &text[..boundary];
+    let omitted_tokens = estimate_tokens(text) - estimate_tokens(kept);
+
+    format!("{kept}\n… truncated ({omitted_tokens} tokens omitted to fit the prompt budget)")
+}
+
+/// One named piece of prompt context, provided in priority order (most
+/// important first).
+pub struct ContextSection {
+    pub name: String,
+    pub content: String,
+}
+
+impl ContextSection {
+    pub fn new(name: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+}
+
+/// Packs `sections` into a single prompt body under `max_tokens`, filling
+/// the budget in priority order: earlier sections are kept in full, the
+/// first section that would overflow the remaining budget is truncated (via
+/// [`truncate_to_token_budget`]) rather than dropped outright, and anything
+/// after that is omitted entirely. This is how a changed model's SQL (given
+/// first, highest priority) survives intact while a large lineage DOT graph
+/// (given last) is the one that gets cut down to fit.
+pub fn pack_sections(sections: &[ContextSection], max_tokens: usize) -> String {
+    let mut remaining = max_tokens;
+    let mut parts = Vec::new();
+
+    for section in sections {
+        if remaining == 0 {
+            break;
+        }
+        let cost = estimate_tokens(&section.content);
+        let content = if cost <= remaining {
+            section.content.clone()
+        } else {
+            truncate_to_token_budget(&section.content, remaining)
+        };
+        remaining = remaining.saturating_sub(estimate_tokens(&content));
+        parts.push(format!("### {}\n{content}", section.name));
+    }
+
+    parts.join("\n\n")
+}
+
+/// Splits `text` into chunks of at most `max_tokens_per_chunk`, breaking on
+/// line boundaries, for a diff too large for one completion call to be sent
+/// across several instead of being truncated away.
+pub fn chunk_by_tokens(text: &str, max_tokens_per_chunk: usize) -> Vec<String> {
+    let max_tokens_per_chunk = max_tokens_per_chunk.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_tokens = 0;
+
+    for line in text.lines() {
+        let line_tokens = estimate_tokens(line).max(1);
+        if current_tokens + line_tokens > max_tokens_per_chunk && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_tokens = 0;
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+        current_tokens += line_tokens;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_tokens_rounds_up_to_the_nearest_token() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn text_within_budget_is_returned_unchanged() {
+        let text = "select 1";
+        assert_eq!(truncate_to_token_budget(text, 100), text);
+    }
+
+    #[test]
+    fn oversized_text_is_cut_on_a_line_boundary_with_a_marker() {
+        let text = "line one\nline two\nline three\nline four";
+
+        let truncated = truncate_to_token_budget(text, 3);
+
+        assert!(truncated.starts_with("line one"));
+        assert!(truncated.contains("truncated"));
+        assert!(!truncated.contains("line four"));
+    }
+
+    #[test]
+    fn pack_sections_keeps_the_first_section_intact_and_truncates_the_last() {
+        let sections = vec![
+            ContextSection::new("changed model sql", "select * from orders"),
+            ContextSection::new(
+                "lineage dot",
+                "digraph { a -> b; b -> c; c -> d; d -> e; e -> f; }",
+            ),
+        ];
+
+        let packed = pack_sections(&sections, 10);
+
+        assert!(packed.contains("select * from orders"));
+        assert!(packed.contains("### lineage dot"));
+    }
+
+    #[test]
+    fn pack_sections_drops_a_section_entirely_once_the_budget_is_exhausted() {
+        let sections = vec![
+            ContextSection::new("changed model sql", "select * from a_fairly_long_orders_table"),
+            ContextSection::new("lineage dot", "digraph { a -> b; }"),
+        ];
+
+        let packed = pack_sections(&sections, 5);
+
+        assert!(!packed.contains("lineage dot"));
+    }
+
+    #[test]
+    fn chunk_by_tokens_splits_a_large_diff_into_multiple_chunks() {
+        let text = (0..20)
+            .map(|i| format!("+ line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let chunks = chunk_by_tokens(&text, 10);
+
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.join("\n"), text);
+    }
+
+    #[test]
+    fn chunk_by_tokens_returns_one_chunk_when_everything_fits() {
+        let chunks = chunk_by_tokens("select 1", 1000);
+        assert_eq!(chunks, vec!["select 1".to_string()]);
+    }
+}
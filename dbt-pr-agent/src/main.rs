@@ -0,0 +1,1272 @@
+use clap::{Parser, Subcommand};
+use dbt_pr_agent::agents::performance::{analyze_slow_models, parse_run_results};
+use dbt_pr_agent::config::{config_json_schema, FileConfig, RuntimeOptions};
+use dbt_pr_agent::github::{
+    GitHubTransport, IssueComment, RepoAccessResponse, ReviewPayload,
+};
+use dbt_pr_agent::warehouse::Warehouse;
+use dbt_pr_agent::{apply_file_filter, resolve_runtime_options};
+use std::collections::HashMap;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "dbt-pr-agent",
+    about = "Automated PR review agent for dbt projects"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Analyze a pull request and post review comments.
+    Review {
+        /// Owner/repo of the pull request to analyze, e.g. acme/analytics.
+        #[arg(long)]
+        repo: String,
+
+        /// Pull request number to analyze.
+        #[arg(long)]
+        pr: u64,
+
+        /// Base URL of the GitHub API, for GitHub Enterprise Server users.
+        /// Defaults to public GitHub.
+        #[arg(long)]
+        github_url: Option<String>,
+
+        /// Also report each changed model's upstream dependencies (sources,
+        /// staging models) for provenance context, not just downstream impact.
+        #[arg(long)]
+        include_upstream: bool,
+
+        /// Path to a dbt-pr-agent.yml config file. Merged with environment
+        /// variables and CLI flags, in that order of increasing precedence.
+        #[arg(long)]
+        config: Option<String>,
+
+        /// Path to write a compact machine-readable gate verdict as JSON
+        /// (approval_status, overall_risk_level, blocking, failed_gates,
+        /// exit_code), for CI pipelines that want one small file to parse
+        /// instead of the full report. Written even when analysis fails.
+        #[arg(long)]
+        gate_output: Option<String>,
+
+        /// Lines of surrounding SQL to show above and below each finding's
+        /// line in the posted comment. Defaults to
+        /// `RuntimeOptions::diff_context_lines` (3) when unset.
+        #[arg(long)]
+        diff_context: Option<usize>,
+
+        /// Local checkout of the dbt project this PR belongs to. When set,
+        /// the base and head commits are each compiled into a real manifest
+        /// (via `git worktree` + `dbt compile`) and the review runs the
+        /// manifest-aware checks (impact analysis, stale sources, breaking
+        /// changes) against them, not just the diff-only quality checks.
+        /// Compilation failure falls back to diff-only analysis with a
+        /// warning rather than failing the review.
+        #[arg(long)]
+        project_dir: Option<String>,
+
+        /// Path to a `sources.json` freshness-check result (from `dbt source
+        /// freshness`). When set alongside `--project-dir`, changed models
+        /// that depend on a source reported stale are flagged instead of
+        /// silently trusting every source as fresh.
+        #[arg(long)]
+        sources_json: Option<String>,
+
+        /// Warehouse `profile.type` value, e.g. bigquery, snowflake,
+        /// redshift, for config-declared risk rules that reference the
+        /// `warehouse` field (see `RiskRuleContext::warehouse`). Unset rules
+        /// out of any `warehouse == ...` condition rather than guessing.
+        #[arg(long)]
+        warehouse: Option<String>,
+
+        /// Exit with a distinct process exit code per approval status
+        /// (0 = Approved, 1 = ChangesRequested, 2 = Blocked, 3 = analysis
+        /// failed) instead of the default 0-on-success/1-on-error, so a CI
+        /// pipeline can gate merges on the outcome without parsing
+        /// `--gate-output`. See [`dbt_pr_agent::config::GateSummary::process_exit_code`].
+        #[arg(long)]
+        exit_code: bool,
+
+        /// Output format for `--report-output`: `markdown`, `json`, `sarif`,
+        /// or `junit`. See `dbt_pr_agent::report::FormatterRegistry`.
+        #[arg(long, default_value = "markdown")]
+        report_format: String,
+
+        /// Path to write the full recommendation list in `--report-format`,
+        /// for CI systems (e.g. Jenkins, GitLab CI, code-scanning uploads)
+        /// that already parse SARIF or JUnit output natively. Independent of
+        /// `--gate-output`, which is always the compact gate verdict.
+        #[arg(long)]
+        report_output: Option<String>,
+    },
+    /// Validate a dbt-pr-agent.yml config file without running an analysis.
+    ValidateConfig {
+        /// Path to the config file to validate.
+        path: String,
+    },
+    /// Print the JSON Schema for the config file format.
+    ConfigSchema,
+    /// Analyze every open PR in a repo and rank them by risk.
+    AnalyzeRepo {
+        /// Owner/repo to analyze, e.g. acme/analytics.
+        #[arg(long)]
+        repo: String,
+
+        /// Path to a JSON file holding the GitHub "list pull requests" response.
+        #[arg(long)]
+        open_prs: String,
+
+        /// Maximum number of PRs to analyze concurrently.
+        #[arg(long, default_value_t = 4)]
+        max_concurrent: usize,
+
+        /// Also print the full recommendation list for each PR, not just the table.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Find the N slowest models from a prior `dbt run`, independent of any PR.
+    AnalyzePerformance {
+        /// Path to `run_results.json`.
+        #[arg(long)]
+        run_results: String,
+
+        /// Directory of compiled SQL (dbt's `target/compiled/**`), matched to
+        /// models by file stem.
+        #[arg(long)]
+        compiled_dir: String,
+
+        /// Warehouse `profile.type` value, e.g. bigquery, snowflake, redshift.
+        #[arg(long)]
+        warehouse: String,
+
+        /// How many of the slowest models to analyze.
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+
+        /// Path to the JSON-lines execution-history store (see
+        /// `dbt_pr_agent::agents::performance::history::ExecutionHistoryStore`)
+        /// to compare this run's execution times against and ingest them
+        /// into. Defaults to `DEFAULT_HISTORY_PATH` under the current
+        /// directory.
+        #[arg(long)]
+        history_path: Option<String>,
+
+        /// Skip comparing against and ingesting into the execution-history
+        /// store, e.g. for a one-off local run that shouldn't pollute the
+        /// project's real history.
+        #[arg(long)]
+        no_history: bool,
+    },
+    /// Watch `models/` for saves and re-render an impact tree for the saved
+    /// model, for an IDE-like local feedback loop without GitHub.
+    Watch {
+        /// Directory to watch for `.sql` saves, typically `models/`.
+        #[arg(long, default_value = "models")]
+        models_dir: String,
+
+        /// Path to `manifest.json`.
+        #[arg(long)]
+        manifest: String,
+    },
+    /// Print everything the agent knows about a model from artifacts:
+    /// materialization, deps, columns, tags/meta, and execution history.
+    /// Read-only; helps users trust the data the agent operates on.
+    ExplainModel {
+        /// The model's manifest `unique_id`, e.g. model.trill_shop.orders_summary.
+        model_id: String,
+
+        /// Path to `manifest.json`.
+        #[arg(long)]
+        manifest: String,
+
+        /// Path to `catalog.json`, for column counts. Omit if you don't have one.
+        #[arg(long)]
+        catalog: Option<String>,
+
+        /// Path to `run_results.json`, for the model's last execution time.
+        /// Omit if you don't have one.
+        #[arg(long)]
+        run_results: Option<String>,
+    },
+    /// Run the analysis pipeline against a manifest N times and report
+    /// per-phase timings, to catch performance regressions on large
+    /// projects.
+    Benchmark {
+        /// Path to `manifest.json`.
+        #[arg(long)]
+        manifest: String,
+
+        /// Path to `run_results.json`, for the performance phase. Omit to
+        /// skip that phase (reported as a zero timing).
+        #[arg(long)]
+        run_results: Option<String>,
+
+        /// How many times to run the pipeline.
+        #[arg(long, default_value_t = 5)]
+        iterations: usize,
+
+        /// Print the timings as JSON instead of a table, for tracking
+        /// results over time.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare two manifest.json snapshots and list models changed by
+    /// manifest content (config, compiled SQL, dependencies) rather than by
+    /// which files a PR touched — dbt's `state:modified` selector,
+    /// reimplemented against our own manifest shape.
+    CompareManifests {
+        /// Path to the base branch's `manifest.json`.
+        #[arg(long)]
+        base: String,
+
+        /// Path to the head branch's `manifest.json`.
+        #[arg(long)]
+        head: String,
+
+        /// Print the full per-model reasons as JSON instead of a plain list
+        /// of changed unique_ids.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+fn main() -> anyhow::Result<()> {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Review {
+            repo,
+            pr,
+            github_url,
+            include_upstream,
+            config,
+            gate_output,
+            diff_context,
+            project_dir,
+            sources_json,
+            warehouse,
+            exit_code,
+            report_format,
+            report_output,
+        } => review(
+            &repo,
+            pr,
+            github_url.as_deref(),
+            include_upstream,
+            config.as_deref(),
+            ReviewOutputOptions {
+                gate_output: gate_output.as_deref(),
+                diff_context,
+                project_dir: project_dir.as_deref(),
+                sources_json: sources_json.as_deref(),
+                warehouse: warehouse.as_deref(),
+                exit_code,
+                report_format: &report_format,
+                report_output: report_output.as_deref(),
+            },
+        ),
+        Command::ValidateConfig { path } => validate_config(&path),
+        Command::ConfigSchema => {
+            println!("{}", serde_json::to_string_pretty(&config_json_schema())?);
+            Ok(())
+        }
+        Command::AnalyzeRepo {
+            repo,
+            open_prs,
+            max_concurrent,
+            json,
+        } => analyze_repo(&repo, &open_prs, max_concurrent, json),
+        Command::AnalyzePerformance {
+            run_results,
+            compiled_dir,
+            warehouse,
+            top,
+            history_path,
+            no_history,
+        } => analyze_performance(
+            &run_results,
+            &compiled_dir,
+            &warehouse,
+            top,
+            history_path.as_deref(),
+            no_history,
+        ),
+        Command::Watch {
+            models_dir,
+            manifest,
+        } => watch(&models_dir, &manifest),
+        Command::ExplainModel {
+            model_id,
+            manifest,
+            catalog,
+            run_results,
+        } => explain_model(&model_id, &manifest, catalog.as_deref(), run_results.as_deref()),
+        Command::Benchmark {
+            manifest,
+            run_results,
+            iterations,
+            json,
+        } => benchmark(&manifest, run_results.as_deref(), iterations, json),
+        Command::CompareManifests { base, head, json } => compare_manifests(&base, &head, json),
+    }
+}
+
+/// The `--gate-output`/`--diff-context`/`--exit-code` flags, grouped since
+/// [`review`] otherwise takes too many positional arguments.
+struct ReviewOutputOptions<'a> {
+    gate_output: Option<&'a str>,
+    diff_context: Option<usize>,
+    project_dir: Option<&'a str>,
+    sources_json: Option<&'a str>,
+    warehouse: Option<&'a str>,
+    exit_code: bool,
+    report_format: &'a str,
+    report_output: Option<&'a str>,
+}
+
+/// Runs the review, writing `output.gate_output` (if set) as a compact
+/// machine-readable verdict for CI regardless of whether analysis
+/// succeeded, so a pipeline always has one small file to parse.
+///
+/// When `output.exit_code` is set, this exits the process directly via
+/// [`GateSummary::process_exit_code`] rather than returning, so a CI
+/// pipeline can gate merges on the outcome without parsing `--gate-output`.
+fn review(
+    repo: &str,
+    pr_number: u64,
+    github_url: Option<&str>,
+    include_upstream: bool,
+    config_path: Option<&str>,
+    output: ReviewOutputOptions,
+) -> anyhow::Result<()> {
+    let result = run_review(
+        repo,
+        pr_number,
+        github_url,
+        include_upstream,
+        config_path,
+        ReviewAnalysisOptions {
+            diff_context: output.diff_context,
+            project_dir: output.project_dir,
+            sources_json: output.sources_json,
+            warehouse: output.warehouse,
+            report_format: output.report_format,
+            report_output: output.report_output,
+        },
+    );
+
+    let summary = match &result {
+        Ok(summary) => summary.clone(),
+        Err(e) => dbt_pr_agent::config::GateSummary::from_error(e.to_string()),
+    };
+
+    if let Some(gate_output) = output.gate_output {
+        summary.write_to(std::path::Path::new(gate_output))?;
+    }
+
+    if output.exit_code {
+        std::process::exit(summary.process_exit_code());
+    }
+
+    result.map(|_| ())
+}
+
+/// A [`GitHubTransport`] backed by the system `curl` binary via
+/// [`std::process::Command`], rather than a Rust HTTP client dependency —
+/// the library crate has none by design (see `github.rs`'s module doc), so
+/// this binary provides the real transport, the same split
+/// [`dbt_pr_agent::dbt_runner`] draws between "this crate spawns no
+/// subprocesses" and the binary that embeds it.
+struct CurlTransport;
+
+impl CurlTransport {
+    /// Runs `curl -i` against `url` and returns the raw response (status
+    /// line, headers, and body) as text. `-i` (rather than `-w` writing a
+    /// separate status file) keeps this to a single process spawn per call.
+    fn request(
+        &self,
+        url: &str,
+        authorization: &str,
+        method: &str,
+        body: Option<&str>,
+    ) -> Result<String, String> {
+        let mut args = vec![
+            "-sS".to_string(),
+            "-i".to_string(),
+            "-X".to_string(),
+            method.to_string(),
+            "-H".to_string(),
+            format!("Authorization: {authorization}"),
+            "-H".to_string(),
+            "Accept: application/vnd.github+json".to_string(),
+            "-H".to_string(),
+            "User-Agent: dbt-pr-agent".to_string(),
+        ];
+        if let Some(body) = body {
+            args.push("-H".to_string());
+            args.push("Content-Type: application/json".to_string());
+            args.push("--data".to_string());
+            args.push(body.to_string());
+        }
+        args.push(url.to_string());
+
+        let output = std::process::Command::new("curl")
+            .args(&args)
+            .output()
+            .map_err(|e| format!("failed to run curl: {e}"))?;
+        if !output.status.success() {
+            return Err(format!(
+                "curl exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// POSTs a [`dbt_pr_agent::notify::Notifier`] payload to a webhook `url` via
+/// `curl`. Unlike [`CurlTransport::request`] this carries no GitHub-specific
+/// headers (no `Authorization`, no `Accept`) — Slack/Teams incoming webhooks
+/// don't take either.
+fn post_webhook(url: &str, payload: &serde_json::Value) -> Result<(), String> {
+    let body = serde_json::to_string(payload).map_err(|e| format!("serializing payload: {e}"))?;
+    let output = std::process::Command::new("curl")
+        .args([
+            "-sS",
+            "-o",
+            "/dev/null",
+            "-w",
+            "%{http_code}",
+            "-X",
+            "POST",
+            "-H",
+            "Content-Type: application/json",
+            "--data",
+            &body,
+            url,
+        ])
+        .output()
+        .map_err(|e| format!("failed to run curl: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "curl exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    let status: u16 = String::from_utf8_lossy(&output.stdout).trim().parse().unwrap_or(0);
+    if (200..300).contains(&status) {
+        Ok(())
+    } else {
+        Err(format!("POST {url}: HTTP {status}"))
+    }
+}
+
+/// Splits a raw `curl -i` response into its status code, headers (lowercased
+/// keys), and body. Assumes no redirects are followed (none of `curl`'s
+/// calls above pass `-L`), so there's exactly one header block.
+fn parse_http_response(raw: &str) -> (u16, HashMap<String, String>, String) {
+    let (headers_part, body) = raw.split_once("\r\n\r\n").unwrap_or((raw, ""));
+    let mut lines = headers_part.lines();
+    let status = lines
+        .next()
+        .and_then(|status_line| status_line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .unwrap_or(0);
+    let mut headers = HashMap::new();
+    for line in lines {
+        if let Some((key, value)) = line.split_once(':') {
+            headers.insert(key.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    (status, headers, body.to_string())
+}
+
+impl GitHubTransport for CurlTransport {
+    fn post(&self, url: &str, authorization: &str, payload: &ReviewPayload) -> Result<(), String> {
+        let body = serde_json::to_string(payload)
+            .map_err(|e| format!("serializing review payload: {e}"))?;
+        let raw = self.request(url, authorization, "POST", Some(&body))?;
+        let (status, _, response_body) = parse_http_response(&raw);
+        if (200..300).contains(&status) {
+            Ok(())
+        } else {
+            Err(format!("posting review: HTTP {status}: {response_body}"))
+        }
+    }
+
+    fn get_repo_access(&self, url: &str, authorization: &str) -> Result<RepoAccessResponse, String> {
+        let raw = self.request(url, authorization, "GET", None)?;
+        let (status, headers, _) = parse_http_response(&raw);
+        let oauth_scopes = headers
+            .get("x-oauth-scopes")
+            .map(|scopes| {
+                scopes
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(RepoAccessResponse { status, oauth_scopes })
+    }
+
+    fn list_comments(&self, url: &str, authorization: &str) -> Result<Vec<IssueComment>, String> {
+        let raw = self.request(url, authorization, "GET", None)?;
+        let (status, _, body) = parse_http_response(&raw);
+        if !(200..300).contains(&status) {
+            return Err(format!("listing comments: HTTP {status}: {body}"));
+        }
+        let value: serde_json::Value =
+            serde_json::from_str(&body).map_err(|e| format!("parsing comments response: {e}"))?;
+        Ok(dbt_pr_agent::github::parse_issue_comments(&value))
+    }
+
+    fn create_comment(&self, url: &str, authorization: &str, body: &str) -> Result<(), String> {
+        let payload = serde_json::json!({ "body": body }).to_string();
+        let raw = self.request(url, authorization, "POST", Some(&payload))?;
+        let (status, _, response_body) = parse_http_response(&raw);
+        if (200..300).contains(&status) {
+            Ok(())
+        } else {
+            Err(format!("creating comment: HTTP {status}: {response_body}"))
+        }
+    }
+
+    fn update_comment(&self, url: &str, authorization: &str, body: &str) -> Result<(), String> {
+        let payload = serde_json::json!({ "body": body }).to_string();
+        let raw = self.request(url, authorization, "PATCH", Some(&payload))?;
+        let (status, _, response_body) = parse_http_response(&raw);
+        if (200..300).contains(&status) {
+            Ok(())
+        } else {
+            Err(format!("updating comment: HTTP {status}: {response_body}"))
+        }
+    }
+
+    fn get_json(&self, url: &str, authorization: &str) -> Result<serde_json::Value, String> {
+        let raw = self.request(url, authorization, "GET", None)?;
+        let (status, _, body) = parse_http_response(&raw);
+        if !(200..300).contains(&status) {
+            return Err(format!("GET {url}: HTTP {status}: {body}"));
+        }
+        serde_json::from_str(&body).map_err(|e| format!("parsing response from {url}: {e}"))
+    }
+}
+
+/// Real, `std::process::Command`-backed [`dbt_pr_agent::dbt_runner::ProcessRunner`],
+/// the same "transport lives in the binary, tests use a stub" split
+/// [`CurlTransport`] uses for [`GitHubTransport`].
+struct SystemProcessRunner;
+
+impl dbt_pr_agent::dbt_runner::ProcessRunner for SystemProcessRunner {
+    fn run(
+        &self,
+        program: &str,
+        args: &[&str],
+        working_dir: &std::path::Path,
+    ) -> Result<dbt_pr_agent::dbt_runner::CommandOutput, String> {
+        let output = std::process::Command::new(program)
+            .args(args)
+            .current_dir(working_dir)
+            .output()
+            .map_err(|e| format!("failed to run {program}: {e}"))?;
+        Ok(dbt_pr_agent::dbt_runner::CommandOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+/// Compiles `pr`'s head commit (required) and base commit (best-effort) out
+/// of `project_dir` via [`dbt_pr_agent::dbt_runner::compile_ref`], into
+/// temporary worktrees cleaned up before returning either way, and parses
+/// the resulting manifests into a [`dbt_pr_agent::ManifestContext`]. The base
+/// commit failing to compile is logged and skipped rather than failing the
+/// whole call: the head manifest alone is still enough for impact analysis,
+/// just not for the base-diff breaking-change checks.
+fn compile_manifest_context(
+    project_dir: &str,
+    pr: &dbt_pr_agent::github::PRContext,
+) -> anyhow::Result<(dbt_pr_agent::ManifestContext, std::time::SystemTime)> {
+    let runner = SystemProcessRunner;
+    let repo_dir = std::path::Path::new(project_dir);
+
+    let load_manifest =
+        |git_ref: &str, label: &str| -> anyhow::Result<(serde_json::Value, std::time::SystemTime)> {
+            let worktree_dir =
+                std::env::temp_dir().join(format!("dbt-pr-agent-worktree-{label}-{}", pr.number));
+            let manifest_path = dbt_pr_agent::dbt_runner::compile_ref(
+                &runner,
+                repo_dir,
+                git_ref,
+                &worktree_dir,
+                false,
+            )
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+            let generated_at = std::fs::metadata(&manifest_path).and_then(|m| m.modified());
+            let contents = std::fs::read_to_string(&manifest_path);
+            let _ = dbt_pr_agent::dbt_runner::remove_worktree(&runner, repo_dir, &worktree_dir);
+            let manifest = serde_json::from_str(&contents?)?;
+            Ok((manifest, generated_at?))
+        };
+
+    let (head_manifest, head_manifest_generated_at) = load_manifest(&pr.head_sha, "head")?;
+    let mut context = dbt_pr_agent::ManifestContext::from_head_manifest(&head_manifest);
+
+    match load_manifest(&pr.base_sha, "base") {
+        Ok((base_manifest, _)) => context = context.with_base_manifest(&base_manifest),
+        Err(e) => log::warn!("compiling base ref {}: {e} (skipping breaking-change checks that need it)", pr.base_sha),
+    }
+
+    Ok((context, head_manifest_generated_at))
+}
+
+/// The head commit's author timestamp, via `git show -s --format=%ct`, for
+/// [`dbt_pr_agent::orchestrator::stale_artifact_anomaly`] to compare against
+/// the compiled manifest's mtime.
+fn commit_timestamp(
+    runner: &impl dbt_pr_agent::dbt_runner::ProcessRunner,
+    repo_dir: &std::path::Path,
+    git_ref: &str,
+) -> anyhow::Result<std::time::SystemTime> {
+    let output = runner
+        .run("git", &["show", "-s", "--format=%ct", git_ref], repo_dir)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    if !output.success {
+        anyhow::bail!("git show {git_ref} failed: {}", output.stderr.trim());
+    }
+    let seconds: u64 = output
+        .stdout
+        .trim()
+        .parse()
+        .map_err(|e| anyhow::anyhow!("parsing commit timestamp for {git_ref}: {e}"))?;
+    Ok(std::time::UNIX_EPOCH + std::time::Duration::from_secs(seconds))
+}
+
+/// The manifest-related [`run_review`] flags (`--diff-context`,
+/// `--project-dir`, `--sources-json`, `--warehouse`), grouped for the same
+/// reason [`ReviewOutputOptions`] groups its own — too many otherwise-
+/// positional arguments to `run_review`.
+struct ReviewAnalysisOptions<'a> {
+    diff_context: Option<usize>,
+    project_dir: Option<&'a str>,
+    sources_json: Option<&'a str>,
+    warehouse: Option<&'a str>,
+    report_format: &'a str,
+    report_output: Option<&'a str>,
+}
+
+fn run_review(
+    repo: &str,
+    pr_number: u64,
+    github_url: Option<&str>,
+    include_upstream: bool,
+    config_path: Option<&str>,
+    analysis: ReviewAnalysisOptions,
+) -> anyhow::Result<dbt_pr_agent::config::GateSummary> {
+    let (owner, repo) = repo
+        .split_once('/')
+        .ok_or_else(|| anyhow::anyhow!("--repo must be in owner/repo form"))?;
+
+    // Layered config: file < environment < CLI flags, each layer only
+    // overriding what the one below it left unset.
+    let file_layer = match config_path {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path)?;
+            FileConfig::from_yaml(&contents).map_err(|e| anyhow::anyhow!("{path}: {e}"))?
+        }
+        None => FileConfig::default(),
+    };
+    let env_layer = dbt_pr_agent::config::load_from_env();
+    let cli_layer = FileConfig {
+        github_url: github_url.map(str::to_string),
+        diff_context_lines: analysis.diff_context,
+        ..FileConfig::default()
+    };
+    let effective_config = file_layer.merge_with(env_layer).merge_with(cli_layer);
+    log::debug!("effective config: {effective_config:?}");
+
+    let options = effective_config.apply(RuntimeOptions::default());
+
+    let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
+    let client = match effective_config.github_url.as_deref() {
+        Some(base_url) => dbt_pr_agent::github::GitHubClient::with_base_url(token, base_url)
+            .map_err(|e| anyhow::anyhow!(e))?,
+        None => dbt_pr_agent::github::GitHubClient::new(token),
+    };
+    let transport = CurlTransport;
+
+    let mut pr = client
+        .fetch_pr_context(&transport, owner, repo, pr_number)
+        .map_err(|e| anyhow::anyhow!("fetching {owner}/{repo}#{pr_number}: {e}"))?;
+
+    let options = resolve_runtime_options(options, &pr);
+    apply_file_filter(&mut pr, &options);
+    log::info!("running with options: {options:?} (include_upstream={include_upstream})");
+
+    // With `--project-dir`, compile real manifests for the PR's base/head
+    // commits and run the manifest-aware checks (impact analysis, stale
+    // sources, breaking changes) against them; without it, fall back to the
+    // diff-only checks below. Compilation failure is non-fatal — the review
+    // still posts, just without those checks — since a broken dbt project
+    // shouldn't block every review from that repo.
+    let mut manifest = None;
+    let mut artifact_anomaly = None;
+    if let Some(project_dir) = analysis.project_dir {
+        match compile_manifest_context(project_dir, &pr) {
+            Ok((context, manifest_generated_at)) => {
+                let runner = SystemProcessRunner;
+                match commit_timestamp(&runner, std::path::Path::new(project_dir), &pr.head_sha) {
+                    Ok(head_commit_at) => {
+                        artifact_anomaly = dbt_pr_agent::orchestrator::stale_artifact_anomaly(
+                            manifest_generated_at,
+                            head_commit_at,
+                        );
+                    }
+                    Err(e) => log::warn!(
+                        "reading head commit timestamp from {project_dir}: {e} (skipping artifact-freshness check)"
+                    ),
+                }
+                manifest = Some(context);
+            }
+            Err(e) => {
+                log::warn!("compiling manifests from {project_dir}: {e} (falling back to diff-only analysis)");
+            }
+        }
+    }
+    // `require_fresh_artifacts` turns a stale-manifest warning into a hard
+    // failure instead of just another report finding — see
+    // `RuntimeOptions::require_fresh_artifacts`.
+    dbt_pr_agent::orchestrator::enforce_fresh_artifacts(
+        artifact_anomaly.as_ref(),
+        options.require_fresh_artifacts,
+    )
+    .map_err(|e| anyhow::anyhow!(e))?;
+    // Freshness results are optional even with a compiled manifest: without
+    // `--sources-json`, every source is treated as fresh rather than
+    // failing the run for missing data the user never asked to provide.
+    let freshness = analysis.sources_json
+        .map(|path| -> anyhow::Result<_> {
+            let contents = std::fs::read_to_string(path)?;
+            let sources: serde_json::Value = serde_json::from_str(&contents)?;
+            Ok(dbt_pr_agent::artifacts::parse_sources_freshness(&sources))
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    let mut recommendations = match &manifest {
+        Some(manifest) => dbt_pr_agent::analyze_pr_with_manifest(
+            &pr,
+            manifest,
+            include_upstream,
+            &freshness,
+            options.summary_mode_threshold,
+            options.max_parallel_agents,
+        ),
+        None => dbt_pr_agent::analyze_pr(&pr),
+    };
+    recommendations.extend(artifact_anomaly);
+
+    // Org-declared risk rules (`options.risk_rules`) only ever escalate a
+    // severity that's already been computed, so with none configured this is
+    // a no-op and `max_severity` behaves exactly as it did before risk rules
+    // existed. `cost_pct` and `coverage` have no real pipeline yet (see the
+    // `coverage: 1.0` note on `GateSummary::from_result` below), so a rule
+    // that conditions on them evaluates against the same honest placeholders
+    // the gate itself uses rather than fabricated numbers.
+    let max_severity = if options.risk_rules.is_empty() {
+        dbt_pr_agent::report::max_severity(&recommendations)
+    } else {
+        let downstream_models = manifest
+            .as_ref()
+            .map(|m| m.downstream_model_count(&pr.changed_paths().map(String::from).collect::<Vec<_>>(), include_upstream))
+            .unwrap_or(0);
+        let risk_context = dbt_pr_agent::risk_rules::RiskRuleContext {
+            downstream_models,
+            cost_pct: 0.0,
+            coverage: 1.0,
+            warehouse: dbt_pr_agent::warehouse::Warehouse::detect(analysis.warehouse.unwrap_or("")),
+        };
+        let computed = dbt_pr_agent::report::max_severity(&recommendations)
+            .unwrap_or(dbt_pr_agent::severity::Severity::Low);
+        Some(dbt_pr_agent::escalate_with_risk_rules(
+            &mut recommendations,
+            computed,
+            &options.risk_rules,
+            &risk_context,
+        ))
+    };
+    let protected_model_touched = dbt_pr_agent::matches_protected_model(
+        &dbt_pr_agent::changed_model_names(&pr),
+        &options.protected_models,
+    );
+
+    // `--report-output` is a separate CI artifact from the GitHub comment
+    // below: the comment is always markdown (that's what GitHub renders),
+    // while this can be SARIF or JUnit for tooling that consumes those
+    // formats natively (code-scanning uploads, CI test-result parsers).
+    if let Some(report_output) = analysis.report_output {
+        let formatted = dbt_pr_agent::report::FormatterRegistry::with_defaults()
+            .format(
+                analysis.report_format,
+                &recommendations,
+                &dbt_pr_agent::redact::default_patterns(),
+            )
+            .map_err(|e| anyhow::anyhow!(e))?;
+        std::fs::write(report_output, formatted)?;
+    }
+
+    let report_body = dbt_pr_agent::report::render_report_with_appendix(
+        recommendations,
+        options.min_finding_confidence,
+        &dbt_pr_agent::redact::default_patterns(),
+        dbt_pr_agent::report::DEFAULT_MAX_DISPLAYED_RECOMMENDATIONS,
+    );
+    client
+        .update_or_replace_comment(&transport, &pr, "review", &report_body)
+        .map_err(|e| anyhow::anyhow!("posting review comment: {e}"))?;
+
+    // Routes each changed model's impact finding to its owner's channel
+    // (see `RuntimeOptions::notify`), in addition to the summary comment
+    // above. A webhook failure is logged and skipped rather than failing
+    // the review — a broken Slack/Teams integration shouldn't block a PR.
+    if let (Some(manifest), Some(notify_config)) = (&manifest, &options.notify) {
+        let changed_paths: Vec<String> = pr.changed_paths().map(String::from).collect();
+        let findings = manifest.owned_findings(&changed_paths);
+        let notifier = notify_config.notifier();
+        for routed in dbt_pr_agent::notify::route_by_owner(&findings, &notify_config.routing) {
+            let payload = notifier.render(&routed);
+            if let Err(e) = post_webhook(&routed.webhook, &payload) {
+                log::warn!("notifying {}: {e}", routed.webhook);
+            }
+        }
+    }
+
+    // Line-anchored review comments, in addition to the summary comment
+    // above: SQL lint findings can be pinned to the exact changed line via
+    // GitHub's review API, which reads better inline than in a single big
+    // markdown block.
+    let lint_issues = dbt_pr_agent::lint_pr(
+        &pr,
+        &dbt_pr_agent::agents::quality::sql_rules::SqlLintConfig::default(),
+    );
+    if !lint_issues.is_empty() {
+        let review_payload = dbt_pr_agent::github::build_review(
+            &pr,
+            &lint_issues,
+            "",
+            options.diff_context_lines,
+            &|path| manifest.as_ref().and_then(|m| m.compiled_code_for(path)),
+        );
+        if !review_payload.comments.is_empty() || !review_payload.general_comments.is_empty() {
+            client
+                .post_review(&transport, &pr, &review_payload)
+                .map_err(|e| anyhow::anyhow!("posting line-anchored review: {e}"))?;
+        }
+    }
+
+    // `coverage` and `estimated_cost_increase_dollars` still have no real
+    // data source (no test-coverage or cost-estimation pipeline is wired up
+    // yet), so they're reported as "gate doesn't apply" rather than faked;
+    // `max_severity`, `protected_model_touched`, and `is_draft` are now the
+    // real values observed above.
+    Ok(dbt_pr_agent::config::GateSummary::from_result(
+        &options,
+        max_severity,
+        1.0,
+        protected_model_touched,
+        pr.is_draft,
+        None,
+    ))
+}
+
+/// Runs bulk analysis over every open PR in `repo`: each PR's changed files
+/// are fetched via [`dbt_pr_agent::github::GitHubClient::fetch_pr_context`]
+/// and run through [`dbt_pr_agent::analyze_pr`], the same diff-only quality
+/// checks a single [`run_review`] performs, so the ranking reflects real
+/// findings instead of an empty placeholder. A PR whose context can't be
+/// fetched is logged and analyzed with no findings rather than failing the
+/// whole batch.
+fn analyze_repo(
+    repo: &str,
+    open_prs_path: &str,
+    max_concurrent: usize,
+    json: bool,
+) -> anyhow::Result<()> {
+    let (owner, repo_name) = repo
+        .split_once('/')
+        .ok_or_else(|| anyhow::anyhow!("--repo must be in owner/repo form"))?;
+
+    let contents = std::fs::read_to_string(open_prs_path)?;
+    let response: serde_json::Value = serde_json::from_str(&contents)?;
+    let prs = dbt_pr_agent::github::parse_open_prs(&response);
+
+    let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
+    let client = dbt_pr_agent::github::GitHubClient::new(token);
+    let transport = CurlTransport;
+
+    let summaries = dbt_pr_agent::bulk::analyze_repo(&prs, max_concurrent, |pr| {
+        match client.fetch_pr_context(&transport, owner, repo_name, pr.number) {
+            Ok(context) => dbt_pr_agent::analyze_pr(&context),
+            Err(e) => {
+                log::warn!("PR #{}: failed to fetch context, skipping analysis: {e}", pr.number);
+                Vec::new()
+            }
+        }
+    });
+
+    println!("{}", dbt_pr_agent::bulk::render_table(&summaries));
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(
+                &summaries
+                    .iter()
+                    .map(|s| &s.recommendations)
+                    .collect::<Vec<_>>()
+            )?
+        );
+    }
+
+    Ok(())
+}
+
+fn analyze_performance(
+    run_results_path: &str,
+    compiled_dir: &str,
+    warehouse: &str,
+    top: usize,
+    history_path: Option<&str>,
+    no_history: bool,
+) -> anyhow::Result<()> {
+    let contents = std::fs::read_to_string(run_results_path)?;
+    let run_results: serde_json::Value = serde_json::from_str(&contents)?;
+    let executions = parse_run_results(&run_results);
+
+    let mut sql_by_model = HashMap::new();
+    let compiled_dir_path = std::path::Path::new(compiled_dir);
+    let glob_pattern = format!("{compiled_dir}/**/*.sql");
+    for entry in glob::glob(&glob_pattern)?.flatten() {
+        let Some(stem) = entry.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if let Some(execution) = executions
+            .iter()
+            .find(|e| e.unique_id.ends_with(&format!(".{stem}")))
+        {
+            let Some(relative_path) = entry
+                .strip_prefix(compiled_dir_path)
+                .ok()
+                .and_then(|p| p.to_str())
+            else {
+                continue;
+            };
+            match dbt_pr_agent::artifacts::read_model_file(
+                compiled_dir_path,
+                relative_path,
+                dbt_pr_agent::artifacts::DEFAULT_MAX_MODEL_FILE_BYTES,
+            ) {
+                Ok(sql) => {
+                    sql_by_model.insert(execution.unique_id.clone(), sql);
+                }
+                Err(e) => log::warn!("skipping compiled SQL for {}: {e}", execution.unique_id),
+            }
+        }
+    }
+
+    let warehouse = Warehouse::detect(warehouse);
+    let mut recommendations = analyze_slow_models(&executions, &sql_by_model, &warehouse, top);
+
+    // Compares this run against every model's historical baseline, not just
+    // the `top` slowest in this run — a model can regress badly without
+    // being the single slowest model in the project. Ingested after
+    // comparing, so this run's own numbers become part of future baselines
+    // without skewing the comparison against itself.
+    if !no_history {
+        let store = dbt_pr_agent::agents::performance::history::ExecutionHistoryStore::new(
+            history_path.unwrap_or(dbt_pr_agent::agents::performance::history::DEFAULT_HISTORY_PATH),
+        );
+        recommendations.extend(
+            dbt_pr_agent::agents::performance::history::detect_regressions(
+                &store,
+                &executions,
+                &warehouse,
+                dbt_pr_agent::agents::performance::history::DEFAULT_REGRESSION_THRESHOLD,
+            )?,
+        );
+        store.ingest(&executions, std::time::SystemTime::now())?;
+    }
+
+    println!("{}", serde_json::to_string_pretty(&recommendations)?);
+
+    Ok(())
+}
+
+/// Polls `models_dir` for `.sql` saves and re-renders that model's impact
+/// tree, for an IDE-like local feedback loop without GitHub.
+///
+/// A real implementation would use an OS-level watcher (the `notify` crate)
+/// to get push notifications instead of polling mtimes; that's future work
+/// (see [`dbt_pr_agent::watch`]'s module doc). This polls on a short
+/// interval and feeds every observed mtime change through the same
+/// [`dbt_pr_agent::watch::SaveWatcher`] debounce policy a real watcher would
+/// use, so swapping in `notify` later only changes how events arrive, not
+/// how they're coalesced.
+fn watch(models_dir: &str, manifest_path: &str) -> anyhow::Result<()> {
+    let contents = std::fs::read_to_string(manifest_path)?;
+    let manifest: serde_json::Value = serde_json::from_str(&contents)?;
+    let nodes = dbt_pr_agent::artifacts::parse_manifest_nodes(&manifest);
+    let models = dbt_pr_agent::artifacts::manifest_nodes_to_model_infos(&nodes);
+    let graph = dbt_pr_agent::lineage::LineageGraph::from_models(models.clone());
+    let formatter = dbt_pr_agent::render::TextFormatter { max_depth: 5 };
+
+    let watcher = dbt_pr_agent::watch::SaveWatcher::new(std::time::Duration::from_millis(300));
+    let mut last_modified: HashMap<std::path::PathBuf, std::time::SystemTime> = HashMap::new();
+
+    log::info!("watching {models_dir} for saves (Ctrl-C to stop)");
+    loop {
+        let glob_pattern = format!("{models_dir}/**/*.sql");
+        for entry in glob::glob(&glob_pattern)?.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let Ok(modified) = metadata.modified() else {
+                continue;
+            };
+            if last_modified.get(&entry) != Some(&modified) {
+                last_modified.insert(entry.clone(), modified);
+                watcher.record_save(entry, std::time::Instant::now());
+            }
+        }
+
+        for path in watcher.take_settled(std::time::Instant::now()) {
+            let Some(model) = models
+                .iter()
+                .find(|m| std::path::Path::new(&m.original_file_path) == path)
+            else {
+                continue;
+            };
+            println!("{}", formatter.render_impact_tree(&graph, &model.unique_id));
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    }
+}
+
+/// Prints [`dbt_pr_agent::render::explain_model`]'s report for `model_id`,
+/// reading `catalog`/`run_results` when given for column and execution-time
+/// data; both are optional since not every project has run `dbt docs
+/// generate` or a prior `dbt run` handy.
+fn explain_model(
+    model_id: &str,
+    manifest_path: &str,
+    catalog_path: Option<&str>,
+    run_results_path: Option<&str>,
+) -> anyhow::Result<()> {
+    let contents = std::fs::read_to_string(manifest_path)?;
+    let manifest: serde_json::Value = serde_json::from_str(&contents)?;
+    let nodes = dbt_pr_agent::artifacts::parse_manifest_nodes(&manifest);
+    let models = dbt_pr_agent::artifacts::manifest_nodes_to_model_infos(&nodes);
+    let graph = dbt_pr_agent::lineage::LineageGraph::from_models(models);
+
+    let model = graph
+        .node(model_id)
+        .ok_or_else(|| anyhow::anyhow!("no model with unique_id '{model_id}' in the manifest"))?;
+
+    let catalog_node = catalog_path
+        .map(std::fs::read_to_string)
+        .transpose()?
+        .map(|contents| serde_json::from_str(&contents))
+        .transpose()?
+        .map(|catalog: serde_json::Value| dbt_pr_agent::artifacts::parse_catalog_nodes(&catalog))
+        .and_then(|nodes| nodes.into_iter().find(|n| n.unique_id == model_id));
+
+    let execution = run_results_path
+        .map(std::fs::read_to_string)
+        .transpose()?
+        .map(|contents| serde_json::from_str(&contents))
+        .transpose()?
+        .map(|run_results: serde_json::Value| {
+            dbt_pr_agent::agents::performance::parse_run_results(&run_results)
+        })
+        .and_then(|executions| executions.into_iter().find(|e| e.unique_id == model_id));
+
+    println!(
+        "{}",
+        dbt_pr_agent::render::explain_model(model, &graph, catalog_node.as_ref(), execution.as_ref())
+    );
+
+    Ok(())
+}
+
+/// Loads `base_path` and `head_path` as manifests and prints the models
+/// [`dbt_pr_agent::state::compare_manifests`] considers changed.
+fn compare_manifests(base_path: &str, head_path: &str, json: bool) -> anyhow::Result<()> {
+    let load = |path: &str| -> anyhow::Result<Vec<dbt_pr_agent::artifacts::ManifestNode>> {
+        let contents = std::fs::read_to_string(path)?;
+        let manifest: serde_json::Value = serde_json::from_str(&contents)?;
+        Ok(dbt_pr_agent::artifacts::parse_manifest_nodes(&manifest))
+    };
+
+    let base = load(base_path)?;
+    let head = load(head_path)?;
+    let changes = dbt_pr_agent::state::compare_manifests(&base, &head);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&changes)?);
+    } else if changes.is_empty() {
+        println!("no models changed between {base_path} and {head_path}");
+    } else {
+        for change in &changes {
+            let reasons: Vec<String> = change
+                .reasons
+                .iter()
+                .map(|r| format!("{r:?}"))
+                .collect();
+            println!("{} ({})", change.unique_id, reasons.join(", "));
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs the analysis pipeline against `manifest_path` `iterations` times and
+/// reports mean/p95 timing for each phase (manifest load, graph build,
+/// impact traversal, quality, performance, report synthesis), to catch
+/// performance regressions on large projects.
+///
+/// There's no live PR here (this reads local artifacts only, like
+/// [`explain_model`]), so "impact traversal" and "quality" run over every
+/// model in the manifest rather than a PR's changed set — the point is to
+/// profile the pipeline's cost against the project's full size, not to
+/// review anything.
+fn benchmark(
+    manifest_path: &str,
+    run_results_path: Option<&str>,
+    iterations: usize,
+    json: bool,
+) -> anyhow::Result<()> {
+    use dbt_pr_agent::benchmark::{time_phase, BenchmarkReport, Phase};
+
+    if iterations == 0 {
+        anyhow::bail!("--iterations must be at least 1");
+    }
+
+    let raw_manifest = std::fs::read_to_string(manifest_path)?;
+    let raw_run_results = run_results_path.map(std::fs::read_to_string).transpose()?;
+
+    let mut samples_by_phase: HashMap<Phase, Vec<std::time::Duration>> = HashMap::new();
+
+    for _ in 0..iterations {
+        let (nodes, manifest_load) = time_phase(|| {
+            let manifest: serde_json::Value = serde_json::from_str(&raw_manifest)?;
+            Ok::<_, anyhow::Error>(dbt_pr_agent::artifacts::parse_manifest_nodes(&manifest))
+        });
+        let nodes = nodes?;
+        samples_by_phase
+            .entry(Phase::ManifestLoad)
+            .or_default()
+            .push(manifest_load);
+
+        let models = dbt_pr_agent::artifacts::manifest_nodes_to_model_infos(&nodes);
+        let all_ids: Vec<String> = models.iter().map(|m| m.unique_id.clone()).collect();
+        let (graph, graph_build) =
+            time_phase(|| dbt_pr_agent::lineage::LineageGraph::from_models(models));
+        samples_by_phase
+            .entry(Phase::GraphBuild)
+            .or_default()
+            .push(graph_build);
+
+        let (_, impact_traversal) = time_phase(|| graph.analyze_impact(&all_ids));
+        samples_by_phase
+            .entry(Phase::ImpactTraversal)
+            .or_default()
+            .push(impact_traversal);
+
+        let (quality_recommendations, quality) = time_phase(|| {
+            nodes
+                .iter()
+                .filter_map(|n| n.compiled_code.as_deref().map(|sql| (n, sql)))
+                .filter_map(|(n, sql)| {
+                    dbt_pr_agent::agents::quality::detect_select_star_propagation(
+                        &n.unique_id,
+                        sql,
+                        &[],
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+        samples_by_phase.entry(Phase::Quality).or_default().push(quality);
+
+        let (performance_recommendations, performance) = time_phase(|| match &raw_run_results {
+            Some(raw) => {
+                let run_results: serde_json::Value = serde_json::from_str(raw)?;
+                let executions =
+                    dbt_pr_agent::agents::performance::parse_run_results(&run_results);
+                let warehouse = dbt_pr_agent::warehouse::Warehouse::detect("");
+                Ok::<_, anyhow::Error>(dbt_pr_agent::agents::performance::analyze_slow_models(
+                    &executions,
+                    &HashMap::new(),
+                    &warehouse,
+                    10,
+                ))
+            }
+            None => Ok(Vec::new()),
+        });
+        let performance_recommendations = performance_recommendations?;
+        samples_by_phase
+            .entry(Phase::Performance)
+            .or_default()
+            .push(performance);
+
+        let (_, report_synthesis) = time_phase(|| {
+            let quality_markdown = dbt_pr_agent::report::render_markdown(
+                &dbt_pr_agent::report::dedupe_recommendations(quality_recommendations.clone()),
+                &dbt_pr_agent::redact::default_patterns(),
+            );
+            let performance_markdown = performance_recommendations
+                .iter()
+                .map(|r| format!("- {r:?}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("{quality_markdown}\n{performance_markdown}")
+        });
+        samples_by_phase
+            .entry(Phase::ReportSynthesis)
+            .or_default()
+            .push(report_synthesis);
+    }
+
+    let report = BenchmarkReport::from_samples(
+        iterations,
+        Phase::ALL
+            .into_iter()
+            .map(|phase| (phase, samples_by_phase.remove(&phase).unwrap_or_default()))
+            .collect(),
+    );
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report.to_table());
+    }
+
+    Ok(())
+}
+
+fn validate_config(path: &str) -> anyhow::Result<()> {
+    let contents = std::fs::read_to_string(path)?;
+    let config = FileConfig::from_yaml(&contents).map_err(|e| anyhow::anyhow!("{path}: {e}"))?;
+    config
+        .validate()
+        .map_err(|e| anyhow::anyhow!("{path}: {e}"))?;
+    println!("{path}: OK");
+    Ok(())
+}
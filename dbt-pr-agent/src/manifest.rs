@@ -0,0 +1,175 @@
+//! Minimal representation of the pieces of `manifest.json` the agents need.
+//!
+//! Full typed deserialization of the dbt manifest artifact is tracked as a
+//! later request; for now we only pull out the fields lineage analysis
+//! depends on.
+
+use crate::project::{classify_path, PathLayer, ProjectPaths};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// How a model is materialized in the warehouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Materialization {
+    View,
+    Table,
+    Incremental,
+    Ephemeral,
+    Seed,
+    Snapshot,
+}
+
+/// A model's dbt `access` modifier, controlling who may reference it.
+/// `protected` (referenceable within its own project, not across packages)
+/// is dbt's default when a model declares no `access`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    Public,
+    #[default]
+    Protected,
+    Private,
+}
+
+/// A single node (model, seed, or snapshot) from the dbt manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub unique_id: String,
+    pub name: String,
+    pub package_name: String,
+    pub materialized: Materialization,
+    /// `unique_id`s of the nodes this model selects from.
+    pub depends_on: Vec<String>,
+    /// Repo-relative path to the model's `.sql` file.
+    pub original_file_path: String,
+    /// Repo-relative path to the `schema.yml` that documents/tests this
+    /// model, when one patched it (dbt sets this when a node has YAML
+    /// config alongside its SQL).
+    #[serde(default)]
+    pub patch_path: Option<String>,
+    /// `meta.owner`, falling back to the dbt `group` name, when set.
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// The dbt `groups` name this model belongs to, if any.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Who may reference this model: `public` models are relied on by other
+    /// packages/teams, so changing one is inherently riskier.
+    #[serde(default)]
+    pub access: Access,
+    /// `config.tags` from the manifest node.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// `config.meta` from the manifest node: arbitrary team-declared metadata
+    /// (`owner`, `criticality`, `pii`, ...) that criticality/ownership/PII
+    /// checks build on.
+    #[serde(default)]
+    pub meta: HashMap<String, Value>,
+}
+
+/// Maps a PR's changed file paths to the `unique_id`s of the models they
+/// touch.
+///
+/// A `.sql` change maps by `original_file_path`. A `schema.yml` change maps
+/// by `patch_path`, since dbt records tests/docs edits there rather than
+/// against the model's own file — otherwise a schema.yml-only PR (adding a
+/// test, say) would map to no models and impact analysis would see nothing.
+pub fn discover_changed_models(models: &[ModelInfo], changed_files: &[String]) -> Vec<String> {
+    let mut matched: Vec<String> = models
+        .iter()
+        .filter(|m| {
+            changed_files.iter().any(|f| f == &m.original_file_path)
+                || m.patch_path
+                    .as_ref()
+                    .is_some_and(|p| changed_files.contains(p))
+        })
+        .map(|m| m.unique_id.clone())
+        .collect();
+    matched.sort();
+    matched.dedup();
+    matched
+}
+
+/// Changed `.sql` files under a configured model path (per `project_paths`)
+/// that [`discover_changed_models`] couldn't map to any manifest node —
+/// almost always a model added in this PR, which a base-branch-generated
+/// manifest has no entry for and so would otherwise get zero analysis.
+/// These still deserve review, just without lineage (see
+/// [`crate::agents::quality::analyze_new_model_file`]).
+pub fn discover_new_model_files(
+    models: &[ModelInfo],
+    changed_files: &[String],
+    project_paths: &ProjectPaths,
+) -> Vec<String> {
+    let matched_paths: HashSet<&str> =
+        models.iter().map(|m| m.original_file_path.as_str()).collect();
+
+    changed_files
+        .iter()
+        .filter(|f| f.ends_with(".sql"))
+        .filter(|f| classify_path(project_paths, f) == PathLayer::Model)
+        .filter(|f| !matched_paths.contains(f.as_str()))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_yml_only_change_maps_to_the_model_it_patches() {
+        let models = vec![ModelInfo {
+            unique_id: "model.trill_shop.stg_orders".to_string(),
+            name: "stg_orders".to_string(),
+            package_name: "trill_shop".to_string(),
+            materialized: Materialization::View,
+            depends_on: vec![],
+            original_file_path: "models/staging/stg_orders.sql".to_string(),
+            patch_path: Some("models/staging/stg_orders.yml".to_string()),
+            owner: None,
+            group: None,
+            access: Access::default(),
+            tags: Vec::new(),
+            meta: HashMap::new(),
+        }];
+
+        let changed = vec!["models/staging/stg_orders.yml".to_string()];
+        let matched = discover_changed_models(&models, &changed);
+
+        assert_eq!(matched, vec!["model.trill_shop.stg_orders".to_string()]);
+    }
+
+    #[test]
+    fn a_brand_new_model_file_under_a_model_path_is_reported_as_unmatched() {
+        let models = vec![ModelInfo {
+            unique_id: "model.trill_shop.stg_orders".to_string(),
+            name: "stg_orders".to_string(),
+            package_name: "trill_shop".to_string(),
+            materialized: Materialization::View,
+            depends_on: vec![],
+            original_file_path: "models/staging/stg_orders.sql".to_string(),
+            patch_path: None,
+            owner: None,
+            group: None,
+            access: Access::default(),
+            tags: Vec::new(),
+            meta: HashMap::new(),
+        }];
+        let changed = vec![
+            "models/staging/stg_orders.sql".to_string(),
+            "models/staging/stg_new_model.sql".to_string(),
+            "macros/cents_to_dollars.sql".to_string(),
+        ];
+
+        let new_files =
+            discover_new_model_files(&models, &changed, &ProjectPaths::default());
+
+        assert_eq!(
+            new_files,
+            vec!["models/staging/stg_new_model.sql".to_string()]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! A model source cache shared across agents within one analysis run, so
+//! impact/quality/performance don't each independently re-read the same
+//! model file from disk when building LLM context.
+//!
+//! There's no async runtime in this crate (see [`crate::cancellation`]), so
+//! this is a plain `Mutex<HashMap>` behind an `Arc` rather than an async
+//! once-per-key cache — [`read_model_file`]'s disk IO is synchronous, and a
+//! lock held for its duration is not a bottleneck at PR-sized model counts.
+//! There's no TTL or explicit invalidation: a cache is scoped to a single
+//! analysis run by construction — build a fresh [`ModelSourceCache`] per PR
+//! analysis (and share it via `Clone`, which is a cheap `Arc` bump) rather
+//! than reusing one across runs.
+
+use crate::artifacts::read_model_file;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Error)]
+#[error("{0}")]
+pub struct ModelSourceCacheError(String);
+
+type CachedRead = Result<Arc<str>, ModelSourceCacheError>;
+
+/// Reads each model's source file at most once per analysis, no matter how
+/// many agents (or threads — see [`crate::orchestrator::run_detailed_or_summary_concurrent`])
+/// ask for it. `Clone` shares the same underlying cache.
+#[derive(Clone, Default)]
+pub struct ModelSourceCache {
+    inner: Arc<Mutex<HashMap<String, CachedRead>>>,
+}
+
+impl ModelSourceCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `relative_path`'s contents, reading it from disk under
+    /// `project_dir` only on the first request for that path; every later
+    /// call (from any agent or thread holding a clone of this cache)
+    /// returns the cached result without touching disk again. Errors are
+    /// cached too, so a missing or oversized file isn't retried on every
+    /// agent's request.
+    pub fn get(
+        &self,
+        project_dir: &Path,
+        relative_path: &str,
+        max_bytes: u64,
+    ) -> CachedRead {
+        self.get_with(relative_path, || {
+            read_model_file(project_dir, relative_path, max_bytes)
+                .map_err(|e| ModelSourceCacheError(e.to_string()))
+        })
+    }
+
+    /// The lookup-or-populate logic behind [`get`](Self::get), with the
+    /// actual read supplied by the caller instead of always hitting disk —
+    /// a seam so tests can assert a given path is only ever read once
+    /// without touching the filesystem.
+    fn get_with(
+        &self,
+        relative_path: &str,
+        read: impl FnOnce() -> Result<String, ModelSourceCacheError>,
+    ) -> CachedRead {
+        let mut cache = self.inner.lock().unwrap();
+        if let Some(cached) = cache.get(relative_path) {
+            return cached.clone();
+        }
+        let result = read().map(Arc::<str>::from);
+        cache.insert(relative_path.to_string(), result.clone());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn a_path_requested_by_two_agents_is_only_read_once() {
+        let cache = ModelSourceCache::new();
+        let reads = AtomicUsize::new(0);
+        let read = || {
+            reads.fetch_add(1, Ordering::SeqCst);
+            Ok("select 1".to_string())
+        };
+
+        let impact_agent_read = cache.get_with("models/stg_orders.sql", read);
+        let quality_agent_read = cache.get_with("models/stg_orders.sql", read);
+
+        assert_eq!(impact_agent_read.unwrap().as_ref(), "select 1");
+        assert_eq!(quality_agent_read.unwrap().as_ref(), "select 1");
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn a_cached_error_is_also_not_retried() {
+        let cache = ModelSourceCache::new();
+        let reads = AtomicUsize::new(0);
+        let read = || {
+            reads.fetch_add(1, Ordering::SeqCst);
+            Err(ModelSourceCacheError("file not found".to_string()))
+        };
+
+        assert!(cache.get_with("models/missing.sql", read).is_err());
+        assert!(cache.get_with("models/missing.sql", read).is_err());
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn distinct_paths_are_read_independently() {
+        let cache = ModelSourceCache::new();
+        let reads = AtomicUsize::new(0);
+        let read = || {
+            reads.fetch_add(1, Ordering::SeqCst);
+            Ok("select 1".to_string())
+        };
+
+        cache.get_with("models/a.sql", read).unwrap();
+        cache.get_with("models/b.sql", read).unwrap();
+
+        assert_eq!(reads.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn cloning_the_cache_shares_the_same_underlying_reads() {
+        let cache = ModelSourceCache::new();
+        let shared = cache.clone();
+        let reads = AtomicUsize::new(0);
+        let read = || {
+            reads.fetch_add(1, Ordering::SeqCst);
+            Ok("select 1".to_string())
+        };
+
+        cache.get_with("models/a.sql", read).unwrap();
+        shared.get_with("models/a.sql", read).unwrap();
+
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,230 @@
+//! Routes findings to the right team's channel based on dbt model ownership.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Owner (or dbt `group`) name to Slack webhook URL.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct OwnerRoutingTable {
+    pub webhooks: HashMap<String, String>,
+    pub default_webhook: String,
+}
+
+/// Which [`Notifier`] a [`NotifyConfig`] renders payloads for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyPlatform {
+    Slack,
+    Teams,
+}
+
+/// Org-declared destination for review findings, routed by model ownership
+/// (see [`route_by_owner`]) instead of always posting the whole report to
+/// one place.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct NotifyConfig {
+    pub platform: NotifyPlatform,
+    pub routing: OwnerRoutingTable,
+}
+
+impl NotifyConfig {
+    /// The [`Notifier`] matching [`Self::platform`].
+    pub fn notifier(&self) -> Box<dyn Notifier> {
+        match self.platform {
+            NotifyPlatform::Slack => Box::new(SlackNotifier),
+            NotifyPlatform::Teams => Box::new(TeamsNotifier),
+        }
+    }
+}
+
+/// A finding on a single model, ready to be grouped by owner.
+#[derive(Debug, Clone)]
+pub struct OwnedFinding {
+    pub model: String,
+    pub owner: Option<String>,
+    pub message: String,
+}
+
+/// A summary message routed to one destination webhook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutedMessage {
+    pub webhook: String,
+    pub summary: String,
+}
+
+/// Groups `findings` by owner and produces one summary message per
+/// destination webhook. Models with no owner metadata route to
+/// `routing.default_webhook`.
+pub fn route_by_owner(
+    findings: &[OwnedFinding],
+    routing: &OwnerRoutingTable,
+) -> Vec<RoutedMessage> {
+    let mut by_webhook: HashMap<String, Vec<String>> = HashMap::new();
+
+    for finding in findings {
+        let webhook = finding
+            .owner
+            .as_ref()
+            .and_then(|owner| routing.webhooks.get(owner))
+            .cloned()
+            .unwrap_or_else(|| routing.default_webhook.clone());
+
+        by_webhook
+            .entry(webhook)
+            .or_default()
+            .push(format!("{}: {}", finding.model, finding.message));
+    }
+
+    let mut messages: Vec<RoutedMessage> = by_webhook
+        .into_iter()
+        .map(|(webhook, lines)| RoutedMessage {
+            webhook,
+            summary: lines.join("\n"),
+        })
+        .collect();
+    messages.sort_by(|a, b| a.webhook.cmp(&b.webhook));
+    messages
+}
+
+/// Renders a [`RoutedMessage`] into the destination-specific JSON body a
+/// webhook POST would carry. This crate has no HTTP client (the same seam
+/// as [`crate::github::GitHubTransport`]), so a notifier's job stops at
+/// building the payload — actually posting it to the webhook URL is the
+/// caller's responsibility.
+pub trait Notifier {
+    fn render(&self, message: &RoutedMessage) -> Value;
+}
+
+/// Slack incoming webhooks expect a flat `blocks` list; a summary becomes
+/// one markdown section block.
+pub struct SlackNotifier;
+
+impl Notifier for SlackNotifier {
+    fn render(&self, message: &RoutedMessage) -> Value {
+        json!({
+            "blocks": [{
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": message.summary },
+            }],
+        })
+    }
+}
+
+/// Microsoft Teams incoming webhooks expect an Adaptive Card wrapped in an
+/// `attachments` envelope, unlike Slack's flat `blocks` list.
+pub struct TeamsNotifier;
+
+impl Notifier for TeamsNotifier {
+    fn render(&self, message: &RoutedMessage) -> Value {
+        json!({
+            "type": "message",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "content": {
+                    "type": "AdaptiveCard",
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "version": "1.4",
+                    "body": [{
+                        "type": "TextBlock",
+                        "text": message.summary,
+                        "wrap": true,
+                    }],
+                },
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_differently_owned_models_produce_two_routed_messages() {
+        let routing = OwnerRoutingTable {
+            webhooks: HashMap::from([
+                (
+                    "data-eng".to_string(),
+                    "https://hooks.example/data-eng".to_string(),
+                ),
+                (
+                    "marketing".to_string(),
+                    "https://hooks.example/marketing".to_string(),
+                ),
+            ]),
+            default_webhook: "https://hooks.example/default".to_string(),
+        };
+
+        let findings = vec![
+            OwnedFinding {
+                model: "orders_summary".to_string(),
+                owner: Some("data-eng".to_string()),
+                message: "missing not_null test".to_string(),
+            },
+            OwnedFinding {
+                model: "campaign_performance".to_string(),
+                owner: Some("marketing".to_string()),
+                message: "SELECT * detected".to_string(),
+            },
+        ];
+
+        let routed = route_by_owner(&findings, &routing);
+
+        assert_eq!(routed.len(), 2);
+        assert!(routed
+            .iter()
+            .any(|m| m.webhook == "https://hooks.example/data-eng"));
+        assert!(routed
+            .iter()
+            .any(|m| m.webhook == "https://hooks.example/marketing"));
+    }
+
+    fn sample_message() -> RoutedMessage {
+        RoutedMessage {
+            webhook: "https://hooks.example/data-eng".to_string(),
+            summary: "orders_summary: missing not_null test".to_string(),
+        }
+    }
+
+    #[test]
+    fn slack_notifier_renders_a_single_markdown_section_block() {
+        let payload = SlackNotifier.render(&sample_message());
+
+        assert_eq!(
+            payload["blocks"][0]["text"]["text"],
+            "orders_summary: missing not_null test"
+        );
+        assert_eq!(payload["blocks"][0]["type"], "section");
+    }
+
+    #[test]
+    fn teams_notifier_wraps_an_adaptive_card_in_an_attachment() {
+        let payload = TeamsNotifier.render(&sample_message());
+
+        assert_eq!(
+            payload["attachments"][0]["contentType"],
+            "application/vnd.microsoft.card.adaptive"
+        );
+        assert_eq!(
+            payload["attachments"][0]["content"]["body"][0]["text"],
+            "orders_summary: missing not_null test"
+        );
+    }
+
+    #[test]
+    fn notify_config_picks_the_notifier_matching_its_platform() {
+        let config = NotifyConfig {
+            platform: NotifyPlatform::Teams,
+            routing: OwnerRoutingTable::default(),
+        };
+
+        let payload = config.notifier().render(&sample_message());
+
+        assert_eq!(
+            payload["attachments"][0]["contentType"],
+            "application/vnd.microsoft.card.adaptive"
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! Coordinates the individual agents into a single review run.
+//!
+//! Currently covers just enough to decide whether a PR gets full per-model
+//! LLM analysis or falls back to the cheaper summary-only mode; the rest of
+//! agent coordination lives inline in each agent module for now (see
+//! [`crate::agents`]).
+
+use crate::config::AgentKind;
+use crate::lineage::LineageGraph;
+use crate::report::{Priority, Recommendation};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// How many changed models' agent analysis runs concurrently unless the
+/// caller overrides it. This crate has no async runtime (see
+/// [`crate::cancellation`]), so "concurrent" here means real OS threads
+/// capped at this many at a time, the same bounded-concurrency approach
+/// [`crate::bulk::analyze_repo`] uses for PRs.
+pub const DEFAULT_MAX_PARALLEL_AGENTS: usize = 4;
+
+/// Above how many changed models a PR falls back to summary-only analysis:
+/// aggregate statistics and risk, no per-model LLM calls. Kept conservative
+/// since LLM context and cost scale with model count.
+pub const DEFAULT_SUMMARY_MODE_THRESHOLD: usize = 200;
+
+/// Whether a PR gets full per-model analysis or the cheaper aggregate-only
+/// fallback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisMode {
+    /// Full per-model LLM analysis.
+    Detailed,
+    /// Aggregate statistics and risk only: the PR exceeded the
+    /// detailed-analysis threshold, so per-model LLM analysis is skipped.
+    Summary {
+        changed_model_count: usize,
+        threshold: usize,
+    },
+}
+
+impl AnalysisMode {
+    /// Picks [`Detailed`](Self::Detailed) or [`Summary`](Self::Summary) for a
+    /// PR touching `changed_model_count` models against `threshold`.
+    pub fn resolve(changed_model_count: usize, threshold: usize) -> Self {
+        if changed_model_count > threshold {
+            AnalysisMode::Summary {
+                changed_model_count,
+                threshold,
+            }
+        } else {
+            AnalysisMode::Detailed
+        }
+    }
+
+    /// A note for the report explaining why detailed analysis was skipped,
+    /// or `None` in [`Detailed`](Self::Detailed) mode.
+    pub fn note(&self) -> Option<String> {
+        match self {
+            AnalysisMode::Summary { changed_model_count, threshold } => Some(format!(
+                "PR touches {changed_model_count} models, exceeding the detailed-analysis threshold of \
+                 {threshold}; showing aggregate statistics and risk only."
+            )),
+            AnalysisMode::Detailed => None,
+        }
+    }
+}
+
+/// Runs `analyze_model` once per changed model in [`AnalysisMode::Detailed`];
+/// in [`AnalysisMode::Summary`] mode, skips per-model analysis entirely.
+pub fn run_detailed_or_summary<T>(
+    changed_models: &[String],
+    threshold: usize,
+    analyze_model: impl Fn(&str) -> T,
+) -> (AnalysisMode, Vec<T>) {
+    let mode = AnalysisMode::resolve(changed_models.len(), threshold);
+    let results = match mode {
+        AnalysisMode::Detailed => changed_models.iter().map(|m| analyze_model(m)).collect(),
+        AnalysisMode::Summary { .. } => Vec::new(),
+    };
+    (mode, results)
+}
+
+/// Flags the case where a PR clearly touches `.sql` model files but
+/// [`crate::manifest::discover_changed_models`] mapped none of them to a
+/// manifest node. In practice this almost always means the manifest is
+/// stale (`dbt compile` wasn't re-run) or the changed paths don't match the
+/// project the manifest was built from — a silent false-negative that would
+/// otherwise let impact analysis report a deceptively clean PR.
+pub fn stale_manifest_anomaly(
+    changed_files: &[String],
+    changed_models: &[String],
+) -> Option<Recommendation> {
+    let touches_sql = changed_files.iter().any(|f| f.ends_with(".sql"));
+    if touches_sql && changed_models.is_empty() {
+        Some(Recommendation {
+            source: AgentKind::Impact,
+            message: "changed .sql files were found but zero models mapped to the manifest; \
+                      the manifest may be stale — re-run dbt compile before trusting this report"
+                .to_string(),
+            priority: Priority::High,
+            confidence: None,
+        })
+    } else {
+        None
+    }
+}
+
+/// Flags a manifest generated before the change under analysis was
+/// committed — the #1 cause of a wrong analysis, since every downstream
+/// agent trusts the manifest's lineage and config as ground truth.
+/// `manifest_generated_at` is the manifest's embedded `generated_at` (or its
+/// file mtime if that's unavailable) and `head_commit_at` is the analyzed
+/// commit's timestamp; both are caller-supplied so this stays a pure,
+/// easily testable comparison instead of reaching into the filesystem or
+/// shelling out to git itself.
+pub fn stale_artifact_anomaly(
+    manifest_generated_at: SystemTime,
+    head_commit_at: SystemTime,
+) -> Option<Recommendation> {
+    if manifest_generated_at < head_commit_at {
+        Some(Recommendation {
+            source: AgentKind::Impact,
+            message: "manifest artifacts are older than the changes being analyzed; \
+                      re-run dbt compile before trusting this report"
+                .to_string(),
+            priority: Priority::High,
+            confidence: None,
+        })
+    } else {
+        None
+    }
+}
+
+/// Escalates a [`stale_artifact_anomaly`] finding to a hard error when
+/// `require_fresh_artifacts` is set (see
+/// [`crate::config::RuntimeOptions::require_fresh_artifacts`]), instead of
+/// letting it pass through as just another report finding.
+pub fn enforce_fresh_artifacts(
+    anomaly: Option<&Recommendation>,
+    require_fresh_artifacts: bool,
+) -> Result<(), String> {
+    match anomaly {
+        Some(anomaly) if require_fresh_artifacts => Err(anomaly.message.clone()),
+        _ => Ok(()),
+    }
+}
+
+/// Like [`run_detailed_or_summary`], but in [`AnalysisMode::Detailed`] mode
+/// fans `analyze_model` out across real OS threads capped at
+/// `max_parallel_agents`, sharing one already-built [`LineageGraph`] across
+/// every thread instead of each agent invocation rebuilding it.
+pub fn run_detailed_or_summary_concurrent<T: Send>(
+    changed_models: &[String],
+    threshold: usize,
+    max_parallel_agents: usize,
+    graph: Arc<LineageGraph>,
+    analyze_model: impl Fn(&Arc<LineageGraph>, &str) -> T + Sync,
+) -> (AnalysisMode, Vec<T>) {
+    let mode = AnalysisMode::resolve(changed_models.len(), threshold);
+    let results = match mode {
+        AnalysisMode::Detailed => {
+            let max_parallel_agents = max_parallel_agents.max(1);
+            let mut results = Vec::with_capacity(changed_models.len());
+            for chunk in changed_models.chunks(max_parallel_agents) {
+                let chunk_results: Vec<T> = std::thread::scope(|scope| {
+                    let handles: Vec<_> = chunk
+                        .iter()
+                        .map(|m| scope.spawn(|| analyze_model(&graph, m)))
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|h| h.join().expect("agent analysis thread panicked"))
+                        .collect()
+                });
+                results.extend(chunk_results);
+            }
+            results
+        }
+        AnalysisMode::Summary { .. } => Vec::new(),
+    };
+    (mode, results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::sync::Mutex;
+
+    #[test]
+    fn a_600_model_pr_triggers_summary_mode_and_skips_llm_calls() {
+        let changed_models: Vec<String> =
+            (0..600).map(|i| format!("model.trill_shop.m{i}")).collect();
+        let llm_calls = Cell::new(0u32);
+
+        let (mode, results) =
+            run_detailed_or_summary(&changed_models, DEFAULT_SUMMARY_MODE_THRESHOLD, |_model| {
+                llm_calls.set(llm_calls.get() + 1);
+                "analysis"
+            });
+
+        assert_eq!(
+            mode,
+            AnalysisMode::Summary {
+                changed_model_count: 600,
+                threshold: DEFAULT_SUMMARY_MODE_THRESHOLD
+            }
+        );
+        assert_eq!(
+            llm_calls.get(),
+            0,
+            "summary mode must not call the per-model analyzer"
+        );
+        assert!(results.is_empty());
+        assert!(mode.note().unwrap().contains("600"));
+    }
+
+    #[test]
+    fn a_small_pr_stays_in_detailed_mode() {
+        let changed_models = vec!["model.trill_shop.stg_orders".to_string()];
+
+        let (mode, results) =
+            run_detailed_or_summary(&changed_models, DEFAULT_SUMMARY_MODE_THRESHOLD, |m| {
+                m.to_string()
+            });
+
+        assert_eq!(mode, AnalysisMode::Detailed);
+        assert_eq!(results, vec!["model.trill_shop.stg_orders".to_string()]);
+        assert!(mode.note().is_none());
+    }
+
+    #[test]
+    fn changed_sql_files_that_match_no_manifest_node_raise_a_stale_manifest_warning() {
+        let changed_files = vec!["models/marts/new_model_not_in_manifest.sql".to_string()];
+
+        let warning =
+            stale_manifest_anomaly(&changed_files, &[]).expect("should flag a stale manifest");
+
+        assert_eq!(warning.priority, Priority::High);
+        assert!(warning.message.contains("dbt compile"));
+    }
+
+    #[test]
+    fn changed_sql_files_that_do_map_to_models_raise_no_warning() {
+        let changed_files = vec!["models/staging/stg_orders.sql".to_string()];
+        let changed_models = vec!["model.trill_shop.stg_orders".to_string()];
+
+        assert!(stale_manifest_anomaly(&changed_files, &changed_models).is_none());
+    }
+
+    #[test]
+    fn a_schema_yml_only_pr_with_no_mapped_models_is_not_a_stale_manifest_anomaly() {
+        let changed_files = vec!["models/staging/stg_orders.yml".to_string()];
+
+        assert!(
+            stale_manifest_anomaly(&changed_files, &[]).is_none(),
+            "no .sql files changed, so nothing to flag"
+        );
+    }
+
+    #[test]
+    fn a_manifest_older_than_the_analyzed_change_triggers_the_warning() {
+        let head_commit_at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000);
+        let manifest_generated_at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(900);
+
+        let warning = stale_artifact_anomaly(manifest_generated_at, head_commit_at)
+            .expect("a manifest older than the analyzed commit should be flagged");
+
+        assert_eq!(warning.priority, Priority::High);
+        assert!(warning.message.contains("dbt compile"));
+    }
+
+    #[test]
+    fn a_manifest_generated_after_the_analyzed_change_raises_no_warning() {
+        let head_commit_at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000);
+        let manifest_generated_at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_100);
+
+        assert!(stale_artifact_anomaly(manifest_generated_at, head_commit_at).is_none());
+    }
+
+    #[test]
+    fn a_stale_artifact_warning_is_only_a_hard_error_under_require_fresh_artifacts() {
+        let head_commit_at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_000);
+        let manifest_generated_at = std::time::UNIX_EPOCH + std::time::Duration::from_secs(900);
+        let anomaly = stale_artifact_anomaly(manifest_generated_at, head_commit_at);
+
+        assert!(enforce_fresh_artifacts(anomaly.as_ref(), false).is_ok());
+        assert!(enforce_fresh_artifacts(anomaly.as_ref(), true).is_err());
+        assert!(enforce_fresh_artifacts(None, true).is_ok());
+    }
+
+    #[test]
+    fn all_agents_receive_the_same_shared_graph_instance() {
+        let graph = Arc::new(LineageGraph::from_models(Vec::new()));
+        let addresses: Arc<Mutex<Vec<usize>>> = Arc::new(Mutex::new(Vec::new()));
+        let changed_models: Vec<String> =
+            (0..8).map(|i| format!("model.trill_shop.m{i}")).collect();
+
+        let (mode, results) = run_detailed_or_summary_concurrent(
+            &changed_models,
+            DEFAULT_SUMMARY_MODE_THRESHOLD,
+            DEFAULT_MAX_PARALLEL_AGENTS,
+            Arc::clone(&graph),
+            |g, model| {
+                addresses.lock().unwrap().push(Arc::as_ptr(g) as usize);
+                model.to_string()
+            },
+        );
+
+        assert_eq!(mode, AnalysisMode::Detailed);
+        assert_eq!(results.len(), 8);
+        let addrs = addresses.lock().unwrap();
+        assert_eq!(addrs.len(), 8);
+        assert!(
+            addrs.iter().all(|&a| a == Arc::as_ptr(&graph) as usize),
+            "every agent invocation should have received the same graph instance, not a rebuilt copy"
+        );
+    }
+
+    #[test]
+    fn a_600_model_pr_in_concurrent_mode_still_skips_llm_calls() {
+        let graph = Arc::new(LineageGraph::from_models(Vec::new()));
+        let changed_models: Vec<String> =
+            (0..600).map(|i| format!("model.trill_shop.m{i}")).collect();
+        let calls = Arc::new(Mutex::new(0u32));
+        let calls_for_closure = Arc::clone(&calls);
+
+        let (mode, results) = run_detailed_or_summary_concurrent(
+            &changed_models,
+            DEFAULT_SUMMARY_MODE_THRESHOLD,
+            DEFAULT_MAX_PARALLEL_AGENTS,
+            graph,
+            move |_g, _model| {
+                *calls_for_closure.lock().unwrap() += 1;
+                "analysis"
+            },
+        );
+
+        assert_eq!(
+            mode,
+            AnalysisMode::Summary {
+                changed_model_count: 600,
+                threshold: DEFAULT_SUMMARY_MODE_THRESHOLD
+            }
+        );
+        assert_eq!(*calls.lock().unwrap(), 0);
+        assert!(results.is_empty());
+    }
+}
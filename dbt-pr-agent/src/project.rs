@@ -0,0 +1,193 @@
+//! Project layout, as declared in `dbt_project.yml`.
+//!
+//! Path-based classification elsewhere in this crate (which changed files
+//! are models vs. macros, for instance) should read the project's
+//! configured roots via [`get_project_info`] rather than hard-coding dbt's
+//! defaults (`models/`, `macros/`) — a project with a custom `model-paths`
+//! (e.g. `transform/`) would otherwise be misclassified.
+
+use serde::Deserialize;
+use std::path::Path;
+
+/// The directories dbt resolves models, sources, and macros from, as
+/// declared in `dbt_project.yml`. Falls back to dbt's own defaults for any
+/// key the project doesn't set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectPaths {
+    pub model_paths: Vec<String>,
+    pub macro_paths: Vec<String>,
+}
+
+impl Default for ProjectPaths {
+    fn default() -> Self {
+        Self {
+            model_paths: vec!["models".to_string()],
+            macro_paths: vec!["macros".to_string()],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+struct DbtProjectYml {
+    #[serde(rename = "model-paths", default)]
+    model_paths: Option<Vec<String>>,
+    #[serde(rename = "macro-paths", default)]
+    macro_paths: Option<Vec<String>>,
+}
+
+/// Parses the `model-paths`/`macro-paths` keys out of a `dbt_project.yml`
+/// document, defaulting any key the project doesn't set. A malformed
+/// document falls back to dbt's defaults entirely rather than failing the
+/// whole analysis over a layout detail.
+pub fn parse_project_paths(yaml: &str) -> ProjectPaths {
+    let defaults = ProjectPaths::default();
+    let parsed: DbtProjectYml = match serde_yaml::from_str(yaml) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            log::warn!("failed to parse dbt_project.yml for project paths: {e}");
+            return defaults;
+        }
+    };
+
+    ProjectPaths {
+        model_paths: parsed.model_paths.unwrap_or(defaults.model_paths),
+        macro_paths: parsed.macro_paths.unwrap_or(defaults.macro_paths),
+    }
+}
+
+/// Reads and parses `project_dir`'s `dbt_project.yml`. Falls back to dbt's
+/// defaults, with a warning, when the file is missing or unreadable — a
+/// project without a checked-out `dbt_project.yml` on hand (e.g. only a
+/// manifest was fetched) shouldn't stop analysis.
+pub fn get_project_info(project_dir: &Path) -> ProjectPaths {
+    match std::fs::read_to_string(project_dir.join("dbt_project.yml")) {
+        Ok(yaml) => parse_project_paths(&yaml),
+        Err(e) => {
+            log::warn!("could not read dbt_project.yml, assuming default project layout: {e}");
+            ProjectPaths::default()
+        }
+    }
+}
+
+/// Which part of the project a changed file belongs to, per [`ProjectPaths`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathLayer {
+    Model,
+    Macro,
+    Other,
+}
+
+fn is_under_any(roots: &[String], path: &str) -> bool {
+    roots.iter().any(|root| {
+        let root = root.trim_end_matches('/');
+        path == root || path.starts_with(&format!("{root}/"))
+    })
+}
+
+/// Classifies `path` (a repo-relative path, as found on [`crate::github::ChangedFile::path`])
+/// against `paths`'s configured roots.
+pub fn classify_path(paths: &ProjectPaths, path: &str) -> PathLayer {
+    if is_under_any(&paths.model_paths, path) {
+        PathLayer::Model
+    } else if is_under_any(&paths.macro_paths, path) {
+        PathLayer::Macro
+    } else {
+        PathLayer::Other
+    }
+}
+
+/// Normalizes a finding's file path to be repo-root-relative.
+///
+/// Manifest-derived paths ([`crate::manifest::ModelInfo::original_file_path`])
+/// are relative to the dbt project root, which in a monorepo often lives in
+/// a subdirectory of the git repo (e.g. `analytics/`) rather than at its
+/// root; GitHub-returned paths ([`crate::github::ChangedFile::path`]) are
+/// already repo-root-relative. Mixing the two in the same report means
+/// GitHub annotations and SARIF output can point at a path that doesn't
+/// resolve in the UI. `project_subdir` is the project's location relative to
+/// the repo root (empty string when the dbt project *is* the repo root); a
+/// `path` that already starts with it is left untouched.
+pub fn to_repo_relative_path(project_subdir: &str, path: &str) -> String {
+    let project_subdir = project_subdir.trim_matches('/');
+    if project_subdir.is_empty() || path.starts_with(&format!("{project_subdir}/")) {
+        return path.to_string();
+    }
+    format!("{project_subdir}/{path}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_dbts_standard_layout_when_dbt_project_yml_is_absent() {
+        let paths = get_project_info(Path::new("/nonexistent/path/for/this/test"));
+        assert_eq!(paths, ProjectPaths::default());
+    }
+
+    #[test]
+    fn a_custom_model_paths_key_is_honored() {
+        let yaml = "name: trill_shop\nmodel-paths: [\"transform\"]\n";
+
+        let paths = parse_project_paths(yaml);
+
+        assert_eq!(paths.model_paths, vec!["transform".to_string()]);
+        assert_eq!(
+            classify_path(&paths, "transform/staging/stg_orders.sql"),
+            PathLayer::Model
+        );
+        assert_eq!(
+            classify_path(&paths, "models/staging/stg_orders.sql"),
+            PathLayer::Other
+        );
+    }
+
+    #[test]
+    fn an_unset_key_falls_back_to_the_dbt_default() {
+        let yaml = "name: trill_shop\nmacro-paths: [\"custom_macros\"]\n";
+
+        let paths = parse_project_paths(yaml);
+
+        assert_eq!(paths.model_paths, vec!["models".to_string()]);
+        assert_eq!(paths.macro_paths, vec!["custom_macros".to_string()]);
+    }
+
+    #[test]
+    fn a_macro_path_change_is_classified_as_macro_not_model() {
+        let paths = ProjectPaths::default();
+        assert_eq!(
+            classify_path(&paths, "macros/cents_to_dollars.sql"),
+            PathLayer::Macro
+        );
+    }
+
+    #[test]
+    fn malformed_yaml_falls_back_to_defaults_instead_of_erroring() {
+        let paths = parse_project_paths("model-paths: [transform");
+        assert_eq!(paths, ProjectPaths::default());
+    }
+
+    #[test]
+    fn a_project_relative_path_in_a_monorepo_subdir_is_normalized_to_the_repo_root_path() {
+        assert_eq!(
+            to_repo_relative_path("analytics", "models/staging/stg_orders.sql"),
+            "analytics/models/staging/stg_orders.sql"
+        );
+    }
+
+    #[test]
+    fn a_path_already_repo_root_relative_is_left_untouched() {
+        assert_eq!(
+            to_repo_relative_path("analytics", "analytics/models/staging/stg_orders.sql"),
+            "analytics/models/staging/stg_orders.sql"
+        );
+    }
+
+    #[test]
+    fn no_project_subdir_leaves_the_path_untouched() {
+        assert_eq!(
+            to_repo_relative_path("", "models/staging/stg_orders.sql"),
+            "models/staging/stg_orders.sql"
+        );
+    }
+}
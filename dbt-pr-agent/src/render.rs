@@ -0,0 +1,363 @@
+//! Rendering findings for terminal/text output, as opposed to the GitHub
+//! review comment format in [`crate::github`].
+
+use crate::agents::performance::ModelExecution;
+use crate::agents::quality::QualityIssue;
+use crate::artifacts::CatalogNode;
+use crate::lineage::LineageGraph;
+use crate::manifest::ModelInfo;
+use crate::severity::Severity;
+
+/// Renders impact analysis as plain text for CLI users without a browser.
+pub struct TextFormatter {
+    /// Downstream depth beyond which a branch is collapsed to a single
+    /// "… (N more)" line instead of being spelled out node by node.
+    pub max_depth: usize,
+}
+
+impl TextFormatter {
+    /// Renders the downstream impact of `root` as an indented ASCII tree,
+    /// one line per model, indented two spaces per depth level.
+    pub fn render_impact_tree(&self, graph: &LineageGraph, root: &str) -> String {
+        let mut downstream = graph.all_downstream_with_depth(root);
+        downstream.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+
+        let mut lines = vec![root.to_string()];
+        let mut collapsed = 0usize;
+
+        for (unique_id, depth) in downstream {
+            if depth > self.max_depth {
+                collapsed += 1;
+                continue;
+            }
+            let indent = "  ".repeat(depth);
+            lines.push(format!("{indent}└─ {unique_id}"));
+        }
+
+        if collapsed > 0 {
+            let indent = "  ".repeat(self.max_depth + 1);
+            lines.push(format!("{indent}└─ … ({collapsed} more)"));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Renders everything the agent knows about `model` from artifacts —
+/// materialization, upstream/downstream deps, columns, tags/meta, and
+/// historical execution time when available — for the `explain-model`
+/// subcommand's ad-hoc introspection. `catalog`/`execution` are `None` when
+/// the caller didn't have (or couldn't match) a `catalog.json`/
+/// `run_results.json` entry for this model.
+pub fn explain_model(
+    model: &ModelInfo,
+    graph: &LineageGraph,
+    catalog: Option<&CatalogNode>,
+    execution: Option<&ModelExecution>,
+) -> String {
+    let mut lines = vec![
+        format!("{} ({})", model.unique_id, model.name),
+        format!("materialized: {:?}", model.materialized),
+    ];
+
+    if model.depends_on.is_empty() {
+        lines.push("upstream dependencies: none".to_string());
+    } else {
+        lines.push(format!(
+            "upstream dependencies ({}): {}",
+            model.depends_on.len(),
+            model.depends_on.join(", ")
+        ));
+    }
+
+    let direct_dependents = graph.direct_dependent_count(&model.unique_id);
+    let total_dependents = graph.all_downstream(&model.unique_id).len();
+    lines.push(format!(
+        "downstream dependents: {direct_dependents} direct, {total_dependents} total"
+    ));
+
+    match catalog {
+        Some(catalog) => lines.push(format!("columns: {}", catalog.columns.len())),
+        None => lines.push("columns: unknown (no catalog.json entry for this model)".to_string()),
+    }
+
+    if model.tags.is_empty() {
+        lines.push("tags: none".to_string());
+    } else {
+        lines.push(format!("tags: {}", model.tags.join(", ")));
+    }
+
+    match model.owner.as_deref() {
+        Some(owner) => lines.push(format!("owner: {owner}")),
+        None => lines.push("owner: unknown".to_string()),
+    }
+
+    match execution {
+        Some(execution) => lines.push(format!(
+            "last execution time: {:.1}s",
+            execution.execution_time
+        )),
+        None => lines.push(
+            "last execution time: unknown (no run_results.json entry for this model)".to_string(),
+        ),
+    }
+
+    lines.join("\n")
+}
+
+/// Renders `context_lines` lines above and below `line_number` (1-indexed,
+/// matching a diff hunk's new-file numbering) from `source` as a fenced
+/// code block with line-number gutters, so a finding anchored to a single
+/// line can be understood without opening the file. `line_number` itself is
+/// included in the range. Line numbers past either end of `source` are
+/// clamped rather than treated as an error, since a finding near the top or
+/// bottom of a file is the common case, not an edge case to reject.
+pub fn render_context_block(source: &str, line_number: u32, context_lines: usize) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let target = line_number.max(1) as usize;
+    let start = target.saturating_sub(context_lines).max(1);
+    let end = (target + context_lines).min(lines.len());
+
+    let mut block = String::from("```sql\n");
+    for (i, line) in lines.iter().enumerate().take(end).skip(start - 1) {
+        block.push_str(&format!("{:>4} | {line}\n", i + 1));
+    }
+    block.push_str("```");
+    block
+}
+
+/// Returns `true` when running inside a GitHub Actions job, per the
+/// `GITHUB_ACTIONS` variable Actions sets on every runner. Used to
+/// auto-select [`GithubActionsFormatter`] when `--output` isn't given
+/// explicitly.
+pub fn github_actions_active() -> bool {
+    std::env::var("GITHUB_ACTIONS").as_deref() == Ok("true")
+}
+
+/// Renders findings as GitHub Actions workflow commands
+/// (`::error`/`::warning file=...,line=...::message`), so they show up as
+/// inline annotations in the Actions UI and job log without a separate
+/// upload step. See <https://docs.github.com/actions/using-workflows/workflow-commands-for-github-actions#setting-an-error-message>.
+pub struct GithubActionsFormatter;
+
+impl GithubActionsFormatter {
+    /// Maps [`Severity`] to the Actions annotation level: only `critical`
+    /// findings — the ones that actually fail the gate by default — are
+    /// surfaced as `::error`; everything else is a `::warning` so it's
+    /// visible in the Actions UI without failing the job on its own.
+    fn command(severity: Severity) -> &'static str {
+        match severity {
+            Severity::Critical => "error",
+            Severity::High | Severity::Medium | Severity::Low => "warning",
+        }
+    }
+
+    /// Renders a single issue as one workflow command line. `line_number` is
+    /// omitted from the command when the issue couldn't be anchored to a
+    /// specific line.
+    pub fn render_issue(&self, issue: &QualityIssue) -> String {
+        let command = Self::command(issue.severity);
+        match issue.line_number {
+            Some(line) => format!(
+                "::{command} file={},line={line}::{}",
+                issue.file_path, issue.message
+            ),
+            None => format!("::{command} file={}::{}", issue.file_path, issue.message),
+        }
+    }
+
+    /// Renders every issue as one workflow command per line, ready to
+    /// `println!` straight into the job log.
+    pub fn render(&self, issues: &[QualityIssue]) -> String {
+        issues
+            .iter()
+            .map(|issue| self.render_issue(issue))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::{Access, Materialization, ModelInfo};
+    use std::collections::HashMap;
+
+    fn model(id: &str, depends_on: &[&str]) -> ModelInfo {
+        ModelInfo {
+            unique_id: id.to_string(),
+            name: id.to_string(),
+            package_name: "trill_shop".to_string(),
+            materialized: Materialization::Table,
+            depends_on: depends_on.iter().map(|s| s.to_string()).collect(),
+            original_file_path: format!("models/{id}.sql"),
+            patch_path: None,
+            owner: None,
+            group: None,
+            access: Access::default(),
+            tags: Vec::new(),
+            meta: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn depth_two_model_is_indented_under_its_parent() {
+        let graph = LineageGraph::from_models(vec![
+            model("a", &[]),
+            model("b", &["a"]),
+            model("c", &["b"]),
+        ]);
+
+        let text = TextFormatter { max_depth: 5 }.render_impact_tree(&graph, "a");
+
+        let b_line = text.lines().find(|l| l.contains('b')).unwrap();
+        let c_line = text.lines().find(|l| l.contains('c')).unwrap();
+        let b_indent = b_line.len() - b_line.trim_start_matches(' ').len();
+        let c_indent = c_line.len() - c_line.trim_start_matches(' ').len();
+
+        assert!(
+            c_indent > b_indent,
+            "depth-2 model 'c' should be indented further than depth-1 model 'b'"
+        );
+    }
+
+    #[test]
+    fn branches_beyond_the_depth_cap_are_collapsed() {
+        let graph = LineageGraph::from_models(vec![
+            model("a", &[]),
+            model("b", &["a"]),
+            model("c", &["b"]),
+        ]);
+
+        let text = TextFormatter { max_depth: 1 }.render_impact_tree(&graph, "a");
+
+        assert!(text.contains("… (1 more)"));
+        assert!(!text.contains('c'));
+    }
+
+    #[test]
+    fn a_high_severity_issue_renders_as_a_warning_annotation() {
+        let issue = QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: Some(12),
+            message: "avoid SELECT *".to_string(),
+            severity: Severity::High,
+        };
+
+        let line = GithubActionsFormatter.render_issue(&issue);
+
+        assert_eq!(
+            line,
+            "::warning file=models/marts/orders.sql,line=12::avoid SELECT *"
+        );
+    }
+
+    #[test]
+    fn a_critical_severity_issue_renders_as_an_error_annotation() {
+        let issue = QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: Some(12),
+            message: "missing unique_key".to_string(),
+            severity: Severity::Critical,
+        };
+
+        let line = GithubActionsFormatter.render_issue(&issue);
+
+        assert_eq!(
+            line,
+            "::error file=models/marts/orders.sql,line=12::missing unique_key"
+        );
+    }
+
+    #[test]
+    fn an_issue_with_no_line_number_omits_the_line_field() {
+        let issue = QualityIssue {
+            file_path: "models/marts/orders.sql".to_string(),
+            line_number: None,
+            message: "whole-file finding".to_string(),
+            severity: Severity::Medium,
+        };
+
+        let line = GithubActionsFormatter.render_issue(&issue);
+
+        assert_eq!(
+            line,
+            "::warning file=models/marts/orders.sql::whole-file finding"
+        );
+    }
+
+    #[test]
+    fn explanation_includes_direct_dependents_and_column_count() {
+        let graph = LineageGraph::from_models(vec![
+            model("a", &[]),
+            model("b", &["a"]),
+            model("c", &["b"]),
+        ]);
+        let catalog = CatalogNode {
+            unique_id: "a".to_string(),
+            columns: HashMap::from([
+                (
+                    "id".to_string(),
+                    crate::artifacts::ColumnStats {
+                        data_type: "INT64".to_string(),
+                    },
+                ),
+                (
+                    "name".to_string(),
+                    crate::artifacts::ColumnStats {
+                        data_type: "STRING".to_string(),
+                    },
+                ),
+            ]),
+            stats: HashMap::new(),
+        };
+
+        let text = explain_model(graph.node("a").unwrap(), &graph, Some(&catalog), None);
+
+        assert!(text.contains("1 direct"));
+        assert!(text.contains("2 total"));
+        assert!(text.contains("columns: 2"));
+    }
+
+    #[test]
+    fn context_block_includes_lines_above_and_below_the_finding_line() {
+        let source = (1..=10)
+            .map(|n| format!("line {n}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let block = render_context_block(&source, 5, 2);
+
+        assert!(block.contains("line 3"));
+        assert!(block.contains("line 4"));
+        assert!(block.contains("line 5"));
+        assert!(block.contains("line 6"));
+        assert!(block.contains("line 7"));
+        assert!(!block.contains("line 2"));
+        assert!(!block.contains("line 8"));
+    }
+
+    #[test]
+    fn context_block_near_the_top_of_the_file_clamps_instead_of_underflowing() {
+        let source = (1..=10)
+            .map(|n| format!("line {n}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let block = render_context_block(&source, 1, 3);
+
+        assert!(block.contains("line 1"));
+        assert!(block.contains("line 4"));
+        assert!(!block.contains("line 5"));
+    }
+
+    #[test]
+    fn missing_catalog_and_execution_data_is_reported_as_unknown_not_omitted() {
+        let graph = LineageGraph::from_models(vec![model("a", &[])]);
+
+        let text = explain_model(graph.node("a").unwrap(), &graph, None, None);
+
+        assert!(text.contains("columns: unknown"));
+        assert!(text.contains("last execution time: unknown"));
+    }
+}
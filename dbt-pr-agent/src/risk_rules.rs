@@ -0,0 +1,261 @@
+//! Org-declared risk escalation rules, evaluated against a run's report
+//! metrics without a recompile.
+//!
+//! Deliberately not a general expression language: a condition is one or
+//! more `field operator value` clauses joined by `AND` (no `OR`, no
+//! nesting, no arithmetic). That covers every case this feature is meant
+//! for, without pulling in an expression-evaluator dependency for a crate
+//! that otherwise has none.
+
+use crate::severity::Severity;
+use crate::warehouse::Warehouse;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The report metrics a [`RiskRule`] condition can reference.
+#[derive(Debug, Clone)]
+pub struct RiskRuleContext {
+    pub downstream_models: usize,
+    /// Estimated cost increase as a percentage of current spend.
+    pub cost_pct: f64,
+    /// Weighted test coverage, 0.0-1.0.
+    pub coverage: f64,
+    pub warehouse: Warehouse,
+}
+
+/// An org-declared rule: when `condition` matches, the computed risk level
+/// escalates (never de-escalates) to `escalate_to`, with `reason` surfaced
+/// in the report so the escalation isn't a mystery.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct RiskRule {
+    /// e.g. `"downstream_models > 20 AND warehouse == bigquery"`.
+    pub condition: String,
+    pub escalate_to: Severity,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Gt,
+    Lt,
+    Ge,
+    Le,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FieldValue {
+    Number(f64),
+    Text(String),
+}
+
+struct Clause {
+    field: String,
+    comparison: Comparison,
+    value: FieldValue,
+}
+
+/// Longest operators first, so `>=` isn't misparsed as `>` followed by `=`.
+const OPERATORS: [(&str, Comparison); 6] = [
+    (">=", Comparison::Ge),
+    ("<=", Comparison::Le),
+    ("==", Comparison::Eq),
+    ("!=", Comparison::Ne),
+    (">", Comparison::Gt),
+    ("<", Comparison::Lt),
+];
+
+fn parse_clause(clause: &str) -> Result<Clause, String> {
+    let clause = clause.trim();
+    let (op, comparison) = OPERATORS
+        .iter()
+        .find(|(op, _)| clause.contains(op))
+        .ok_or_else(|| format!("no comparison operator found in condition clause: '{clause}'"))?;
+
+    let (field, rhs) = clause
+        .split_once(op)
+        .expect("operator was just matched via contains");
+    let rhs = rhs.trim().trim_matches('"');
+    let value = match rhs.parse::<f64>() {
+        Ok(n) => FieldValue::Number(n),
+        Err(_) => FieldValue::Text(rhs.to_ascii_lowercase()),
+    };
+
+    Ok(Clause {
+        field: field.trim().to_string(),
+        comparison: *comparison,
+        value,
+    })
+}
+
+fn split_on_and(condition: &str) -> Vec<String> {
+    let mut clauses = Vec::new();
+    let mut current = Vec::new();
+    for word in condition.split_whitespace() {
+        if word.eq_ignore_ascii_case("and") {
+            clauses.push(current.join(" "));
+            current = Vec::new();
+        } else {
+            current.push(word);
+        }
+    }
+    clauses.push(current.join(" "));
+    clauses
+}
+
+fn parse_condition(condition: &str) -> Result<Vec<Clause>, String> {
+    split_on_and(condition)
+        .iter()
+        .map(|clause| parse_clause(clause))
+        .collect()
+}
+
+fn field_value(context: &RiskRuleContext, field: &str) -> Result<FieldValue, String> {
+    match field {
+        "downstream_models" => Ok(FieldValue::Number(context.downstream_models as f64)),
+        "cost_pct" => Ok(FieldValue::Number(context.cost_pct)),
+        "coverage" => Ok(FieldValue::Number(context.coverage)),
+        "warehouse" => Ok(FieldValue::Text(
+            context.warehouse.to_string().to_ascii_lowercase(),
+        )),
+        other => Err(format!("unknown risk rule field: '{other}'")),
+    }
+}
+
+fn evaluate_clause(clause: &Clause, context: &RiskRuleContext) -> Result<bool, String> {
+    let lhs = field_value(context, &clause.field)?;
+    match (&lhs, &clause.value) {
+        (FieldValue::Number(l), FieldValue::Number(r)) => Ok(match clause.comparison {
+            Comparison::Gt => l > r,
+            Comparison::Lt => l < r,
+            Comparison::Ge => l >= r,
+            Comparison::Le => l <= r,
+            Comparison::Eq => l == r,
+            Comparison::Ne => l != r,
+        }),
+        (FieldValue::Text(l), FieldValue::Text(r)) => match clause.comparison {
+            Comparison::Eq => Ok(l == r),
+            Comparison::Ne => Ok(l != r),
+            _ => Err(format!(
+                "'{}' only supports == and != on text fields",
+                clause.field
+            )),
+        },
+        _ => Err(format!("type mismatch evaluating '{}'", clause.field)),
+    }
+}
+
+/// True when every clause in `rule.condition` holds against `context`.
+pub fn evaluate_rule(rule: &RiskRule, context: &RiskRuleContext) -> Result<bool, String> {
+    let clauses = parse_condition(&rule.condition)?;
+    for clause in &clauses {
+        if !evaluate_clause(clause, context)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Evaluates every rule against `context`, escalating `computed` (never
+/// de-escalating below it) to the highest matching rule's `escalate_to` and
+/// collecting every matching rule's reason for the report. An invalid rule
+/// is logged and skipped rather than failing the whole analysis.
+pub fn apply_risk_rules(
+    rules: &[RiskRule],
+    context: &RiskRuleContext,
+    computed: Severity,
+) -> (Severity, Vec<String>) {
+    let mut severity = computed;
+    let mut reasons = Vec::new();
+
+    for rule in rules {
+        match evaluate_rule(rule, context) {
+            Ok(true) => {
+                severity = severity.max(rule.escalate_to);
+                reasons.push(rule.reason.clone());
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("skipping invalid risk rule '{}': {e}", rule.condition),
+        }
+    }
+
+    (severity, reasons)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> RiskRuleContext {
+        RiskRuleContext {
+            downstream_models: 25,
+            cost_pct: 5.0,
+            coverage: 0.8,
+            warehouse: Warehouse::BigQuery,
+        }
+    }
+
+    #[test]
+    fn a_custom_rule_escalates_risk_to_critical_with_its_reason_shown() {
+        let rules = vec![RiskRule {
+            condition: "downstream_models > 20 AND warehouse == bigquery".to_string(),
+            escalate_to: Severity::Critical,
+            reason: "high fan-out change on BigQuery".to_string(),
+        }];
+
+        let (severity, reasons) = apply_risk_rules(&rules, &context(), Severity::Low);
+
+        assert_eq!(severity, Severity::Critical);
+        assert_eq!(reasons, vec!["high fan-out change on BigQuery".to_string()]);
+    }
+
+    #[test]
+    fn a_rule_never_de_escalates_below_the_computed_severity() {
+        let rules = vec![RiskRule {
+            condition: "downstream_models > 100".to_string(),
+            escalate_to: Severity::Low,
+            reason: "irrelevant".to_string(),
+        }];
+
+        let (severity, reasons) = apply_risk_rules(&rules, &context(), Severity::High);
+
+        assert_eq!(
+            severity,
+            Severity::High,
+            "computed severity is already above the rule's escalation target"
+        );
+        assert!(
+            reasons.is_empty(),
+            "the rule's condition didn't match, so it shouldn't contribute a reason"
+        );
+    }
+
+    #[test]
+    fn a_non_matching_rule_leaves_severity_untouched() {
+        let rules = vec![RiskRule {
+            condition: "warehouse == snowflake".to_string(),
+            escalate_to: Severity::Critical,
+            reason: "irrelevant".to_string(),
+        }];
+
+        let (severity, reasons) = apply_risk_rules(&rules, &context(), Severity::Medium);
+
+        assert_eq!(severity, Severity::Medium);
+        assert!(reasons.is_empty());
+    }
+
+    #[test]
+    fn an_invalid_rule_is_skipped_rather_than_erroring() {
+        let rules = vec![RiskRule {
+            condition: "nonexistent_field > 5".to_string(),
+            escalate_to: Severity::Critical,
+            reason: "irrelevant".to_string(),
+        }];
+
+        let (severity, reasons) = apply_risk_rules(&rules, &context(), Severity::Low);
+
+        assert_eq!(severity, Severity::Low);
+        assert!(reasons.is_empty());
+    }
+}
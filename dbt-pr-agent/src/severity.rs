@@ -0,0 +1,100 @@
+//! The severity scale shared by every agent's findings and by the gate
+//! threshold ([`crate::config::FailOn`]) that compares against them.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// Maps free-form LLM-provided category strings (e.g. `"Syntax Error"`) to a
+/// [`Severity`], so a team's prompts can introduce new categories without a
+/// code change. Category lookups are case-insensitive; anything not in the
+/// map resolves to a configurable default and is logged, rather than
+/// silently landing in the wrong bucket.
+#[derive(Debug, Clone)]
+pub struct SeverityMapping {
+    by_category: HashMap<String, Severity>,
+    default: Severity,
+}
+
+impl SeverityMapping {
+    /// An empty mapping that resolves every category to `default`.
+    pub fn new(default: Severity) -> Self {
+        Self {
+            by_category: HashMap::new(),
+            default,
+        }
+    }
+
+    /// Registers `category` (case-insensitive) to resolve to `severity`.
+    pub fn with_category(mut self, category: impl Into<String>, severity: Severity) -> Self {
+        self.by_category
+            .insert(category.into().to_ascii_lowercase(), severity);
+        self
+    }
+
+    /// The mapping this crate ships with by default, covering the
+    /// categories our own prompts currently emit.
+    pub fn default_mapping() -> Self {
+        Self::new(Severity::Low)
+            .with_category("syntax error", Severity::Critical)
+            .with_category("breaking change", Severity::Critical)
+            .with_category("data quality", Severity::High)
+            .with_category("performance", Severity::Medium)
+            .with_category("style", Severity::Low)
+    }
+
+    /// Resolves `category` to a [`Severity`], falling back to the configured
+    /// default bucket for anything unrecognized.
+    pub fn resolve(&self, category: &str) -> Severity {
+        match self.by_category.get(&category.to_ascii_lowercase()) {
+            Some(severity) => *severity,
+            None => {
+                log::warn!("unrecognized LLM category '{category}'; routing to the default severity bucket");
+                self.default
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_custom_category_is_routed_per_config() {
+        let mapping =
+            SeverityMapping::new(Severity::Low).with_category("data leak", Severity::Critical);
+
+        assert_eq!(
+            mapping.resolve("Data Leak"),
+            Severity::Critical,
+            "lookup should be case-insensitive"
+        );
+    }
+
+    #[test]
+    fn unrecognized_categories_fall_back_to_the_default_bucket() {
+        let mapping = SeverityMapping::new(Severity::Medium);
+
+        assert_eq!(mapping.resolve("something new"), Severity::Medium);
+    }
+
+    #[test]
+    fn default_mapping_matches_the_categories_our_prompts_emit() {
+        let mapping = SeverityMapping::default_mapping();
+
+        assert_eq!(mapping.resolve("Syntax Error"), Severity::Critical);
+        assert_eq!(mapping.resolve("style"), Severity::Low);
+    }
+}
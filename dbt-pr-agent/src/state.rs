@@ -0,0 +1,192 @@
+//! Comparison between two manifest snapshots — dbt's `state:modified`
+//! selector, reimplemented against our own [`ManifestNode`] shape — so a
+//! caller can find changed models from actual manifest content (config,
+//! compiled SQL, dependencies) instead of matching changed file paths as
+//! [`crate::manifest::discover_changed_models`] does. This catches
+//! config-only and macro-driven changes that never touch a model's own
+//! `.sql`/`.yml` file and so are invisible to path matching.
+//!
+//! Manifest nodes here carry no column-level detail (see
+//! [`crate::artifacts::ManifestNode`]), so unlike real dbt `state:modified`
+//! this can't detect a column added/removed with no other config change.
+
+use crate::artifacts::ManifestNode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Why [`compare_manifests`] considers a model changed between two
+/// snapshots. A model can have more than one reason; see
+/// [`ModelStateChange::reasons`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateChangeReason {
+    /// Present in `head` but not `base`.
+    Added,
+    /// Present in `base` but not `head`.
+    Removed,
+    /// `config` (materialization, tags, meta, partitioning, ...) differs.
+    ConfigChanged,
+    /// `compiled_code` differs, e.g. a macro it calls changed.
+    CodeChanged,
+    /// The set of upstream `unique_id`s it depends on differs.
+    DependenciesChanged,
+}
+
+/// One model's diff between two manifest snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelStateChange {
+    pub unique_id: String,
+    pub reasons: Vec<StateChangeReason>,
+}
+
+/// Diffs `base` against `head` and returns every model that changed, in
+/// manifest order among `head` (added/changed) followed by anything only
+/// `base` had (removed).
+pub fn compare_manifests(base: &[ManifestNode], head: &[ManifestNode]) -> Vec<ModelStateChange> {
+    let base_by_id: HashMap<&str, &ManifestNode> =
+        base.iter().map(|n| (n.unique_id.as_str(), n)).collect();
+    let head_by_id: HashMap<&str, &ManifestNode> =
+        head.iter().map(|n| (n.unique_id.as_str(), n)).collect();
+
+    let mut changes = Vec::new();
+
+    for node in head {
+        let Some(before) = base_by_id.get(node.unique_id.as_str()) else {
+            changes.push(ModelStateChange {
+                unique_id: node.unique_id.clone(),
+                reasons: vec![StateChangeReason::Added],
+            });
+            continue;
+        };
+
+        let mut reasons = Vec::new();
+        if before.config != node.config {
+            reasons.push(StateChangeReason::ConfigChanged);
+        }
+        if before.compiled_code != node.compiled_code {
+            reasons.push(StateChangeReason::CodeChanged);
+        }
+        if before.depends_on.nodes != node.depends_on.nodes {
+            reasons.push(StateChangeReason::DependenciesChanged);
+        }
+        if !reasons.is_empty() {
+            changes.push(ModelStateChange {
+                unique_id: node.unique_id.clone(),
+                reasons,
+            });
+        }
+    }
+
+    for node in base {
+        if !head_by_id.contains_key(node.unique_id.as_str()) {
+            changes.push(ModelStateChange {
+                unique_id: node.unique_id.clone(),
+                reasons: vec![StateChangeReason::Removed],
+            });
+        }
+    }
+
+    changes
+}
+
+/// Just the `unique_id`s from [`compare_manifests`], for callers (impact
+/// analysis, the `analyze-pr` changed-model set) that only need "what
+/// changed" and not why.
+pub fn changed_model_ids(base: &[ManifestNode], head: &[ManifestNode]) -> Vec<String> {
+    compare_manifests(base, head)
+        .into_iter()
+        .map(|c| c.unique_id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifacts::{DependsOn, NodeConfig};
+
+    fn node(unique_id: &str, materialized: Option<&str>, compiled_code: Option<&str>) -> ManifestNode {
+        ManifestNode {
+            unique_id: unique_id.to_string(),
+            name: unique_id.rsplit('.').next().unwrap_or_default().to_string(),
+            resource_type: "model".to_string(),
+            original_file_path: format!("models/{unique_id}.sql"),
+            patch_path: None,
+            depends_on: DependsOn::default(),
+            config: NodeConfig {
+                materialized: materialized.map(str::to_string),
+                ..NodeConfig::default()
+            },
+            compiled_code: compiled_code.map(str::to_string),
+            access: None,
+        }
+    }
+
+    #[test]
+    fn a_config_only_change_is_detected_even_though_no_file_path_changed() {
+        let base = vec![node("model.trill_shop.orders", Some("view"), Some("select 1"))];
+        let head = vec![node(
+            "model.trill_shop.orders",
+            Some("incremental"),
+            Some("select 1"),
+        )];
+
+        let changes = compare_manifests(&base, &head);
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].unique_id, "model.trill_shop.orders");
+        assert_eq!(changes[0].reasons, vec![StateChangeReason::ConfigChanged]);
+    }
+
+    #[test]
+    fn a_macro_driven_code_change_is_detected_with_no_config_change() {
+        let base = vec![node("model.trill_shop.orders", Some("view"), Some("select 1"))];
+        let head = vec![node("model.trill_shop.orders", Some("view"), Some("select 2"))];
+
+        let changes = compare_manifests(&base, &head);
+
+        assert_eq!(changes[0].reasons, vec![StateChangeReason::CodeChanged]);
+    }
+
+    #[test]
+    fn an_identical_node_produces_no_change() {
+        let base = vec![node("model.trill_shop.orders", Some("view"), Some("select 1"))];
+        let head = base.clone();
+
+        assert!(compare_manifests(&base, &head).is_empty());
+    }
+
+    #[test]
+    fn a_new_model_in_head_is_reported_as_added() {
+        let base: Vec<ManifestNode> = Vec::new();
+        let head = vec![node("model.trill_shop.orders", Some("view"), Some("select 1"))];
+
+        let changes = compare_manifests(&base, &head);
+
+        assert_eq!(changes[0].reasons, vec![StateChangeReason::Added]);
+    }
+
+    #[test]
+    fn a_model_removed_in_head_is_reported_as_removed() {
+        let base = vec![node("model.trill_shop.orders", Some("view"), Some("select 1"))];
+        let head: Vec<ManifestNode> = Vec::new();
+
+        let changes = compare_manifests(&base, &head);
+
+        assert_eq!(changes[0].reasons, vec![StateChangeReason::Removed]);
+    }
+
+    #[test]
+    fn changed_model_ids_returns_only_the_unique_ids() {
+        let base = vec![node("model.trill_shop.orders", Some("view"), Some("select 1"))];
+        let head = vec![node(
+            "model.trill_shop.orders",
+            Some("incremental"),
+            Some("select 1"),
+        )];
+
+        assert_eq!(
+            changed_model_ids(&base, &head),
+            vec!["model.trill_shop.orders".to_string()]
+        );
+    }
+}
@@ -0,0 +1,50 @@
+//! The warehouse a dbt project targets, and how to detect it.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Warehouse {
+    BigQuery,
+    Snowflake,
+    Redshift,
+    Postgres,
+    Other(String),
+}
+
+impl Warehouse {
+    /// Maps a dbt profile `type` (as found in `profiles.yml` / `dbt_project.yml`
+    /// target config) to a [`Warehouse`].
+    pub fn detect(profile_type: &str) -> Self {
+        match profile_type.to_ascii_lowercase().as_str() {
+            "bigquery" => Warehouse::BigQuery,
+            "snowflake" => Warehouse::Snowflake,
+            "redshift" => Warehouse::Redshift,
+            "postgres" => Warehouse::Postgres,
+            other => Warehouse::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Warehouse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warehouse::BigQuery => write!(f, "BigQuery"),
+            Warehouse::Snowflake => write!(f, "Snowflake"),
+            Warehouse::Redshift => write!(f, "Redshift"),
+            Warehouse::Postgres => write!(f, "Postgres"),
+            Warehouse::Other(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_known_warehouses_case_insensitively() {
+        assert_eq!(Warehouse::detect("BigQuery"), Warehouse::BigQuery);
+        assert_eq!(Warehouse::detect("snowflake"), Warehouse::Snowflake);
+    }
+}
@@ -0,0 +1,102 @@
+//! Coalesces local filesystem save events into a single re-analysis trigger
+//! for the `watch` subcommand's IDE-like feedback loop.
+//!
+//! Actually watching `models/` needs a long-running OS-level file watcher
+//! (the `notify` crate) and a run loop; neither exists in this crate yet (no
+//! async runtime, no daemon process). This module models the trigger policy
+//! only, the same way [`crate::debounce`] models webhook coalescing: a
+//! caller feeds it save events as they arrive from wherever the watcher
+//! lives and polls [`SaveWatcher::take_settled`] to decide which paths are
+//! ready to re-analyze.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Tracks pending saves per path and coalesces rapid re-saves of the same
+/// file (e.g. an editor's autosave firing mid-edit) into one re-analysis
+/// once `quiet_period` has elapsed since the last save.
+pub struct SaveWatcher {
+    quiet_period: Duration,
+    pending: Mutex<HashMap<PathBuf, Instant>>,
+}
+
+impl SaveWatcher {
+    pub fn new(quiet_period: Duration) -> Self {
+        Self {
+            quiet_period,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records a save event for `path`, resetting its quiet-period clock.
+    pub fn record_save(&self, path: PathBuf, now: Instant) {
+        self.pending.lock().unwrap().insert(path, now);
+    }
+
+    /// Removes and returns every pending path whose quiet period has
+    /// elapsed as of `now`, so each save triggers exactly one re-analysis
+    /// rather than one per keystroke-driven autosave.
+    pub fn take_settled(&self, now: Instant) -> Vec<PathBuf> {
+        let mut pending = self.pending.lock().unwrap();
+        let (settled, still_pending): (HashMap<_, _>, HashMap<_, _>) =
+            pending.drain().partition(|(_, last_saved_at)| {
+                now.duration_since(*last_saved_at) >= self.quiet_period
+            });
+        *pending = still_pending;
+        settled.into_keys().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_single_save_triggers_exactly_one_reanalysis_once_settled() {
+        let watcher = SaveWatcher::new(Duration::from_millis(50));
+        let t0 = Instant::now();
+        let path = PathBuf::from("models/staging/stg_orders.sql");
+
+        watcher.record_save(path.clone(), t0);
+
+        assert!(
+            watcher
+                .take_settled(t0 + Duration::from_millis(10))
+                .is_empty(),
+            "still within the quiet period"
+        );
+
+        let settled = watcher.take_settled(t0 + Duration::from_millis(60));
+        assert_eq!(settled, vec![path.clone()]);
+
+        assert!(
+            watcher
+                .take_settled(t0 + Duration::from_millis(200))
+                .is_empty(),
+            "a settled save must not trigger a second re-analysis"
+        );
+    }
+
+    #[test]
+    fn rapid_resaves_of_the_same_file_coalesce_into_one_trigger() {
+        let watcher = SaveWatcher::new(Duration::from_millis(50));
+        let t0 = Instant::now();
+        let path = PathBuf::from("models/marts/orders_summary.sql");
+
+        watcher.record_save(path.clone(), t0);
+        watcher.record_save(path.clone(), t0 + Duration::from_millis(20));
+        watcher.record_save(path.clone(), t0 + Duration::from_millis(40));
+
+        assert!(
+            watcher
+                .take_settled(t0 + Duration::from_millis(70))
+                .is_empty(),
+            "still within the quiet period of the last save"
+        );
+
+        let settled = watcher.take_settled(t0 + Duration::from_millis(95));
+        assert_eq!(settled, vec![path]);
+    }
+}